@@ -0,0 +1,64 @@
+//! Serves LCDproc clients on port 13666 and prints the emulated 20x4 display on every change.
+//!
+//! Try it with `lcdproc -s localhost C L` or `telnet localhost 13666`.
+
+extern crate clerk;
+
+use std::thread;
+use std::time::Duration;
+
+use clerk::{BusSimulator, DefaultLines, Delay, Display, DisplayControlBuilder,
+            FunctionSetBuilder, LcdProcError, LcdProcServer, LineNumber};
+
+const WIDTH: u8 = 20;
+const HEIGHT: u8 = 4;
+
+struct NoDelay;
+
+impl Delay for NoDelay {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn print_display(bus: &BusSimulator) {
+    let starts = [0x00, 0x40, WIDTH, 0x40 + WIDTH];
+
+    println!("+{}+", "-".repeat(usize::from(WIDTH)));
+    for &start in &starts[..usize::from(HEIGHT)] {
+        let line: String = (start..start + WIDTH)
+            .map(|address| match bus.ddram(address) {
+                byte @ 0x20..=0x7d => char::from(byte),
+                0xff => '#',
+                _ => '*',
+            })
+            .collect();
+        println!("|{}|", line);
+    }
+    println!("+{}+", "-".repeat(usize::from(WIDTH)));
+}
+
+fn main() -> Result<(), LcdProcError<std::convert::Infallible>> {
+    let bus = BusSimulator::new();
+    let lcd: Display<_, DefaultLines> = Display::new(bus.pins_4lines().into_connection(NoDelay));
+
+    lcd.init(FunctionSetBuilder::default().set_line_number(LineNumber::Two))
+        .map_err(LcdProcError::Display)?;
+    lcd.set_display_control(&DisplayControlBuilder::default())
+        .map_err(LcdProcError::Display)?;
+
+    let mut server =
+        LcdProcServer::bind("127.0.0.1:13666", lcd, WIDTH, HEIGHT).map_err(LcdProcError::Io)?;
+    let mut shown = vec![];
+
+    loop {
+        server.handle_clients().map_err(LcdProcError::Io)?;
+        server.tick().map_err(LcdProcError::Display)?;
+
+        let ddram: Vec<u8> = (0..0x80).map(|address| bus.ddram(address)).collect();
+        if ddram != shown {
+            print_display(&bus);
+            shown = ddram;
+        }
+
+        thread::sleep(Duration::from_millis(125));
+    }
+}
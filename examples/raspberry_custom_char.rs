@@ -4,14 +4,14 @@ extern crate clerk;
 extern crate sysfs_gpio;
 
 use clerk::{CursorBlinking, CursorState, DataPins4Lines, DefaultLines, Display,
-            DisplayControlBuilder, DisplayState, FunctionSetBuilder, LineNumber, Pins,
+            DisplayControlBuilder, DisplayState, Error, FunctionSetBuilder, LineNumber, Pins,
             SeekCgRamFrom, SetFrom};
 
 mod utils;
 use utils::ExternPin;
 use utils::CustomDelay;
 
-fn main() {
+fn main() -> Result<(), Error<sysfs_gpio::Error>> {
     let pins = Pins {
         register_select: ExternPin::new(2),
         read: ExternPin::new(3),
@@ -24,30 +24,35 @@ fn main() {
         },
     };
 
-    let lcd: Display<_, DefaultLines> = Display::new(pins.into_connection::<CustomDelay>());
+    let lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(CustomDelay));
 
-    lcd.init(FunctionSetBuilder::default().set_line_number(LineNumber::Two));
+    lcd.init(FunctionSetBuilder::default().set_line_number(LineNumber::Two))?;
 
     lcd.set_display_control(
         DisplayControlBuilder::default()
             .set_display(DisplayState::On)
             .set_cursor(CursorState::Off)
             .set_cursor_blinking(CursorBlinking::On),
-    );
+    )?;
 
-    let mut lcd = lcd.set_cgram_address(0);
+    let mut lcd = lcd.set_cgram_address(0).map_err(|(_, e)| e)?;
     let character = [
         0b0_1110, 0b1_0101, 0b1_1111, 0b1_0101, 0b0_1110, 0b0_0100, 0b0_0100, 0b1_1111
     ];
-    lcd.write_message(str::from_utf8(&character).unwrap());
+    lcd.write_message(str::from_utf8(&character).unwrap())?;
 
-    lcd.seek(SeekCgRamFrom::Home(0));
+    lcd.seek(SeekCgRamFrom::Home(0))?;
     println!("Created custom char is: ");
     for _ in 0..8 {
-        let value = lcd.read_byte();
+        let value = lcd.read_byte()?;
         println!("{:#08b}", value)
     }
 
-    let mut lcd = lcd.set_ddram_address(SetFrom::Home(0));
-    lcd.write(0);
+    let mut lcd = lcd.set_ddram_address(SetFrom::Home(0)).map_err(|(_, e)| e)?;
+    lcd.write(0)?;
+
+    // unexports the GPIOs
+    lcd.release()?;
+
+    Ok(())
 }
@@ -2,13 +2,13 @@ extern crate clerk;
 extern crate sysfs_gpio;
 
 use clerk::{CursorBlinking, CursorState, DataPins4Lines, DefaultLines, Display,
-            DisplayControlBuilder, DisplayState, FunctionSetBuilder, LineNumber, Pins, SeekFrom};
+            DisplayControlBuilder, DisplayState, Error, FunctionSetBuilder, LineNumber, Pins, SeekFrom};
 
 mod utils;
 use utils::ExternPin;
 use utils::CustomDelay;
 
-fn main() {
+fn main() -> Result<(), Error<sysfs_gpio::Error>> {
     let pins = Pins {
         register_select: ExternPin::new(2),
         read: ExternPin::new(3),
@@ -21,23 +21,28 @@ fn main() {
         },
     };
 
-    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection::<CustomDelay>());
+    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(CustomDelay));
 
-    lcd.init(FunctionSetBuilder::default().set_line_number(LineNumber::Two));
+    lcd.init(FunctionSetBuilder::default().set_line_number(LineNumber::Two))?;
 
     lcd.set_display_control(
         DisplayControlBuilder::default()
             .set_display(DisplayState::On)
             .set_cursor(CursorState::Off)
             .set_cursor_blinking(CursorBlinking::On),
-    );
+    )?;
 
-    lcd.write_message("Hello");
+    lcd.write_message("Hello")?;
 
     lcd.seek(SeekFrom::Line {
         line: DefaultLines::Two,
         offset: 5,
-    });
+    })?;
 
-    lcd.write_message("world!");
+    lcd.write_message("world!")?;
+
+    // unexports the GPIOs
+    lcd.release()?;
+
+    Ok(())
 }
@@ -2,13 +2,13 @@ extern crate clerk;
 extern crate sysfs_gpio;
 
 use clerk::{CursorBlinking, CursorState, DataPins4Lines, DefaultLines, Display,
-            DisplayControlBuilder, DisplayState, FunctionSetBuilder, Pins, SeekFrom};
+            DisplayControlBuilder, DisplayState, Error, FunctionSetBuilder, Pins, SeekFrom};
 
 mod utils;
 use utils::ExternPin;
 use utils::CustomDelay;
 
-fn main() {
+fn main() -> Result<(), Error<sysfs_gpio::Error>> {
     let pins = Pins {
         register_select: ExternPin::new(2),
         read: ExternPin::new(3),
@@ -21,32 +21,37 @@ fn main() {
         },
     };
 
-    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection::<CustomDelay>());
+    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(CustomDelay));
 
-    lcd.init(&FunctionSetBuilder::default());
+    lcd.init(&FunctionSetBuilder::default())?;
 
     lcd.set_display_control(
         DisplayControlBuilder::default()
             .set_display(DisplayState::On)
             .set_cursor(CursorState::Off)
             .set_cursor_blinking(CursorBlinking::On),
-    );
+    )?;
 
-    lcd.write_message("Hello");
+    lcd.write_message("Hello")?;
 
-    lcd.seek(SeekFrom::Home(0));
-    let value = lcd.read_byte();
+    lcd.seek(SeekFrom::Home(0))?;
+    let value = lcd.read_byte()?;
     println!("Value is: {}", value as char);
 
-    let (busy_flag, address) = lcd.read_busy_flag();
+    let (busy_flag, address) = lcd.read_busy_flag()?;
     println!("Busy Flag: {}, Address: {}", busy_flag, address);
 
-    let value = lcd.read_byte();
+    let value = lcd.read_byte()?;
     println!("Value is: {}", value as char);
 
-    let (busy_flag, address) = lcd.read_busy_flag();
+    let (busy_flag, address) = lcd.read_busy_flag()?;
     println!("Busy Flag: {}, Address: {}", busy_flag, address);
 
-    lcd.seek(SeekFrom::Current(0));
-    lcd.write_message("llo World!");
+    lcd.seek(SeekFrom::Current(0))?;
+    lcd.write_message("llo World!")?;
+
+    // unexports the GPIOs
+    lcd.release()?;
+
+    Ok(())
 }
@@ -5,7 +5,15 @@ use clerk::Delay;
 pub struct CustomDelay;
 
 impl Delay for CustomDelay {
-    fn delay_ns(ns: u16) {
-        thread::sleep(time::Duration::new(0, u32::from(ns)));
+    fn delay_ns(&self, ns: u32) {
+        thread::sleep(time::Duration::from_nanos(u64::from(ns)));
+    }
+
+    fn delay_us(&self, us: u32) {
+        thread::sleep(time::Duration::from_micros(u64::from(us)));
+    }
+
+    fn delay_ms(&self, ms: u32) {
+        thread::sleep(time::Duration::from_millis(u64::from(ms)));
     }
 }
@@ -1,5 +1,3 @@
-use sysfs_gpio;
-
 use clerk::{Direction, DisplayHardwareLayer, Level};
 
 pub struct ExternPin(sysfs_gpio::Pin);
@@ -11,33 +9,35 @@ impl ExternPin {
 }
 
 impl DisplayHardwareLayer for ExternPin {
-    fn init(&self) {
-        self.0.export().unwrap();
+    type Error = sysfs_gpio::Error;
+
+    fn init(&self) -> Result<(), Self::Error> {
+        self.0.export()
     }
 
-    fn cleanup(&self) {
-        self.0.unexport().unwrap();
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        self.0.unexport()
     }
 
-    fn set_direction(&self, direction: Direction) {
+    fn set_direction(&self, direction: Direction) -> Result<(), Self::Error> {
         let native_direction = match direction {
             Direction::In => sysfs_gpio::Direction::In,
             Direction::Out => sysfs_gpio::Direction::Out,
         };
 
-        self.0.set_direction(native_direction).unwrap();
+        self.0.set_direction(native_direction)
     }
 
-    fn set_level(&self, level: Level) {
+    fn set_level(&self, level: Level) -> Result<(), Self::Error> {
         let value = match level {
             Level::High => 1,
             Level::Low => 0,
         };
 
-        self.0.set_value(value).unwrap();
+        self.0.set_value(value)
     }
 
-    fn get_value(&self) -> u8 {
-        self.0.get_value().unwrap()
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        self.0.get_value()
     }
 }
@@ -0,0 +1,310 @@
+//! Adapters for using [`embedded-hal`] pins and delays with clerk.
+//!
+//! Errors of the pins are reported as [`ErrorKind`], so that pins of different types can be
+//! used within the same connection.
+//!
+//! [`ErrorKind`]: https://docs.rs/embedded-hal/1/embedded_hal/digital/enum.ErrorKind.html
+//! [`embedded-hal`]: https://docs.rs/embedded-hal
+
+use core::cell::RefCell;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{Error, ErrorKind, InputPin, OutputPin};
+
+use crate::hal::{Delay, Direction, DisplayHardwareLayer, Level};
+
+/// Wraps an `embedded-hal` [`OutputPin`] as a [`DisplayHardwareLayer`].
+///
+/// Intended for pins which are only written, like RS, R/W and E.
+///
+/// Reading the pin or switching it to `Direction::In` fails with `ErrorKind::Other`.
+///
+/// [`OutputPin`]: https://docs.rs/embedded-hal/1/embedded_hal/digital/trait.OutputPin.html
+/// [`DisplayHardwareLayer`]: ../trait.DisplayHardwareLayer.html
+pub struct OutputPinAdapter<P>(RefCell<P>);
+
+impl<P> OutputPinAdapter<P> {
+    pub fn new(pin: P) -> Self {
+        OutputPinAdapter(RefCell::new(pin))
+    }
+
+    /// Returns the wrapped pin.
+    pub fn into_inner(self) -> P {
+        self.0.into_inner()
+    }
+}
+
+impl<P: OutputPin> DisplayHardwareLayer for OutputPinAdapter<P> {
+    type Error = ErrorKind;
+
+    fn set_direction(&self, direction: Direction) -> Result<(), ErrorKind> {
+        match direction {
+            Direction::Out => Ok(()),
+            // output pins can not be used as input
+            Direction::In => Err(ErrorKind::Other),
+        }
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), ErrorKind> {
+        set_level(&mut *self.0.borrow_mut(), level)
+    }
+
+    fn get_value(&self) -> Result<u8, ErrorKind> {
+        Err(ErrorKind::Other)
+    }
+}
+
+/// Wraps an `embedded-hal` [`InputPin`] as a [`DisplayHardwareLayer`].
+///
+/// Setting a level or switching the pin to `Direction::Out` fails with `ErrorKind::Other`.
+///
+/// [`InputPin`]: https://docs.rs/embedded-hal/1/embedded_hal/digital/trait.InputPin.html
+/// [`DisplayHardwareLayer`]: ../trait.DisplayHardwareLayer.html
+pub struct InputPinAdapter<P>(RefCell<P>);
+
+impl<P> InputPinAdapter<P> {
+    pub fn new(pin: P) -> Self {
+        InputPinAdapter(RefCell::new(pin))
+    }
+
+    /// Returns the wrapped pin.
+    pub fn into_inner(self) -> P {
+        self.0.into_inner()
+    }
+}
+
+impl<P: InputPin> DisplayHardwareLayer for InputPinAdapter<P> {
+    type Error = ErrorKind;
+
+    fn set_direction(&self, direction: Direction) -> Result<(), ErrorKind> {
+        match direction {
+            Direction::In => Ok(()),
+            // input pins can not be used as output
+            Direction::Out => Err(ErrorKind::Other),
+        }
+    }
+
+    fn set_level(&self, _: Level) -> Result<(), ErrorKind> {
+        Err(ErrorKind::Other)
+    }
+
+    fn get_value(&self) -> Result<u8, ErrorKind> {
+        get_value(&mut *self.0.borrow_mut())
+    }
+}
+
+/// Wraps an `embedded-hal` pin which is both [`InputPin`] and [`OutputPin`] as a
+/// [`DisplayHardwareLayer`], e.g. for the data pins of a readable display.
+///
+/// The pin is expected to be configured as open drain output with a pull-up resistor. Switching
+/// it to `Direction::In` sets it `High`, which releases the line, so that the display can drive
+/// it.
+///
+/// [`InputPin`]: https://docs.rs/embedded-hal/1/embedded_hal/digital/trait.InputPin.html
+/// [`OutputPin`]: https://docs.rs/embedded-hal/1/embedded_hal/digital/trait.OutputPin.html
+/// [`DisplayHardwareLayer`]: ../trait.DisplayHardwareLayer.html
+pub struct IoPinAdapter<P>(RefCell<P>);
+
+impl<P> IoPinAdapter<P> {
+    pub fn new(pin: P) -> Self {
+        IoPinAdapter(RefCell::new(pin))
+    }
+
+    /// Returns the wrapped pin.
+    pub fn into_inner(self) -> P {
+        self.0.into_inner()
+    }
+}
+
+impl<P: InputPin + OutputPin> DisplayHardwareLayer for IoPinAdapter<P> {
+    type Error = ErrorKind;
+
+    fn set_direction(&self, direction: Direction) -> Result<(), ErrorKind> {
+        if direction == Direction::In {
+            set_level(&mut *self.0.borrow_mut(), Level::High)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), ErrorKind> {
+        set_level(&mut *self.0.borrow_mut(), level)
+    }
+
+    fn get_value(&self) -> Result<u8, ErrorKind> {
+        get_value(&mut *self.0.borrow_mut())
+    }
+}
+
+fn set_level<P: OutputPin>(pin: &mut P, level: Level) -> Result<(), ErrorKind> {
+    match level {
+        Level::High => pin.set_high(),
+        Level::Low => pin.set_low(),
+    }.map_err(|e| e.kind())
+}
+
+fn get_value<P: InputPin>(pin: &mut P) -> Result<u8, ErrorKind> {
+    match pin.is_high() {
+        Ok(true) => Ok(1),
+        Ok(false) => Ok(0),
+        Err(e) => Err(e.kind()),
+    }
+}
+
+/// Uses an `embedded-hal` [`DelayNs`] implementation, e.g. a timer peripheral, as [`Delay`].
+///
+/// [`DelayNs`]: https://docs.rs/embedded-hal/1/embedded_hal/delay/trait.DelayNs.html
+/// [`Delay`]: ../trait.Delay.html
+pub struct DelayAdapter<D>(RefCell<D>);
+
+impl<D> DelayAdapter<D> {
+    pub fn new(delay: D) -> Self {
+        DelayAdapter(RefCell::new(delay))
+    }
+
+    /// Returns the wrapped delay.
+    pub fn into_inner(self) -> D {
+        self.0.into_inner()
+    }
+}
+
+impl<D: DelayNs> Delay for DelayAdapter<D> {
+    fn delay_ns(&self, ns: u32) {
+        self.0.borrow_mut().delay_ns(ns);
+    }
+
+    fn delay_us(&self, us: u32) {
+        self.0.borrow_mut().delay_us(us);
+    }
+
+    fn delay_ms(&self, ms: u32) {
+        self.0.borrow_mut().delay_ms(ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::convert::Infallible;
+
+    use embedded_hal::digital::ErrorType;
+
+    #[derive(Default)]
+    struct PinMock {
+        high: bool,
+    }
+
+    impl ErrorType for PinMock {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for PinMock {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.high = false;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.high = true;
+            Ok(())
+        }
+    }
+
+    impl InputPin for PinMock {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(self.high)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn output_pin_set_level() {
+        let pin = OutputPinAdapter::new(PinMock::default());
+
+        pin.set_direction(Direction::Out).unwrap();
+        pin.set_level(Level::High).unwrap();
+        assert!(pin.into_inner().high);
+    }
+
+    #[test]
+    fn output_pin_can_not_be_read() {
+        let pin = OutputPinAdapter::new(PinMock::default());
+
+        assert_eq!(pin.set_direction(Direction::In), Err(ErrorKind::Other));
+        assert_eq!(pin.get_value(), Err(ErrorKind::Other));
+    }
+
+    #[test]
+    fn input_pin_get_value() {
+        let pin = InputPinAdapter::new(PinMock { high: true });
+
+        pin.set_direction(Direction::In).unwrap();
+        assert_eq!(pin.get_value(), Ok(1));
+    }
+
+    #[test]
+    fn input_pin_can_not_be_written() {
+        let pin = InputPinAdapter::new(PinMock::default());
+
+        assert_eq!(pin.set_direction(Direction::Out), Err(ErrorKind::Other));
+        assert_eq!(pin.set_level(Level::High), Err(ErrorKind::Other));
+    }
+
+    #[test]
+    fn io_pin_releases_line_as_input() {
+        let pin = IoPinAdapter::new(PinMock::default());
+
+        pin.set_direction(Direction::Out).unwrap();
+        pin.set_level(Level::Low).unwrap();
+        assert_eq!(pin.get_value(), Ok(0));
+
+        pin.set_direction(Direction::In).unwrap();
+        assert_eq!(pin.get_value(), Ok(1));
+    }
+
+    struct BrokenPin;
+
+    impl ErrorType for BrokenPin {
+        type Error = ErrorKind;
+    }
+
+    impl OutputPin for BrokenPin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Err(ErrorKind::Other)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Err(ErrorKind::Other)
+        }
+    }
+
+    #[test]
+    fn output_pin_reports_error() {
+        let pin = OutputPinAdapter::new(BrokenPin);
+
+        assert_eq!(pin.set_level(Level::High), Err(ErrorKind::Other));
+    }
+
+    struct DelayMock(u32);
+
+    impl DelayNs for DelayMock {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += ns;
+        }
+    }
+
+    #[test]
+    fn delay() {
+        let delay = DelayAdapter::new(DelayMock(0));
+
+        delay.delay_ns(450);
+        delay.delay_us(2);
+        delay.delay_ms(1);
+
+        assert_eq!(delay.into_inner().0, 1_002_450);
+    }
+}
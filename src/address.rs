@@ -20,7 +20,7 @@ impl<T> From<Address<T>> for u8 {
     }
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(expl_impl_clone_on_copy))]
+#[allow(clippy::expl_impl_clone_on_copy)]
 impl<T> Clone for Address<T> {
     fn clone(&self) -> Self {
         *self
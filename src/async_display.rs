@@ -0,0 +1,322 @@
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use super::address::{Address, Overflow};
+use super::{DataLength, DisplayControlBuilder, EntryModeBuilder, FunctionSetBuilder, Home};
+use crate::async_hal::{AsyncInit, AsyncReceive, AsyncSend, AsyncWait};
+use crate::display::{clear_instruction, parse_busy_flag, CgRam, DdRam, SeekCgRamFrom, SeekFrom,
+                     SetFrom, ShiftTo, FIRST_INIT_WAIT_TIME, INIT_4BIT_INSTRUCTION,
+                     INIT_8BIT_INSTRUCTION, POWER_ON_WAIT_TIME, SECOND_INIT_WAIT_TIME};
+use crate::error::Error;
+use crate::hal::{ErrorType, ReadMode, WriteMode};
+use crate::text::TextLayout;
+
+pub type DdRamDisplay<P, U> = Display<P, U, DdRam>;
+
+/// The result of switching an async display from the RAM type `From` to `To`. On failure the
+/// unchanged display is returned together with the error, so that the connection is not lost.
+pub type SwitchRamResult<P, U, From, To> =
+    Result<Display<P, U, To>, (Display<P, U, From>, Error<<P as ErrorType>::Error>)>;
+
+/// A HD44780 compliant display driven by an async executor.
+///
+/// It mirrors the API of [`Display`], but awaits the execution time of every instruction, so
+/// that other tasks can run in the meantime.
+///
+/// [`Display`]: type.Display.html
+pub struct Display<P, U, RT>
+where
+    U: Into<Address<RT>> + Home,
+{
+    connection: P,
+    cursor_address: Cell<Address<RT>>,
+    text_layout: TextLayout,
+    _ram_type: PhantomData<RT>,
+    _line_marker: PhantomData<U>,
+}
+
+impl<P, U> Display<P, U, DdRam>
+where
+    U: Into<Address<DdRam>> + Home,
+{
+    /// Create a new `AsyncDisplay` using the given connection.
+    pub fn new(connection: P) -> Display<P, U, DdRam> {
+        Display {
+            connection,
+            cursor_address: Cell::new(Address::from(0)),
+            text_layout: TextLayout::default(),
+            _ram_type: PhantomData::<DdRam>,
+            _line_marker: PhantomData,
+        }
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: AsyncSend + AsyncWait,
+    U: Into<Address<RT>> + Home,
+{
+    /// Sends the given write operation and waits until it has been executed.
+    async fn execute(&self, mode: WriteMode) -> Result<(), Error<P::Error>> {
+        self.connection.send(mode).await.map_err(Error::Connection)?;
+        self.connection.wait(mode).await.map_err(Error::Connection)
+    }
+
+    async fn wait_us(&self, us: u32) -> Result<(), Error<P::Error>> {
+        self.connection.wait_us(us).await.map_err(Error::Connection)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    U: Into<Address<RT>> + Home,
+{
+    pub fn get_connection(self) -> P {
+        self.connection
+    }
+
+    /// Sets the number of columns and lines of the display, `write_message` truncates messages
+    /// after the number of columns. A display has 16 columns and 2 lines by default.
+    pub fn set_geometry(&mut self, columns: u8, lines: u8) {
+        self.text_layout.columns = columns;
+        self.text_layout.lines = lines;
+    }
+
+    fn switch_ram<T>(self, cursor_address: Address<T>) -> Display<P, U, T>
+    where
+        U: Into<Address<T>>,
+    {
+        Display {
+            connection: self.connection,
+            cursor_address: Cell::new(cursor_address),
+            text_layout: self.text_layout,
+            _ram_type: PhantomData::<T>,
+            _line_marker: PhantomData,
+        }
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: AsyncInit + AsyncSend + AsyncWait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
+    pub async fn init(&self, builder: &FunctionSetBuilder) -> Result<(), Error<P::Error>> {
+        self.connection.init().await.map_err(Error::Connection)?;
+
+        self.init_by_instruction(builder).await
+    }
+
+    async fn init_by_instruction(
+        &self,
+        builder: &FunctionSetBuilder,
+    ) -> Result<(), Error<P::Error>> {
+        // the busy flag can not be checked until the interface data length is set, so the init
+        // instructions have to wait for the times given in the datasheet
+        self.wait_us(POWER_ON_WAIT_TIME).await?;
+
+        // three single nibbles put the controller into 8-bit mode, whatever nibble phase it has
+        // been left in
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION).await?;
+        self.wait_us(FIRST_INIT_WAIT_TIME).await?;
+
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION).await?;
+        self.wait_us(SECOND_INIT_WAIT_TIME).await?;
+
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION).await?;
+        self.wait_us(SECOND_INIT_WAIT_TIME).await?;
+
+        if let DataLength::FourBit = builder.data_length() {
+            self.send_upper_nibble(INIT_4BIT_INSTRUCTION).await?;
+            self.wait_us(SECOND_INIT_WAIT_TIME).await?;
+        }
+
+        self.execute(WriteMode::Command(builder.build_command())).await?;
+
+        self.clear().await
+    }
+
+    async fn send_upper_nibble(&self, cmd: u8) -> Result<(), Error<P::Error>> {
+        self.connection.send_upper_nibble(cmd).await.map_err(Error::Connection)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: AsyncSend + AsyncWait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
+    /// Sets the entry mode of the display.
+    pub async fn set_entry_mode(&self, builder: &EntryModeBuilder) -> Result<(), Error<P::Error>> {
+        let cmd = WriteMode::Command(builder.build_command());
+        self.execute(cmd).await
+    }
+
+    /// Sets the display control settings.
+    pub async fn set_display_control(
+        &self,
+        builder: &DisplayControlBuilder,
+    ) -> Result<(), Error<P::Error>> {
+        let cmd = WriteMode::Command(builder.build_command());
+        self.execute(cmd).await
+    }
+
+    /// Shifts the cursor to the left or the right by the given offset.
+    pub async fn shift_cursor(&mut self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        let (offset, cmd) = direction.cursor_instruction();
+
+        if offset == 0 {
+            return Ok(());
+        }
+
+        self.cursor_address.set(direction.shift_address(self.cursor_address.get()));
+
+        self.raw_shift(offset, cmd).await
+    }
+
+    /// Shifts the display to the right or the left by the given offset.
+    pub async fn shift(&self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        let (offset, cmd) = direction.display_instruction();
+
+        self.raw_shift(offset, cmd).await
+    }
+
+    async fn raw_shift(&self, offset: u8, cmd: WriteMode) -> Result<(), Error<P::Error>> {
+        for _ in 0..offset {
+            self.execute(cmd).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Clears the entire display, sets the cursor to the home position and undo all display
+    /// shifts.
+    pub async fn clear(&self) -> Result<(), Error<P::Error>> {
+        self.execute(clear_instruction()).await?;
+        self.cursor_address.set(Address::from(0));
+
+        Ok(())
+    }
+
+    /// Writes the given byte to data or character generator RAM, depending on the previous
+    /// seek operation.
+    pub async fn write(&mut self, c: u8) -> Result<(), Error<P::Error>> {
+        self.execute(WriteMode::Data(c)).await?;
+        self.cursor_address.set(self.cursor_address.get() + Address::from(1));
+
+        Ok(())
+    }
+
+    /// Writes the given message to data or character generator RAM, depending on the previous
+    /// seek operation. The message is truncated after the number of columns set by
+    /// [`set_geometry()`].
+    ///
+    /// [`set_geometry()`]: #method.set_geometry
+    pub async fn write_message(&mut self, msg: &str) -> Result<(), Error<P::Error>> {
+        let columns = usize::from(self.text_layout.columns);
+
+        for c in msg.as_bytes().iter().take(columns) {
+            self.write(*c).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reading from the display is only available if its connection is able to receive data, i.e.
+/// it is not wired write-only.
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: AsyncReceive + AsyncWait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
+    /// Reads a single byte from data RAM.
+    ///
+    /// Afterwards it awaits the update of the address counter, which takes as long as writing
+    /// data, so that consecutive reads do not return stale characters.
+    pub async fn read_byte(&mut self) -> Result<u8, Error<P::Error>> {
+        let byte = self.connection
+            .receive(ReadMode::Data)
+            .await
+            .map_err(Error::Connection)?;
+        self.connection
+            .wait(WriteMode::Data(byte))
+            .await
+            .map_err(Error::Connection)?;
+        self.cursor_address.set(self.cursor_address.get() + Address::from(1));
+
+        Ok(byte)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: AsyncReceive,
+    U: Into<Address<RT>> + Home,
+{
+
+    /// Reads busy flag and the cursor's current address.
+    pub async fn read_busy_flag(&self) -> Result<(bool, u8), Error<P::Error>> {
+        let byte = self.connection
+            .receive(ReadMode::BusyFlag)
+            .await
+            .map_err(Error::Connection)?;
+
+        Ok(parse_busy_flag(byte))
+    }
+}
+
+impl<P, U> Display<P, U, DdRam>
+where
+    P: AsyncSend + AsyncWait,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Seeks to an offset in display data RAM.
+    pub async fn seek(&mut self, pos: SeekFrom<U>) -> Result<(), Error<P::Error>> {
+        let address = pos.address(self.cursor_address.get());
+        self.cursor_address.set(address);
+
+        self.execute(address.seek_instruction()).await
+    }
+
+    /// Switches to the character generator RAM (CGRAM) and set the cursor's
+    /// address to the given value.
+    pub async fn set_cgram_address(self, address: u8) -> SwitchRamResult<P, U, DdRam, CgRam> {
+        let ddram_address = self.cursor_address.get();
+        let mut cgram_display = self.switch_ram(Address::<CgRam>::from(0));
+
+        match cgram_display.seek(SeekCgRamFrom::Home(address)).await {
+            Ok(()) => Ok(cgram_display),
+            Err(e) => Err((cgram_display.switch_ram(ddram_address), e)),
+        }
+    }
+}
+
+impl<P, U> Display<P, U, CgRam>
+where
+    P: AsyncSend + AsyncWait,
+    U: Into<Address<CgRam>> + Into<Address<DdRam>> + Home,
+{
+    /// Seeks to an offset in character generator RAM.
+    pub async fn seek(&mut self, pos: SeekCgRamFrom) -> Result<(), Error<P::Error>> {
+        let address = pos.address(self.cursor_address.get());
+        self.cursor_address.set(address);
+
+        self.execute(address.seek_instruction()).await
+    }
+
+    /// Switches to the display data RAM (DDRAM) and set the cursor's address to
+    /// the given value.
+    pub async fn set_ddram_address(self, pos: SetFrom<U>) -> SwitchRamResult<P, U, CgRam, DdRam> {
+        let cgram_address = self.cursor_address.get();
+        let mut ddram_display = self.switch_ram(Address::<DdRam>::from(0));
+
+        match ddram_display.seek(pos.into()).await {
+            Ok(()) => Ok(ddram_display),
+            Err(e) => Err((ddram_display.switch_ram(cgram_address), e)),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Async counterparts of the connection traits, which allow other tasks to run while the display
+//! is executing an instruction.
+//!
+//! Only the waiting for an instruction's execution time is done asynchronously. The timing of
+//! the bus signals is in the range of nanoseconds and is still done by the blocking
+//! [`Delay::delay_ns`].
+//!
+//! [`Delay::delay_ns`]: trait.Delay.html#tymethod.delay_ns
+
+use crate::hal::{Delay, ErrorType, Init, ParallelConnection, ReadMode, Receive, Send, WriteMode};
+use crate::i2c_expander::I2cExpanderConnection;
+use crate::mcp230xx::Mcp230xxConnection;
+use crate::shift_register::ShiftRegisterConnection;
+
+/// This trait is used to provide an async initialization implementation for an [`AsyncDisplay`]
+/// connection.
+///
+/// [`AsyncDisplay`]: type.AsyncDisplay.html
+#[allow(async_fn_in_trait)]
+pub trait AsyncInit: ErrorType {
+    /// Initializes the connection.
+    async fn init(&self) -> Result<(), Self::Error>;
+}
+
+/// This trait is used to provide an async implementation for sending data via an
+/// [`AsyncDisplay`] connection.
+///
+/// [`AsyncDisplay`]: type.AsyncDisplay.html
+#[allow(async_fn_in_trait)]
+pub trait AsyncSend: ErrorType {
+    /// Sends data via the connection.
+    async fn send(&self, mode: WriteMode) -> Result<(), Self::Error>;
+
+    /// Sends only the upper nibble of a command on connections with a 4-bit interface, see
+    /// [`Send::send_upper_nibble`].
+    ///
+    /// [`Send::send_upper_nibble`]: trait.Send.html#method.send_upper_nibble
+    async fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        self.send(WriteMode::Command(cmd)).await
+    }
+}
+
+/// This trait is used to provide an async implementation for receiving data via an
+/// [`AsyncDisplay`] connection.
+///
+/// [`AsyncDisplay`]: type.AsyncDisplay.html
+#[allow(async_fn_in_trait)]
+pub trait AsyncReceive: ErrorType {
+    /// Receives data via the connection.
+    async fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error>;
+}
+
+/// This trait is used to provide an async implementation for waiting until an [`AsyncDisplay`]
+/// has executed an instruction.
+///
+/// [`AsyncDisplay`]: type.AsyncDisplay.html
+#[allow(async_fn_in_trait)]
+pub trait AsyncWait: ErrorType {
+    /// Waits until the given write operation has been executed.
+    async fn wait(&self, mode: WriteMode) -> Result<(), Self::Error>;
+
+    /// Waits for the given amount of microseconds.
+    async fn wait_us(&self, us: u32) -> Result<(), Self::Error>;
+}
+
+/// The async counterpart of [`Delay`], usually implemented on top of the timer of an async
+/// executor.
+///
+/// [`Delay`]: trait.Delay.html
+#[allow(async_fn_in_trait)]
+pub trait AsyncDelay: Delay {
+    /// Waits for the given amount of microseconds without blocking other tasks.
+    async fn delay_us(&self, us: u32);
+}
+
+/// Implements the async connection traits for a connection with a delay type parameter `T`, by
+/// delegating to its blocking implementations and awaiting the execution times via the
+/// `AsyncDelay` and `Timing` of the connection.
+macro_rules! impl_async_connection {
+    ($connection:ident<$($param:ident),+>) => {
+        impl<$($param),+> AsyncInit for $connection<$($param),+>
+        where
+            Self: Init,
+        {
+            async fn init(&self) -> Result<(), Self::Error> {
+                Init::init(self)
+            }
+        }
+
+        impl<$($param),+> AsyncSend for $connection<$($param),+>
+        where
+            Self: Send,
+        {
+            async fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+                Send::send(self, mode)
+            }
+
+            async fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+                Send::send_upper_nibble(self, cmd)
+            }
+        }
+
+        impl<$($param),+> AsyncReceive for $connection<$($param),+>
+        where
+            Self: Receive,
+        {
+            async fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+                Receive::receive(self, mode)
+            }
+        }
+
+        impl<$($param),+> AsyncWait for $connection<$($param),+>
+        where
+            Self: ErrorType,
+            T: AsyncDelay,
+        {
+            async fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+                let us = self.timing().execution_time(mode);
+                AsyncDelay::delay_us(self.delay(), us).await;
+                Ok(())
+            }
+
+            async fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+                AsyncDelay::delay_us(self.delay(), us).await;
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_async_connection!(ParallelConnection<RS, R, E, D, T, C>);
+impl_async_connection!(I2cExpanderConnection<B, T>);
+impl_async_connection!(Mcp230xxConnection<B, T, R>);
+impl_async_connection!(ShiftRegisterConnection<DS, SH, ST, T>);
@@ -0,0 +1,213 @@
+use core::cell::Cell;
+use core::fmt::Debug;
+
+use crate::hal::{first_error, Direction, DisplayHardwareLayer, ErrorType, Init, Level, Release};
+
+/// This trait is used to switch the backlight of a [`Display`] connection on or off.
+///
+/// [`Display`]: type.Display.html
+pub trait Backlight: ErrorType {
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error>;
+}
+
+/// This trait is used to dim the backlight of a [`Display`] connection.
+///
+/// [`Display`]: type.Display.html
+pub trait Brightness: Backlight {
+    /// Returns the current brightness, where `0` is off and `255` is full brightness.
+    fn brightness(&self) -> u8;
+
+    /// Sets the brightness, where `0` is off and `255` is full brightness.
+    fn set_brightness(&self, brightness: u8) -> Result<(), Self::Error>;
+}
+
+/// The `PwmPin` trait is intended to be implemented by the library user as a thin wrapper around
+/// a hardware specific PWM channel, e.g. to dim the backlight or to generate the contrast voltage.
+pub trait PwmPin {
+    /// The error reported by the hardware specific system calls.
+    type Error: Debug;
+
+    /// Returns the duty cycle, which keeps the output `High` all the time.
+    fn max_duty_cycle(&self) -> u16;
+
+    /// Sets the duty cycle, between `0` and `max_duty_cycle()`.
+    fn set_duty_cycle(&self, duty: u16) -> Result<(), Self::Error>;
+}
+
+/// This trait is used to drive the backlight of a [`BacklightConnection`], either by an I/O pin
+/// implementing [`DisplayHardwareLayer`] or by a [`PwmBacklight`].
+///
+/// [`BacklightConnection`]: struct.BacklightConnection.html
+/// [`DisplayHardwareLayer`]: trait.DisplayHardwareLayer.html
+/// [`PwmBacklight`]: struct.PwmBacklight.html
+pub trait BacklightControl<E> {
+    /// Initializes the backlight.
+    fn init_backlight(&self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    fn switch_backlight(&self, enabled: bool) -> Result<(), E>;
+
+    /// Cleans up the backlight.
+    fn cleanup_backlight(&self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+/// This trait is used to dim the backlight of a [`BacklightConnection`].
+///
+/// [`BacklightConnection`]: struct.BacklightConnection.html
+pub trait BrightnessControl<E>: BacklightControl<E> {
+    /// Returns the current brightness, where `0` is off and `255` is full brightness.
+    fn brightness(&self) -> u8;
+
+    /// Sets the brightness, where `0` is off and `255` is full brightness.
+    fn set_brightness(&self, brightness: u8) -> Result<(), E>;
+}
+
+impl<P: DisplayHardwareLayer> BacklightControl<P::Error> for P {
+    fn init_backlight(&self) -> Result<(), P::Error> {
+        self.init()?;
+        self.set_direction(Direction::Out)
+    }
+
+    fn switch_backlight(&self, enabled: bool) -> Result<(), P::Error> {
+        let level = if enabled { Level::High } else { Level::Low };
+        self.set_level(level)
+    }
+
+    fn cleanup_backlight(&self) -> Result<(), P::Error> {
+        self.cleanup()
+    }
+}
+
+/// A backlight dimmed by a PWM channel.
+///
+/// Switching it `On` sets full brightness.
+pub struct PwmBacklight<P> {
+    pin: P,
+    brightness: Cell<u8>,
+}
+
+impl<P> PwmBacklight<P> {
+    /// Creates a backlight dimmed by the given PWM channel, which is assumed to be switched off.
+    pub fn new(pin: P) -> Self {
+        PwmBacklight {
+            pin,
+            brightness: Cell::new(0),
+        }
+    }
+
+    /// Returns the PWM channel.
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: PwmPin> BacklightControl<P::Error> for PwmBacklight<P> {
+    fn switch_backlight(&self, enabled: bool) -> Result<(), P::Error> {
+        let brightness = if enabled { u8::MAX } else { 0 };
+        self.set_brightness(brightness)
+    }
+}
+
+impl<P: PwmPin> BrightnessControl<P::Error> for PwmBacklight<P> {
+    fn brightness(&self) -> u8 {
+        self.brightness.get()
+    }
+
+    fn set_brightness(&self, brightness: u8) -> Result<(), P::Error> {
+        let max_duty = u32::from(self.pin.max_duty_cycle());
+        let duty = max_duty * u32::from(brightness) / u32::from(u8::MAX);
+
+        self.pin.set_duty_cycle(duty as u16)?;
+        self.brightness.set(brightness);
+
+        Ok(())
+    }
+}
+
+/// A connection wrapper, which adds a backlight to connections without backlight control, e.g. a
+/// [`ParallelConnection`].
+///
+/// The backlight is either driven by an I/O pin or by a [`PwmBacklight`], which have to report
+/// the same error type as the wrapped connection.
+///
+/// [`ParallelConnection`]: struct.ParallelConnection.html
+/// [`PwmBacklight`]: struct.PwmBacklight.html
+pub struct BacklightConnection<P, B> {
+    connection: P,
+    backlight: B,
+}
+
+impl<P, B> BacklightConnection<P, B> {
+    /// Wraps the given connection, adding the given backlight.
+    pub fn new(connection: P, backlight: B) -> Self {
+        BacklightConnection {
+            connection,
+            backlight,
+        }
+    }
+
+    /// Returns the wrapped connection and the backlight.
+    pub fn into_inner(self) -> (P, B) {
+        (self.connection, self.backlight)
+    }
+}
+
+impl<P, B> Init for BacklightConnection<P, B>
+where
+    P: Init,
+    B: BacklightControl<P::Error>,
+{
+    fn init(&self) -> Result<(), Self::Error> {
+        self.backlight.init_backlight()?;
+        self.connection.init()
+    }
+}
+
+forward_connection!(impl<P, B> BacklightConnection<P, B> => P:
+    ErrorType, Send, Receive, Wait, SelectController, Contrast);
+
+/// Releasing hands back the pins of the wrapped connection together with the backlight.
+impl<P, B> Release for BacklightConnection<P, B>
+where
+    P: Release,
+    B: BacklightControl<P::Error>,
+{
+    type Pins = (P::Pins, B);
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        first_error([self.connection.cleanup(), self.backlight.cleanup_backlight()])
+    }
+
+    fn into_pins(self) -> Self::Pins {
+        (self.connection.into_pins(), self.backlight)
+    }
+}
+
+impl<P, B> Backlight for BacklightConnection<P, B>
+where
+    P: ErrorType,
+    B: BacklightControl<P::Error>,
+{
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+        self.backlight.switch_backlight(enabled)
+    }
+}
+
+impl<P, B> Brightness for BacklightConnection<P, B>
+where
+    P: ErrorType,
+    B: BrightnessControl<P::Error>,
+{
+    fn brightness(&self) -> u8 {
+        self.backlight.brightness()
+    }
+
+    fn set_brightness(&self, brightness: u8) -> Result<(), Self::Error> {
+        self.backlight.set_brightness(brightness)
+    }
+}
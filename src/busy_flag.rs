@@ -0,0 +1,72 @@
+use crate::hal::{ReadMode, Receive, Wait, WriteMode};
+
+const BUSY_FLAG: u8 = 0b1000_0000;
+
+/// A connection wrapper, which polls the busy flag of the display after every instruction instead
+/// of waiting for the instruction's worst-case execution time.
+///
+/// This gives maximum throughput on connections which are able to read from the display. The
+/// busy flag is polled every `POLL_INTERVAL_US` microseconds, so that the time until giving up
+/// does not depend on the speed of the bus. If the display is still busy after the given number
+/// of polls, the wrapper falls back to the worst-case execution time of the wrapped connection.
+///
+/// Write-only connections can not be wrapped, they always wait for the worst-case execution time
+/// of an instruction.
+pub struct BusyFlagPolling<P> {
+    connection: P,
+    max_polls: u16,
+}
+
+impl<P> BusyFlagPolling<P> {
+    /// The default number of busy flag polls before falling back to the worst-case execution
+    /// time, which covers the slowest instruction (1.52 ms).
+    pub const DEFAULT_MAX_POLLS: u16 = 200;
+
+    /// The time (µs) to wait between two polls of the busy flag.
+    pub const POLL_INTERVAL_US: u32 = 10;
+
+    /// Wraps the given connection, polling the busy flag up to `DEFAULT_MAX_POLLS` times.
+    /// It gives up after 2 ms, not counting the time of the polls themselves.
+    pub fn new(connection: P) -> Self {
+        Self::with_max_polls(connection, Self::DEFAULT_MAX_POLLS)
+    }
+
+    /// Wraps the given connection, polling the busy flag up to `max_polls` times.
+    pub fn with_max_polls(connection: P, max_polls: u16) -> Self {
+        BusyFlagPolling {
+            connection,
+            max_polls,
+        }
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> P {
+        self.connection
+    }
+}
+
+forward_connection!(impl<P> BusyFlagPolling<P> => P:
+    ErrorType, Init, Release, Send, Receive, SelectController, Backlight, Brightness, Contrast);
+
+impl<P> Wait for BusyFlagPolling<P>
+where
+    P: Receive + Wait,
+{
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        for poll in 0..self.max_polls {
+            if poll > 0 {
+                self.connection.wait_us(Self::POLL_INTERVAL_US)?;
+            }
+
+            if self.connection.receive(ReadMode::BusyFlag)? & BUSY_FLAG == 0 {
+                return Ok(());
+            }
+        }
+
+        self.connection.wait(mode)
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.connection.wait_us(us)
+    }
+}
@@ -0,0 +1,171 @@
+use core::fmt::Debug;
+
+use crate::backlight::PwmPin;
+use crate::hal::{DisplayHardwareLayer, ErrorType, ParallelConnection, Send, Wait, WriteMode};
+use crate::FunctionSetBuilder;
+
+/// The maximum contrast of the controller-internal contrast of ST7032 compliant controllers.
+const ST7032_MAX_CONTRAST: u8 = 0b0011_1111;
+
+const ST7032_INSTRUCTION_TABLE_1: u8 = 0b0000_0001;
+const ST7032_POWER_ICON_CONTRAST_CMD: u8 = 0b0101_0000;
+const ST7032_BOOSTER_ON: u8 = 0b0000_0100;
+const ST7032_CONTRAST_SET_CMD: u8 = 0b0111_0000;
+
+/// This trait is used to adjust the contrast of a [`Display`] connection at runtime.
+///
+/// [`Display`]: type.Display.html
+pub trait Contrast: ErrorType {
+    /// Sets the contrast, where `0` is the lowest and `255` the highest contrast.
+    fn set_contrast(&self, level: u8) -> Result<(), Self::Error>;
+}
+
+/// The `DacPin` trait is intended to be implemented by the library user as a thin wrapper around
+/// a hardware specific DAC channel.
+pub trait DacPin {
+    /// The error reported by the hardware specific system calls.
+    type Error: Debug;
+
+    /// Returns the value, which sets the highest output voltage.
+    fn max_value(&self) -> u16;
+
+    /// Sets the output value, between `0` and `max_value()`.
+    fn set_value(&self, value: u16) -> Result<(), Self::Error>;
+}
+
+/// This trait is used to drive the contrast voltage (V0) of a [`ParallelConnection`].
+///
+/// [`ParallelConnection`]: struct.ParallelConnection.html
+pub trait ContrastControl<E> {
+    /// Sets the contrast, where `0` is the lowest and `255` the highest contrast.
+    fn set_contrast(&self, level: u8) -> Result<(), E>;
+}
+
+/// A marker for connections, whose contrast is set by a potentiometer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoContrast;
+
+/// Returns the output value for the given contrast level. The contrast rises with falling
+/// contrast voltage (V0), so the highest level is mapped to `0`.
+fn contrast_voltage(level: u8, max_value: u16) -> u16 {
+    let inverted = u32::from(u8::MAX - level);
+
+    (u32::from(max_value) * inverted / u32::from(u8::MAX)) as u16
+}
+
+/// A contrast voltage generated by a PWM channel and an RC low-pass filter.
+pub struct PwmContrast<P>(P);
+
+impl<P> PwmContrast<P> {
+    /// Creates a contrast voltage generated by the given PWM channel.
+    pub fn new(pin: P) -> Self {
+        PwmContrast(pin)
+    }
+
+    /// Returns the PWM channel.
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P: PwmPin> ContrastControl<P::Error> for PwmContrast<P> {
+    fn set_contrast(&self, level: u8) -> Result<(), P::Error> {
+        let duty = contrast_voltage(level, self.0.max_duty_cycle());
+        self.0.set_duty_cycle(duty)
+    }
+}
+
+/// A contrast voltage generated by a DAC channel.
+pub struct DacContrast<P>(P);
+
+impl<P> DacContrast<P> {
+    /// Creates a contrast voltage generated by the given DAC channel.
+    pub fn new(pin: P) -> Self {
+        DacContrast(pin)
+    }
+
+    /// Returns the DAC channel.
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P: DacPin> ContrastControl<P::Error> for DacContrast<P> {
+    fn set_contrast(&self, level: u8) -> Result<(), P::Error> {
+        let value = contrast_voltage(level, self.0.max_value());
+        self.0.set_value(value)
+    }
+}
+
+impl<RS, R, E, D, T, C> Contrast for ParallelConnection<RS, R, E, D, T, C>
+where
+    RS: DisplayHardwareLayer,
+    C: ContrastControl<RS::Error>,
+{
+    fn set_contrast(&self, level: u8) -> Result<(), Self::Error> {
+        self.contrast().set_contrast(level)
+    }
+}
+
+/// A connection wrapper for ST7032 compliant controllers, which generate the contrast voltage
+/// internally and set the contrast by instruction.
+///
+/// The function set instruction given on creation has to match the one used to initialize the
+/// display, because it is sent to switch between the instruction tables.
+pub struct St7032Contrast<P> {
+    connection: P,
+    function_set: u8,
+    booster: bool,
+}
+
+impl<P> St7032Contrast<P> {
+    /// Wraps the given connection. The booster circuit has to be enabled for displays driven by
+    /// 3.3 V.
+    pub fn new(connection: P, function_set: &FunctionSetBuilder, booster: bool) -> Self {
+        St7032Contrast {
+            connection,
+            function_set: function_set.build_command(),
+            booster,
+        }
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> P {
+        self.connection
+    }
+}
+
+impl<P> St7032Contrast<P>
+where
+    P: Send + Wait,
+{
+    fn execute(&self, cmd: u8) -> Result<(), P::Error> {
+        let mode = WriteMode::Command(cmd);
+
+        self.connection.send(mode)?;
+        self.connection.wait(mode)
+    }
+}
+
+forward_connection!(impl<P> St7032Contrast<P> => P:
+    ErrorType, Init, Release, Send, Receive, Wait, SelectController, Backlight, Brightness);
+
+impl<P> Contrast for St7032Contrast<P>
+where
+    P: Send + Wait,
+{
+    fn set_contrast(&self, level: u8) -> Result<(), Self::Error> {
+        let contrast = u16::from(level) * u16::from(ST7032_MAX_CONTRAST) / u16::from(u8::MAX);
+        let contrast = contrast as u8;
+
+        let mut power_control = ST7032_POWER_ICON_CONTRAST_CMD | (contrast >> 4);
+        if self.booster {
+            power_control |= ST7032_BOOSTER_ON;
+        }
+
+        self.execute(self.function_set | ST7032_INSTRUCTION_TABLE_1)?;
+        self.execute(power_control)?;
+        self.execute(ST7032_CONTRAST_SET_CMD | (contrast & 0x0f))?;
+        self.execute(self.function_set)
+    }
+}
@@ -1,10 +1,31 @@
+use core::cell::Cell;
+use core::fmt;
 use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
 
 use super::address::{Address, Overflow};
-use super::{DisplayControlBuilder, EntryModeBuilder, FunctionSetBuilder, Home};
-use hal::{Init, ReadMode, Receive, Send, WriteMode};
-
-const LCD_WIDTH: usize = 16;
+use super::{DataLength, DisplayControlBuilder, DisplayState, EntryModeBuilder, FunctionSetBuilder,
+            Home};
+use crate::backlight::{Backlight, Brightness};
+use crate::contrast::Contrast;
+use crate::error::Error;
+use crate::hal::{ErrorType, Init, ReadMode, Receive, Release, Send, Wait, WriteMode};
+use crate::text::{self, LineOverflow, TextLayout, Unencodable};
+
+/// The time (µs) to wait after power on, until the supply voltage has risen above 2.7 V.
+pub(crate) const POWER_ON_WAIT_TIME: u32 = 40_000;
+/// The time (µs) to wait after the first function set instruction during initialization.
+pub(crate) const FIRST_INIT_WAIT_TIME: u32 = 4_100;
+/// The time (µs) to wait after each further init instruction during initialization.
+pub(crate) const SECOND_INIT_WAIT_TIME: u32 = 100;
+
+/// The function set instruction selecting an 8-bit interface, which resets the interface.
+pub(crate) const INIT_8BIT_INSTRUCTION: u8 = 0b0011_0000;
+/// The function set instruction selecting a 4-bit interface.
+pub(crate) const INIT_4BIT_INSTRUCTION: u8 = 0b0010_0000;
+
+const SEEK_DDRAM_CMD: u8 = 0b1000_0000;
+const SEEK_CGRAM_CMD: u8 = 0b0100_0000;
 
 bitflags! {
     struct Instructions: u8 {
@@ -53,10 +74,68 @@ impl ShiftTo {
             ShiftTo::Left(offset) => (offset, ShiftDirection::LEFT),
         }
     }
+
+    /// Returns the number of shift instructions and the instruction to shift the given target.
+    fn instruction(&self, target: ShiftTarget) -> (u8, WriteMode) {
+        let (offset, raw_direction) = self.as_offset_and_raw_direction();
+
+        let mut cmd = Instructions::SHIFT.bits();
+
+        cmd |= target.bits();
+        cmd |= raw_direction.bits();
+
+        (offset, WriteMode::Command(cmd))
+    }
+
+    pub(crate) fn cursor_instruction(&self) -> (u8, WriteMode) {
+        self.instruction(ShiftTarget::CURSOR)
+    }
+
+    pub(crate) fn display_instruction(&self) -> (u8, WriteMode) {
+        self.instruction(ShiftTarget::DISPLAY)
+    }
+
+    /// Returns the cursor address after shifting the cursor from the given address.
+    pub(crate) fn shift_address<RT: Overflow>(&self, address: Address<RT>) -> Address<RT> {
+        match *self {
+            ShiftTo::Right(offset) => address + offset.into(),
+            ShiftTo::Left(offset) => address - offset.into(),
+        }
+    }
+}
+
+pub(crate) fn clear_instruction() -> WriteMode {
+    WriteMode::Command(Instructions::CLEAR_DISPLAY.bits())
+}
+
+/// Splits the given byte into busy flag and address.
+pub(crate) fn parse_busy_flag(byte: u8) -> (bool, u8) {
+    let busy_flag = (byte & 0b1000_0000) != 0;
+
+    let address = byte & 0b0111_1111;
+
+    (busy_flag, address)
+}
+
+impl Address<DdRam> {
+    pub(crate) fn seek_instruction(self) -> WriteMode {
+        WriteMode::Command(SEEK_DDRAM_CMD | u8::from(self))
+    }
+}
+
+impl Address<CgRam> {
+    pub(crate) fn seek_instruction(self) -> WriteMode {
+        WriteMode::Command(SEEK_CGRAM_CMD | u8::from(self))
+    }
 }
 
 pub type DdRamDisplay<P, U> = Display<P, U, DdRam>;
 
+/// The result of switching a display from the RAM type `From` to `To`. On failure the unchanged
+/// display is returned together with the error, so that the connection is not lost.
+pub type SwitchRamResult<P, U, From, To> =
+    Result<Display<P, U, To>, (Display<P, U, From>, Error<<P as ErrorType>::Error>)>;
+
 /// A HD44780 compliant display.
 ///
 /// It provides a high-level and hardware agnostic interface to controll a HD44780 compliant
@@ -66,7 +145,11 @@ where
     U: Into<Address<RT>> + Home,
 {
     connection: P,
-    cursor_address: Address<RT>,
+    cursor_address: Cell<Address<RT>>,
+    turn_off_on_release: bool,
+    text_layout: TextLayout,
+    /// The line, whose remaining text is dropped by `write!` until the next newline.
+    truncated_line: Cell<Option<u8>>,
     _ram_type: PhantomData<RT>,
     _line_marker: PhantomData<U>,
 }
@@ -78,8 +161,11 @@ where
     /// Create a new `Display` using the given connection.
     pub fn new(connection: P) -> Display<P, U, DdRam> {
         Display {
-            connection: connection,
-            cursor_address: Address::from(0),
+            connection,
+            cursor_address: Cell::new(Address::from(0)),
+            turn_off_on_release: false,
+            text_layout: TextLayout::default(),
+            truncated_line: Cell::new(None),
             _ram_type: PhantomData::<DdRam>,
             _line_marker: PhantomData,
         }
@@ -88,60 +174,165 @@ where
 
 impl<P, U, RT> Display<P, U, RT>
 where
-    P: Init + Send + Receive,
+    P: Send + Wait,
     U: Into<Address<RT>> + Home,
-    RT: Overflow,
 {
-    const FIRST_4BIT_INIT_INSTRUCTION: WriteMode = WriteMode::Command(0x33);
-    const SECOND_4BIT_INIT_INSTRUCTION: WriteMode = WriteMode::Command(0x32);
+    /// Sends the given write operation and waits until it has been executed.
+    fn execute(&self, mode: WriteMode) -> Result<(), Error<P::Error>> {
+        self.connection.send(mode).map_err(Error::Connection)?;
+        self.connection.wait(mode).map_err(Error::Connection)
+    }
 
-    pub fn init(&self, builder: &FunctionSetBuilder) {
-        self.connection.init();
+    fn wait_us(&self, us: u32) -> Result<(), Error<P::Error>> {
+        self.connection.wait_us(us).map_err(Error::Connection)
+    }
+}
 
-        let cmd = builder.build_command();
-        let cmd = WriteMode::Command(cmd);
+impl<P, U, RT> Display<P, U, RT>
+where
+    U: Into<Address<RT>> + Home,
+{
+    pub fn get_connection(self) -> P {
+        self.connection
+    }
+
+    /// Sets whether the display is turned off by [`release()`], which is not done by default.
+    ///
+    /// [`release()`]: struct.Display.html#method.release
+    pub fn set_turn_off_on_release(&mut self, enabled: bool) {
+        self.turn_off_on_release = enabled;
+    }
+
+    /// Sets the number of columns and lines of the display, which `write!` wraps or truncates
+    /// text at. A display has 16 columns and 2 lines by default.
+    ///
+    /// Displays with four lines are expected to continue the first and the second line at the
+    /// third and the fourth, as usual for HD44780 panels.
+    pub fn set_geometry(&mut self, columns: u8, lines: u8) {
+        self.text_layout.columns = columns;
+        self.text_layout.lines = lines;
+    }
+
+    /// Sets how `write!` continues text reaching the end of a line, which is
+    /// `LineOverflow::Truncate` by default.
+    pub fn set_line_overflow(&mut self, overflow: LineOverflow) {
+        self.text_layout.overflow = overflow;
+    }
+
+    /// Sets how `write!` handles characters, which are missing in the character set of the
+    /// display. They are substituted by `?` by default.
+    pub fn set_unencodable(&mut self, unencodable: Unencodable) {
+        self.text_layout.unencodable = unencodable;
+    }
+
+    pub(crate) fn connection(&self) -> &P {
+        &self.connection
+    }
+
+    pub(crate) fn cursor_address(&self) -> Address<RT> {
+        self.cursor_address.get()
+    }
+
+    fn set_cursor_address(&self, address: Address<RT>) {
+        self.cursor_address.set(address);
+        self.truncated_line.set(None);
+    }
+
+    fn switch_ram<T>(self, cursor_address: Address<T>) -> Display<P, U, T>
+    where
+        U: Into<Address<T>>,
+    {
+        Display {
+            connection: self.connection,
+            cursor_address: Cell::new(cursor_address),
+            turn_off_on_release: self.turn_off_on_release,
+            text_layout: self.text_layout,
+            truncated_line: Cell::new(None),
+            _ram_type: PhantomData::<T>,
+            _line_marker: PhantomData,
+        }
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Init + Send + Wait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
+    pub fn init(&self, builder: &FunctionSetBuilder) -> Result<(), Error<P::Error>> {
+        self.connection.init().map_err(Error::Connection)?;
 
-        self.init_by_instruction(cmd);
+        self.init_by_instruction(builder)
     }
 
-    fn init_by_instruction(&self, function_set: WriteMode) {
-        self.connection.send(Self::FIRST_4BIT_INIT_INSTRUCTION);
-        self.connection.send(Self::SECOND_4BIT_INIT_INSTRUCTION);
+    fn init_by_instruction(&self, builder: &FunctionSetBuilder) -> Result<(), Error<P::Error>> {
+        // the busy flag can not be checked until the interface data length is set, so the init
+        // instructions have to wait for the times given in the datasheet
+        self.wait_us(POWER_ON_WAIT_TIME)?;
+
+        // after a reset of the MCU the controller may be in 8-bit mode or in 4-bit mode waiting
+        // for either nibble. Three single nibbles put it into 8-bit mode in any case, a pending
+        // instruction completed by the first one is covered by the first wait time.
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION)?;
+        self.wait_us(FIRST_INIT_WAIT_TIME)?;
+
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION)?;
+        self.wait_us(SECOND_INIT_WAIT_TIME)?;
+
+        self.send_upper_nibble(INIT_8BIT_INSTRUCTION)?;
+        self.wait_us(SECOND_INIT_WAIT_TIME)?;
+
+        if let DataLength::FourBit = builder.data_length() {
+            self.send_upper_nibble(INIT_4BIT_INSTRUCTION)?;
+            self.wait_us(SECOND_INIT_WAIT_TIME)?;
+        }
 
-        self.connection.send(function_set);
+        self.execute(WriteMode::Command(builder.build_command()))?;
 
-        self.clear();
+        self.clear()
     }
 
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Error<P::Error>> {
+        self.connection.send_upper_nibble(cmd).map_err(Error::Connection)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Send + Wait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
     /// Sets the entry mode of the display.
-    pub fn set_entry_mode(&self, builder: &EntryModeBuilder) {
+    pub fn set_entry_mode(&self, builder: &EntryModeBuilder) -> Result<(), Error<P::Error>> {
         let cmd = WriteMode::Command(builder.build_command());
-        self.connection.send(cmd);
+        self.execute(cmd)
     }
 
     /// Sets the display control settings.
-    pub fn set_display_control(&self, builder: &DisplayControlBuilder) {
+    pub fn set_display_control(
+        &self,
+        builder: &DisplayControlBuilder,
+    ) -> Result<(), Error<P::Error>> {
         let cmd = WriteMode::Command(builder.build_command());
-        self.connection.send(cmd);
+        self.execute(cmd)
     }
 
     /// Shifts the cursor to the left or the right by the given offset.
     ///
     /// **Note:** Consider to use [seek()](struct.Display.html#method.seek) for longer distances.
-    #[cfg_attr(feature = "cargo-clippy", allow(expl_impl_clone_on_copy))]
-    pub fn shift_cursor(&mut self, direction: ShiftTo) {
-        let (offset, raw_direction) = direction.as_offset_and_raw_direction();
+    #[allow(clippy::expl_impl_clone_on_copy)]
+    pub fn shift_cursor(&mut self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        let (offset, cmd) = direction.cursor_instruction();
 
         if offset == 0 {
-            return;
+            return Ok(());
         }
 
-        match direction {
-            ShiftTo::Right(offset) => self.cursor_address += offset.into(),
-            ShiftTo::Left(offset) => self.cursor_address -= offset.into(),
-        }
+        self.set_cursor_address(direction.shift_address(self.cursor_address()));
 
-        self.raw_shift(ShiftTarget::CURSOR, offset, raw_direction);
+        self.raw_shift(offset, cmd)
     }
 
     /// Shifts the display to the right or the left by the given offset.
@@ -150,66 +341,310 @@ where
     ///
     /// When the displayed data is shifted repeatedly each line moves only horizontally.
     /// The second line display does not shift into the first line position.
-    pub fn shift(&self, direction: ShiftTo) {
-        let (offset, raw_direction) = direction.as_offset_and_raw_direction();
+    pub fn shift(&self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        let (offset, cmd) = direction.display_instruction();
 
-        self.raw_shift(ShiftTarget::DISPLAY, offset, raw_direction);
+        self.raw_shift(offset, cmd)
     }
 
-    fn raw_shift(&self, shift_type: ShiftTarget, offset: u8, raw_direction: ShiftDirection) {
-        let mut cmd = Instructions::SHIFT.bits();
-
-        cmd |= shift_type.bits();
-        cmd |= raw_direction.bits();
-
+    fn raw_shift(&self, offset: u8, cmd: WriteMode) -> Result<(), Error<P::Error>> {
         for _ in 0..offset {
-            self.connection.send(WriteMode::Command(cmd));
+            self.execute(cmd)?;
         }
+
+        Ok(())
     }
 
     /// Clears the entire display, sets the cursor to the home position and undo all display
     /// shifts.
     ///
     /// It also sets the cursor's move direction to `Increment`.
-    pub fn clear(&self) {
-        let cmd = Instructions::CLEAR_DISPLAY.bits();
-        self.connection.send(WriteMode::Command(cmd));
+    pub fn clear(&self) -> Result<(), Error<P::Error>> {
+        self.execute(clear_instruction())?;
+        self.set_cursor_address(Address::from(0));
+
+        Ok(())
     }
 
     /// Writes the given byte to data or character generator RAM, depending on the previous
     /// seek operation.
-    pub fn write(&mut self, c: u8) {
-        self.cursor_address += Address::from(1);
-        self.connection.send(WriteMode::Data(c));
+    pub fn write(&mut self, c: u8) -> Result<(), Error<P::Error>> {
+        self.execute(WriteMode::Data(c))?;
+        self.set_cursor_address(self.cursor_address() + Address::from(1));
+
+        Ok(())
     }
 
+    /// Writes the given message to data or character generator RAM, depending on the previous
+    /// seek operation.
+    ///
+    /// **Note:** The message is written as is and truncated after the number of columns set by
+    /// [`set_geometry()`]. Use `write!` to encode the characters and to continue on the next
+    /// line.
+    ///
+    /// [`set_geometry()`]: struct.Display.html#method.set_geometry
+    pub fn write_message(&mut self, msg: &str) -> Result<(), Error<P::Error>> {
+        let columns = usize::from(self.text_layout.columns);
+
+        for c in msg.as_bytes().iter().take(columns) {
+            self.write(*c)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reading from the display is only available if its connection is able to receive data, i.e.
+/// it is not wired write-only.
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Receive + Wait,
+    U: Into<Address<RT>> + Home,
+    RT: Overflow,
+{
     /// Reads a single byte from data RAM.
-    pub fn read_byte(&mut self) -> u8 {
-        self.cursor_address += Address::from(1);
-        self.connection.receive(ReadMode::Data)
+    ///
+    /// Afterwards it waits until the address counter has been updated, which takes as long as
+    /// writing data, so that consecutive reads do not return stale characters.
+    pub fn read_byte(&mut self) -> Result<u8, Error<P::Error>> {
+        let byte = self.connection
+            .receive(ReadMode::Data)
+            .map_err(Error::Connection)?;
+        self.connection
+            .wait(WriteMode::Data(byte))
+            .map_err(Error::Connection)?;
+        self.set_cursor_address(self.cursor_address() + Address::from(1));
+
+        Ok(byte)
     }
+}
 
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Receive,
+    U: Into<Address<RT>> + Home,
+{
     /// Reads busy flag and the cursor's current address.
-    pub fn read_busy_flag(&self) -> (bool, u8) {
-        let byte = self.connection.receive(ReadMode::BusyFlag);
+    pub fn read_busy_flag(&self) -> Result<(bool, u8), Error<P::Error>> {
+        let byte = self.connection
+            .receive(ReadMode::BusyFlag)
+            .map_err(Error::Connection)?;
 
-        let busy_flag = (byte & 0b1000_0000) != 0;
+        Ok(parse_busy_flag(byte))
+    }
+}
 
-        let address = byte & 0b0111_1111;
+/// Adjusting the contrast is only available if the connection is able to, e.g. a
+/// `ParallelConnection` with a contrast control or ST7032 compliant controllers.
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Contrast,
+    U: Into<Address<RT>> + Home,
+{
+    /// Sets the contrast, where `0` is the lowest and `255` the highest contrast.
+    pub fn set_contrast(&self, level: u8) -> Result<(), Error<P::Error>> {
+        self.connection
+            .set_contrast(level)
+            .map_err(Error::Connection)
+    }
+}
 
-        (busy_flag, address)
+/// Controlling the backlight is only available if the connection is able to, e.g. an I2C
+/// expander or a connection wrapped into a `BacklightConnection`.
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Backlight,
+    U: Into<Address<RT>> + Home,
+{
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    pub fn set_backlight(&self, enabled: bool) -> Result<(), Error<P::Error>> {
+        self.connection
+            .set_backlight(enabled)
+            .map_err(Error::Connection)
     }
+}
 
-    /// Writes the given message to data or character generator RAM, depending on the previous
-    /// seek operation.
-    pub fn write_message(&mut self, msg: &str) {
-        for c in msg.as_bytes().iter().take(LCD_WIDTH) {
-            self.write(*c);
-        }
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Brightness,
+    U: Into<Address<RT>> + Home,
+{
+    /// Returns the current brightness of the backlight, where `0` is off and `255` is full
+    /// brightness.
+    pub fn brightness(&self) -> u8 {
+        self.connection.brightness()
     }
 
-    pub fn get_connection(self) -> P {
+    /// Sets the brightness of the backlight, where `0` is off and `255` is full brightness.
+    pub fn set_brightness(&self, brightness: u8) -> Result<(), Error<P::Error>> {
         self.connection
+            .set_brightness(brightness)
+            .map_err(Error::Connection)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Brightness + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    /// Fades the backlight from the current to the given brightness within the given duration
+    /// (ms), waiting by means of the connection's delay.
+    pub fn fade_to(&self, brightness: u8, duration_ms: u16) -> Result<(), Error<P::Error>> {
+        let mut current = self.brightness();
+        let steps = (i16::from(current) - i16::from(brightness)).unsigned_abs();
+
+        if steps == 0 {
+            return Ok(());
+        }
+
+        let step_time = u32::from(duration_ms) * 1000 / u32::from(steps);
+
+        while current != brightness {
+            if current < brightness {
+                current += 1;
+            } else {
+                current -= 1;
+            }
+
+            self.connection
+                .wait_us(step_time)
+                .map_err(Error::Connection)?;
+            self.set_brightness(current)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fades the backlight in to full brightness within the given duration (ms).
+    pub fn fade_in(&self, duration_ms: u16) -> Result<(), Error<P::Error>> {
+        self.fade_to(u8::MAX, duration_ms)
+    }
+
+    /// Fades the backlight out within the given duration (ms).
+    pub fn fade_out(&self, duration_ms: u16) -> Result<(), Error<P::Error>> {
+        self.fade_to(0, duration_ms)
+    }
+}
+
+impl<P, U, RT> Display<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    /// Turns the display off, if configured by [`set_turn_off_on_release()`], cleans up all pins
+    /// of the connection and returns them for reuse.
+    ///
+    /// The pins are cleaned up, even if the display could not be turned off. On failure the
+    /// first error is returned and the pins are dropped.
+    ///
+    /// [`set_turn_off_on_release()`]: struct.Display.html#method.set_turn_off_on_release
+    pub fn release(self) -> Result<P::Pins, Error<P::Error>> {
+        let turned_off = if self.turn_off_on_release {
+            let mut builder = DisplayControlBuilder::default();
+            builder.set_display(DisplayState::Off);
+
+            self.execute(WriteMode::Command(builder.build_command()))
+        } else {
+            Ok(())
+        };
+
+        let cleaned_up = self.connection.cleanup().map_err(Error::Connection);
+
+        turned_off?;
+        cleaned_up?;
+
+        Ok(self.connection.into_pins())
+    }
+
+    /// Returns a guard, which releases the display when it is dropped, see [`release()`].
+    ///
+    /// [`release()`]: struct.Display.html#method.release
+    pub fn release_on_drop(self) -> ReleaseOnDrop<P, U, RT> {
+        ReleaseOnDrop {
+            display: Some(self),
+        }
+    }
+}
+
+/// A guard, which releases the wrapped [`Display`] when it is dropped. Errors are ignored in this
+/// case, use [`release()`] to handle them.
+///
+/// Methods consuming the display are not available through the guard, [`into_inner()`] returns
+/// the display without releasing it.
+///
+/// [`Display`]: struct.Display.html
+/// [`release()`]: struct.ReleaseOnDrop.html#method.release
+/// [`into_inner()`]: struct.ReleaseOnDrop.html#method.into_inner
+pub struct ReleaseOnDrop<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    display: Option<Display<P, U, RT>>,
+}
+
+impl<P, U, RT> ReleaseOnDrop<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    /// Releases the display, see [`Display::release()`].
+    ///
+    /// [`Display::release()`]: struct.Display.html#method.release
+    pub fn release(mut self) -> Result<P::Pins, Error<P::Error>> {
+        match self.display.take() {
+            Some(display) => display.release(),
+            None => unreachable!("the display is only taken on release"),
+        }
+    }
+
+    /// Returns the display without releasing it.
+    pub fn into_inner(mut self) -> Display<P, U, RT> {
+        match self.display.take() {
+            Some(display) => display,
+            None => unreachable!("the display is only taken on release"),
+        }
+    }
+}
+
+impl<P, U, RT> Deref for ReleaseOnDrop<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    type Target = Display<P, U, RT>;
+
+    fn deref(&self) -> &Self::Target {
+        match self.display {
+            Some(ref display) => display,
+            None => unreachable!("the display is only taken on release"),
+        }
+    }
+}
+
+impl<P, U, RT> DerefMut for ReleaseOnDrop<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self.display {
+            Some(ref mut display) => display,
+            None => unreachable!("the display is only taken on release"),
+        }
+    }
+}
+
+impl<P, U, RT> Drop for ReleaseOnDrop<P, U, RT>
+where
+    P: Release + Send + Wait,
+    U: Into<Address<RT>> + Home,
+{
+    fn drop(&mut self) {
+        if let Some(display) = self.display.take() {
+            let _ = display.release();
+        }
     }
 }
 
@@ -237,6 +672,22 @@ where
     Line { line: T, offset: u8 },
 }
 
+impl<T> SeekFrom<T>
+where
+    T: Into<Address<DdRam>> + Home,
+{
+    /// Returns the address to seek to from the given cursor address.
+    pub(crate) fn address(self, cursor_address: Address<DdRam>) -> Address<DdRam> {
+        let (start, addr) = match self {
+            SeekFrom::Home(offset) => (T::FIRST_LINE_ADDRESS.into(), offset.into()),
+            SeekFrom::Current(offset) => (cursor_address, offset.into()),
+            SeekFrom::Line { line, offset } => (line.into(), offset.into()),
+        };
+
+        start + addr
+    }
+}
+
 impl<T> From<SetFrom<T>> for SeekFrom<T>
 where
     T: Into<Address<DdRam>>,
@@ -251,42 +702,105 @@ where
 
 impl<P, U> Display<P, U, DdRam>
 where
-    P: Send,
+    P: Send + Wait,
     U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
 {
-    const SEEK_DDRAM_CMD: u8 = 0b1000_0000;
-
     /// Seeks to an offset in display data RAM.
-    pub fn seek(&mut self, pos: SeekFrom<U>) {
-        let mut cmd = Self::SEEK_DDRAM_CMD;
+    pub fn seek(&mut self, pos: SeekFrom<U>) -> Result<(), Error<P::Error>> {
+        let address = pos.address(self.cursor_address());
+        self.set_cursor_address(address);
 
-        let (start, addr) = match pos {
-            SeekFrom::Home(offset) => (U::FIRST_LINE_ADDRESS.into(), offset.into()),
-            SeekFrom::Current(offset) => (self.cursor_address, offset.into()),
-            SeekFrom::Line { line, offset } => (line.into(), offset.into()),
-        };
-
-        self.cursor_address = start + addr;
-
-        cmd |= u8::from(self.cursor_address);
-
-        self.connection.send(WriteMode::Command(cmd));
+        self.execute(address.seek_instruction())
     }
 
     /// Switches to the character generator RAM (CGRAM) and set the cursor's
     /// address to the given value. After that all following instructions will
     /// operate on this RAM type until it is switched back to display data RAM.
-    pub fn set_cgram_address(self, address: u8) -> Display<P, U, CgRam> {
-        let mut cgram_display = Display {
-            connection: self.connection,
-            cursor_address: Address::<CgRam>::from(0),
-            _ram_type: PhantomData::<CgRam>,
-            _line_marker: PhantomData,
-        };
+    pub fn set_cgram_address(self, address: u8) -> SwitchRamResult<P, U, DdRam, CgRam> {
+        let ddram_address = self.cursor_address();
+        let mut cgram_display = self.switch_ram(Address::<CgRam>::from(0));
 
-        cgram_display.seek(SeekCgRamFrom::Home(address));
+        match cgram_display.seek(SeekCgRamFrom::Home(address)) {
+            Ok(()) => Ok(cgram_display),
+            Err(e) => Err((cgram_display.switch_ram(ddram_address), e)),
+        }
+    }
+}
 
-        cgram_display
+/// Reading back the display data RAM is only available if the connection is able to receive
+/// data, e.g. to check which text is actually shown.
+impl<P, U> Display<P, U, DdRam>
+where
+    P: Send + Wait + Receive,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Reads the characters of the given zero-based row into the given buffer and restores the
+    /// cursor position afterwards.
+    ///
+    /// The row and its length are taken from the geometry set by [`set_geometry()`]. Returns the
+    /// number of bytes read, which is limited by the buffer and is zero for a row beyond the
+    /// geometry.
+    ///
+    /// [`set_geometry()`]: struct.Display.html#method.set_geometry
+    pub fn read_line(&mut self, row: u8, buffer: &mut [u8]) -> Result<usize, Error<P::Error>> {
+        self.restore_cursor_after(|display| display.read_row(row, buffer))
+    }
+
+    /// Reads the characters of all rows into the given buffer, row by row, and restores the
+    /// cursor position afterwards.
+    ///
+    /// Each row takes as many bytes as the display has columns, see [`set_geometry()`]. Returns
+    /// the number of bytes read, which is limited by the buffer.
+    ///
+    /// [`set_geometry()`]: struct.Display.html#method.set_geometry
+    pub fn read_screen(&mut self, buffer: &mut [u8]) -> Result<usize, Error<P::Error>> {
+        self.restore_cursor_after(|display| {
+            let columns = usize::from(display.text_layout.columns).max(1);
+            let rows = 0..display.text_layout.visible_lines();
+            let mut read = 0;
+
+            for (row, chunk) in rows.zip(buffer.chunks_mut(columns)) {
+                read += display.read_row(row, chunk)?;
+            }
+
+            Ok(read)
+        })
+    }
+
+    fn read_row(&mut self, row: u8, buffer: &mut [u8]) -> Result<usize, Error<P::Error>> {
+        if row >= self.text_layout.visible_lines() {
+            return Ok(0);
+        }
+
+        let len = buffer.len().min(usize::from(self.text_layout.columns));
+        self.seek(SeekFrom::Home(self.text_layout.line_address(row)))?;
+
+        for byte in &mut buffer[..len] {
+            *byte = self.read_byte()?;
+        }
+
+        Ok(len)
+    }
+
+    /// Runs the given operation and seeks back to the previous cursor position, even if the
+    /// operation failed. The first error is returned.
+    fn restore_cursor_after<T, F>(&mut self, operation: F) -> Result<T, Error<P::Error>>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error<P::Error>>,
+    {
+        let address = self.cursor_address();
+        let truncated_line = self.truncated_line.get();
+
+        let result = operation(self);
+
+        let offset = u8::from(address).wrapping_sub(U::FIRST_LINE_ADDRESS);
+        let restored = self.seek(SeekFrom::Home(offset));
+        self.truncated_line.set(truncated_line);
+
+        let value = result?;
+        restored?;
+
+        Ok(value)
     }
 }
 
@@ -298,42 +812,119 @@ pub enum SeekCgRamFrom {
     Current(u8),
 }
 
+impl SeekCgRamFrom {
+    /// Returns the address to seek to from the given cursor address.
+    pub(crate) fn address(self, cursor_address: Address<CgRam>) -> Address<CgRam> {
+        match self {
+            SeekCgRamFrom::Home(offset) => offset.into(),
+            SeekCgRamFrom::Current(offset) => cursor_address + offset.into(),
+        }
+    }
+}
+
 impl<P, U> Display<P, U, CgRam>
 where
-    P: Send,
+    P: Send + Wait,
     U: Into<Address<CgRam>> + Into<Address<DdRam>> + Home,
 {
-    const SEEK_CGRAM_CMD: u8 = 0b0100_0000;
-
     /// Seeks to an offset in character generator RAM.
-    pub fn seek(&mut self, pos: SeekCgRamFrom) {
-        let mut cmd = Self::SEEK_CGRAM_CMD;
+    pub fn seek(&mut self, pos: SeekCgRamFrom) -> Result<(), Error<P::Error>> {
+        let address = pos.address(self.cursor_address());
+        self.set_cursor_address(address);
 
-        let addr = match pos {
-            SeekCgRamFrom::Home(offset) => offset.into(),
-            SeekCgRamFrom::Current(offset) => self.cursor_address + offset.into(),
-        };
-
-        self.cursor_address = addr;
-
-        cmd |= u8::from(self.cursor_address);
-
-        self.connection.send(WriteMode::Command(cmd));
+        self.execute(address.seek_instruction())
     }
 
     /// Switches to the display data RAM (DDRAM) and set the cursor's address to
     /// the given value. After that all following instructions will operate on
     /// this RAM type until it is switched back to character generator RAM.
-    pub fn set_ddram_address(self, pos: SetFrom<U>) -> Display<P, U, DdRam> {
-        let mut ddram_display = Display {
-            connection: self.connection,
-            cursor_address: Address::from(0),
-            _ram_type: PhantomData::<DdRam>,
-            _line_marker: PhantomData,
+    pub fn set_ddram_address(self, pos: SetFrom<U>) -> SwitchRamResult<P, U, CgRam, DdRam> {
+        let cgram_address = self.cursor_address();
+        let mut ddram_display = self.switch_ram(Address::<DdRam>::from(0));
+
+        match ddram_display.seek(pos.into()) {
+            Ok(()) => Ok(ddram_display),
+            Err(e) => Err((ddram_display.switch_ram(cgram_address), e)),
+        }
+    }
+}
+
+impl<P, U> Display<P, U, DdRam>
+where
+    P: Send + Wait,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Writes the given character at the cursor position as configured for `write!`.
+    fn write_text(&mut self, c: char) -> fmt::Result {
+        let layout = self.text_layout;
+        let offset = u8::from(self.cursor_address()).wrapping_sub(U::FIRST_LINE_ADDRESS);
+        let position = layout.position(offset);
+        let line = self
+            .truncated_line
+            .get()
+            .or_else(|| position.map(|(line, _)| line));
+
+        let code = match c {
+            '\n' => return self.seek_line(line.map_or(0, |line| layout.next_line(line))),
+            '\r' => return self.seek_line(line.unwrap_or(0)),
+            c => match (text::encode(c), layout.unencodable) {
+                (Some(code), _) | (None, Unencodable::Substitute(code)) => code,
+                (None, Unencodable::Skip) => return Ok(()),
+                (None, Unencodable::Fail) => return Err(fmt::Error),
+            },
         };
 
-        ddram_display.seek(pos.into());
+        if self.truncated_line.get().is_some() {
+            return Ok(());
+        }
+
+        self.write(code).map_err(|_| fmt::Error)?;
+
+        match position {
+            Some((line, column)) if column + 1 >= layout.columns => match layout.overflow {
+                LineOverflow::Wrap => self.seek_line(layout.next_line(line)),
+                LineOverflow::Truncate => {
+                    self.truncated_line.set(Some(line));
+                    Ok(())
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn seek_line(&mut self, line: u8) -> fmt::Result {
+        let address = self.text_layout.line_address(line);
+        self.seek(SeekFrom::Home(address)).map_err(|_| fmt::Error)
+    }
+}
+
+/// Writes formatted text at the cursor position, e.g. by `write!(lcd, "T={:.1}°C", t)`.
+///
+/// The characters are encoded for the character set of the display (ROM code A00), where
+/// `'\u{0}'` to `'\u{7}'` select the custom characters. Characters missing in it are handled as
+/// set by [`set_unencodable()`].
+///
+/// A newline continues at the start of the next line, a carriage return at the start of the
+/// current line. Text reaching the end of a line is wrapped or truncated as set by
+/// [`set_line_overflow()`], according to the geometry set by [`set_geometry()`]. Text written at
+/// an invisible position is not wrapped.
+///
+/// Errors of the connection are reported as `fmt::Error`, use [`write()`] to handle them.
+///
+/// [`set_unencodable()`]: struct.Display.html#method.set_unencodable
+/// [`set_line_overflow()`]: struct.Display.html#method.set_line_overflow
+/// [`set_geometry()`]: struct.Display.html#method.set_geometry
+/// [`write()`]: struct.Display.html#method.write
+impl<P, U> fmt::Write for Display<P, U, DdRam>
+where
+    P: Send + Wait,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().try_for_each(|c| self.write_text(c))
+    }
 
-        ddram_display
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.write_text(c)
     }
 }
@@ -59,6 +59,7 @@ impl From<CursorBlinking> for DisplayControlFlags {
 }
 
 /// A struct for creating display control settings.
+#[derive(Clone, Copy)]
 pub struct DisplayControlBuilder {
     display: DisplayState,
     cursor: CursorState,
@@ -162,7 +163,7 @@ mod tests {
 
         b.set_display(DisplayState::Off);
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, DISPLAY_STATE_FLAG), false);
+        assert!(!has_bit(cmd, DISPLAY_STATE_FLAG));
     }
 
     #[test]
@@ -170,7 +171,7 @@ mod tests {
         let b = DisplayControlBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, CURSOR_STATE_FLAG), false);
+        assert!(!has_bit(cmd, CURSOR_STATE_FLAG));
     }
 
     #[test]
@@ -183,7 +184,7 @@ mod tests {
 
         b.set_cursor(CursorState::Off);
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, CURSOR_STATE_FLAG), false);
+        assert!(!has_bit(cmd, CURSOR_STATE_FLAG));
     }
 
     #[test]
@@ -191,7 +192,7 @@ mod tests {
         let b = DisplayControlBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, CURSOR_BLINKING_FLAG), false);
+        assert!(!has_bit(cmd, CURSOR_BLINKING_FLAG));
     }
 
     #[test]
@@ -204,6 +205,6 @@ mod tests {
 
         b.set_cursor_blinking(CursorBlinking::Off);
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, CURSOR_STATE_FLAG), false);
+        assert!(!has_bit(cmd, CURSOR_STATE_FLAG));
     }
 }
@@ -0,0 +1,427 @@
+use core::cell::Cell;
+
+use crate::address::{Address, Overflow};
+use crate::display::{self, CgRam, DdRam, SeekCgRamFrom, SeekFrom, SetFrom, ShiftTo};
+use crate::error::Error;
+use crate::hal::{Direction, DisplayHardwareLayer, ErrorType, Init, Level, ParallelConnection,
+                 Receive, Send, Wait};
+use crate::{CursorBlinking, CursorState, DefaultLines, DisplayControlBuilder, EntryModeBuilder,
+            FunctionSetBuilder, Home};
+
+/// Enumeration of the controllers of a display, which is driven by two HD44780 controllers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Controller {
+    /// The controller of the upper half, i.e. lines one and two.
+    First,
+    /// The controller of the lower half, i.e. lines three and four.
+    Second,
+    /// Both controllers at the same time, which is only used for writing.
+    Both,
+}
+
+/// This trait is used to select the controller, which receives the following instructions, of a
+/// display with two controllers.
+pub trait SelectController {
+    fn select_controller(&self, controller: Controller);
+}
+
+/// The enable signal of a display with two controllers (e.g. 40x4 modules), which share the
+/// register select, read/write and data pins, but have their own enable pins (E1 and E2).
+///
+/// It is used as `enable` pin of a [`ParallelConnection`] and only pulses the enable pin of the
+/// selected controller.
+///
+/// [`ParallelConnection`]: struct.ParallelConnection.html
+pub struct DualEnable<E1, E2> {
+    first: E1,
+    second: E2,
+    selected: Cell<Controller>,
+}
+
+impl<E1, E2> DualEnable<E1, E2> {
+    /// Creates the enable signal from the enable pins of the first (E1) and second (E2)
+    /// controller, with the first controller selected.
+    pub fn new(first: E1, second: E2) -> Self {
+        DualEnable {
+            first,
+            second,
+            selected: Cell::new(Controller::First),
+        }
+    }
+}
+
+impl<E1, E2> SelectController for DualEnable<E1, E2> {
+    fn select_controller(&self, controller: Controller) {
+        self.selected.set(controller);
+    }
+}
+
+impl<E1, E2> DisplayHardwareLayer for DualEnable<E1, E2>
+where
+    E1: DisplayHardwareLayer,
+    E2: DisplayHardwareLayer<Error = E1::Error>,
+{
+    type Error = E1::Error;
+
+    fn init(&self) -> Result<(), Self::Error> {
+        self.first.init()?;
+        self.second.init()
+    }
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        self.first.cleanup()?;
+        self.second.cleanup()
+    }
+
+    fn set_direction(&self, direction: Direction) -> Result<(), Self::Error> {
+        self.first.set_direction(direction)?;
+        self.second.set_direction(direction)
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), Self::Error> {
+        match self.selected.get() {
+            Controller::First => self.first.set_level(level),
+            Controller::Second => self.second.set_level(level),
+            Controller::Both => {
+                self.first.set_level(level)?;
+                self.second.set_level(level)
+            }
+        }
+    }
+
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        match self.selected.get() {
+            Controller::Second => self.second.get_value(),
+            _ => self.first.get_value(),
+        }
+    }
+}
+
+impl<RS, R, E, D, T, C> SelectController for ParallelConnection<RS, R, E, D, T, C>
+where
+    E: SelectController,
+{
+    fn select_controller(&self, controller: Controller) {
+        self.enable().select_controller(controller);
+    }
+}
+
+/// Enumeration of the lines of a display with two controllers.
+pub enum DualControllerLines {
+    One,
+    Two,
+    Three,
+    Four,
+}
+
+impl Home for DualControllerLines {}
+
+impl<T: Overflow> From<DualControllerLines> for Address<T> {
+    /// Returns the hardware address of the line within its controller.
+    fn from(line: DualControllerLines) -> Self {
+        let (_, line) = line.split();
+
+        line.into()
+    }
+}
+
+impl DualControllerLines {
+    /// Returns the controller of the line and the line within that controller.
+    fn split(self) -> (Controller, DefaultLines) {
+        match self {
+            DualControllerLines::One => (Controller::First, DefaultLines::One),
+            DualControllerLines::Two => (Controller::First, DefaultLines::Two),
+            DualControllerLines::Three => (Controller::Second, DefaultLines::One),
+            DualControllerLines::Four => (Controller::Second, DefaultLines::Two),
+        }
+    }
+}
+
+pub type DdRamDisplay<P> = Display<P, DdRam>;
+
+/// The result of switching a dual controller display from the RAM type `From` to `To`. On
+/// failure the unchanged display is returned together with the error.
+pub type SwitchRamResult<P, From, To> =
+    Result<Display<P, To>, (Display<P, From>, Error<<P as ErrorType>::Error>)>;
+
+/// A display driven by two HD44780 compliant controllers, e.g. a 40x4 LCD.
+///
+/// Seeking to lines three and four routes all following instructions to the second controller.
+/// Initialization, clearing, entry mode and display shifts go to both controllers, just like
+/// the character generator RAM, so that custom characters can be used on all lines. The cursor
+/// is only visible on the active controller.
+pub struct Display<P, RT>
+where
+    RT: Overflow,
+{
+    display: display::Display<P, DefaultLines, RT>,
+    state: ControllerState,
+}
+
+#[derive(Clone, Copy)]
+struct ControllerState {
+    /// The controller, which receives the instructions for a single line.
+    active: Controller,
+    /// The controller, which is currently selected at the connection.
+    selected: Controller,
+    display_control: Option<DisplayControlBuilder>,
+}
+
+impl<P> Display<P, DdRam> {
+    /// Create a new `DualControllerDisplay` using the given connection.
+    pub fn new(connection: P) -> Display<P, DdRam> {
+        Display {
+            display: display::Display::new(connection),
+            state: ControllerState {
+                active: Controller::First,
+                selected: Controller::First,
+                display_control: None,
+            },
+        }
+    }
+}
+
+impl<P, RT> Display<P, RT>
+where
+    RT: Overflow,
+{
+    pub fn get_connection(self) -> P {
+        self.display.get_connection()
+    }
+
+    /// Returns the controller, which receives the instructions for a single line.
+    pub fn active_controller(&self) -> Controller {
+        self.state.active
+    }
+}
+
+impl<P, RT> Display<P, RT>
+where
+    P: SelectController,
+    RT: Overflow,
+{
+    fn select(&mut self, controller: Controller) {
+        self.state.selected = controller;
+        self.display.connection().select_controller(controller);
+    }
+
+    /// Runs the given operation on the given controller and selects the previously selected
+    /// controller afterwards.
+    fn on<F, O>(&mut self, controller: Controller, operation: F) -> O
+    where
+        F: FnOnce(&mut display::Display<P, DefaultLines, RT>) -> O,
+    {
+        self.display.connection().select_controller(controller);
+        let result = operation(&mut self.display);
+        self.display.connection().select_controller(self.state.selected);
+
+        result
+    }
+}
+
+impl<P> Display<P, DdRam>
+where
+    P: Init + Send + Wait + SelectController,
+{
+    /// Initializes both controllers, the first one is active afterwards.
+    ///
+    /// The `builder` should be set to two lines, because every controller drives two lines.
+    pub fn init(&mut self, builder: &FunctionSetBuilder) -> Result<(), Error<P::Error>> {
+        self.state.active = Controller::First;
+        self.select(Controller::First);
+
+        self.on(Controller::Both, |display| display.init(builder))
+    }
+}
+
+impl<P, RT> Display<P, RT>
+where
+    P: Send + Wait + SelectController,
+    RT: Overflow,
+{
+    /// Sets the entry mode of both controllers.
+    pub fn set_entry_mode(&mut self, builder: &EntryModeBuilder) -> Result<(), Error<P::Error>> {
+        self.on(Controller::Both, |display| display.set_entry_mode(builder))
+    }
+
+    /// Sets the display control settings. The cursor settings only apply to the active
+    /// controller, the cursor of the other one is hidden.
+    pub fn set_display_control(
+        &mut self,
+        builder: &DisplayControlBuilder,
+    ) -> Result<(), Error<P::Error>> {
+        self.state.display_control = Some(*builder);
+
+        self.apply_display_control()
+    }
+
+    fn apply_display_control(&mut self) -> Result<(), Error<P::Error>> {
+        let builder = match self.state.display_control {
+            Some(builder) => builder,
+            None => return Ok(()),
+        };
+
+        let inactive = match self.state.active {
+            Controller::First => Controller::Second,
+            _ => Controller::First,
+        };
+
+        let mut hidden_cursor = builder;
+        hidden_cursor
+            .set_cursor(CursorState::Off)
+            .set_cursor_blinking(CursorBlinking::Off);
+
+        self.on(inactive, |display| display.set_display_control(&hidden_cursor))?;
+        self.on(self.state.active, |display| display.set_display_control(&builder))
+    }
+
+    /// Makes the given controller the active one and moves the visible cursor to it.
+    fn activate(&mut self, controller: Controller) -> Result<(), Error<P::Error>> {
+        if self.state.active == controller {
+            return Ok(());
+        }
+
+        self.state.active = controller;
+
+        self.apply_display_control()
+    }
+
+    /// Shifts the cursor to the left or the right by the given offset.
+    pub fn shift_cursor(&mut self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        self.display.shift_cursor(direction)
+    }
+
+    /// Shifts the display of both controllers to the right or the left by the given offset.
+    pub fn shift(&mut self, direction: ShiftTo) -> Result<(), Error<P::Error>> {
+        self.on(Controller::Both, |display| display.shift(direction))
+    }
+
+    /// Writes the given byte to data RAM of the active controller or to character generator RAM
+    /// of both controllers, depending on the previous seek operation.
+    pub fn write(&mut self, c: u8) -> Result<(), Error<P::Error>> {
+        self.display.write(c)
+    }
+
+    /// Writes the given message to data RAM of the active controller or to character generator
+    /// RAM of both controllers, depending on the previous seek operation.
+    pub fn write_message(&mut self, msg: &str) -> Result<(), Error<P::Error>> {
+        self.display.write_message(msg)
+    }
+}
+
+impl<P> Display<P, DdRam>
+where
+    P: Send + Wait + SelectController,
+{
+    /// Clears the entire display and sets the cursor to the home position of the first
+    /// controller.
+    pub fn clear(&mut self) -> Result<(), Error<P::Error>> {
+        self.on(Controller::Both, |display| display.clear())?;
+
+        self.select(Controller::First);
+        self.activate(Controller::First)
+    }
+
+    /// Seeks to an offset in display data RAM. Seeking to lines three and four activates the
+    /// second controller, seeking home or to lines one and two the first one.
+    pub fn seek(&mut self, pos: SeekFrom<DualControllerLines>) -> Result<(), Error<P::Error>> {
+        let (controller, pos) = match pos {
+            SeekFrom::Home(offset) => (Controller::First, SeekFrom::Home(offset)),
+            SeekFrom::Current(offset) => (self.state.active, SeekFrom::Current(offset)),
+            SeekFrom::Line { line, offset } => {
+                let (controller, line) = line.split();
+                (controller, SeekFrom::Line { line, offset })
+            }
+        };
+
+        self.select(controller);
+        self.activate(controller)?;
+
+        self.display.seek(pos)
+    }
+
+    /// Switches both controllers to the character generator RAM (CGRAM) and set the cursor's
+    /// address to the given value.
+    pub fn set_cgram_address(mut self, address: u8) -> SwitchRamResult<P, DdRam, CgRam> {
+        let active = self.state.active;
+        self.select(Controller::Both);
+
+        let (mut display, e) = match self.switch_cgram(address) {
+            Ok(display) => return Ok(display),
+            Err(err) => err,
+        };
+
+        display.select(active);
+        Err((display, e))
+    }
+
+    fn switch_cgram(self, address: u8) -> SwitchRamResult<P, DdRam, CgRam> {
+        let Display { display, state } = self;
+
+        match display.set_cgram_address(address) {
+            Ok(display) => Ok(Display { display, state }),
+            Err((display, e)) => Err((Display { display, state }, e)),
+        }
+    }
+}
+
+/// Reading is only available from the active controller, because both controllers would drive
+/// the data bus at the same time otherwise.
+impl<P> Display<P, DdRam>
+where
+    P: Receive + Wait,
+{
+    /// Reads a single byte from data RAM of the active controller.
+    pub fn read_byte(&mut self) -> Result<u8, Error<P::Error>> {
+        self.display.read_byte()
+    }
+
+    /// Reads busy flag and the cursor's current address of the active controller.
+    pub fn read_busy_flag(&self) -> Result<(bool, u8), Error<P::Error>> {
+        self.display.read_busy_flag()
+    }
+}
+
+impl<P> Display<P, CgRam>
+where
+    P: Send + Wait + SelectController,
+{
+    /// Seeks to an offset in character generator RAM of both controllers.
+    pub fn seek(&mut self, pos: SeekCgRamFrom) -> Result<(), Error<P::Error>> {
+        self.display.seek(pos)
+    }
+
+    /// Switches both controllers to the display data RAM (DDRAM) and set the cursor's address
+    /// to the given value. Setting the address of lines three and four activates the second
+    /// controller, otherwise the first one.
+    pub fn set_ddram_address(
+        mut self,
+        pos: SetFrom<DualControllerLines>,
+    ) -> SwitchRamResult<P, CgRam, DdRam> {
+        let (controller, pos) = match pos {
+            SetFrom::Home(offset) => (Controller::First, SetFrom::Home(offset)),
+            SetFrom::Line { line, offset } => {
+                let (controller, line) = line.split();
+                (controller, SetFrom::Line { line, offset })
+            }
+        };
+
+        // the display control instructions do not change the address counter, so the cursor
+        // can be moved while both controllers still operate on CGRAM
+        if let Err(e) = self.activate(controller) {
+            return Err((self, e));
+        }
+
+        let Display { display, state } = self;
+
+        match display.set_ddram_address(pos) {
+            Ok(display) => {
+                let mut display = Display { display, state };
+                display.select(controller);
+
+                Ok(display)
+            }
+            Err((display, e)) => Err((Display { display, state }, e)),
+        }
+    }
+}
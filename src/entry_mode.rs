@@ -132,7 +132,7 @@ mod tests {
         b.set_move_direction(MoveDirection::Decrement);
 
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, MOVE_DIRECTION_FLAG), false);
+        assert!(!has_bit(cmd, MOVE_DIRECTION_FLAG));
     }
 
     #[test]
@@ -140,7 +140,7 @@ mod tests {
         let b = EntryModeBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, DISPLAY_SHIFT_FLAG), false);
+        assert!(!has_bit(cmd, DISPLAY_SHIFT_FLAG));
     }
 
     #[test]
@@ -148,7 +148,7 @@ mod tests {
         let mut b = EntryModeBuilder::default();
 
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, DISPLAY_SHIFT_FLAG), false);
+        assert!(!has_bit(cmd, DISPLAY_SHIFT_FLAG));
 
         b.set_display_shift(DisplayShift::On);
 
@@ -0,0 +1,53 @@
+use core::fmt;
+
+/// Enumeration of errors reported by a [`Display`].
+///
+/// [`Display`]: struct.Display.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error<E> {
+    /// The connection to the display failed, e.g. because the GPIO or bus device got lost. It
+    /// contains the error reported by the connection.
+    Connection(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Connection(ref e) => write!(f, "display connection failed: {:?}", e),
+        }
+    }
+}
+
+/// Enumeration of errors in the pin map of a connection, which are reported when the connection
+/// is created.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinError {
+    /// The pin does not exist on the device, e.g. port bit `8` of an 8-bit I/O expander.
+    OutOfRange(u8),
+    /// The pin is wired to more than one signal.
+    Duplicate(u8),
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PinError::OutOfRange(pin) => write!(f, "pin {} does not exist on the device", pin),
+            PinError::Duplicate(pin) => write!(f, "pin {} is wired to more than one signal", pin),
+        }
+    }
+}
+
+/// Checks that all given pins are below `count` and that no pin is used twice.
+pub(crate) fn check_pins(pins: &[u8], count: u8) -> Result<(), PinError> {
+    for (i, &pin) in pins.iter().enumerate() {
+        if pin >= count {
+            return Err(PinError::OutOfRange(pin));
+        }
+
+        if pins[..i].contains(&pin) {
+            return Err(PinError::Duplicate(pin));
+        }
+    }
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ bitflags! {
 }
 
 /// Enumeration of possible interface data lengths.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DataLength {
     /// 4-bit mode
     FourBit,
@@ -29,7 +29,7 @@ impl From<DataLength> for FunctionSetFlags {
 }
 
 /// Enumeration to set display line number.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineNumber {
     One,
     Two,
@@ -45,7 +45,7 @@ impl From<LineNumber> for FunctionSetFlags {
 }
 
 /// Enumeration to set display character font.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CharacterFont {
     Dots5By10,
     Dots5By8,
@@ -83,6 +83,10 @@ impl FunctionSetBuilder {
         self
     }
 
+    pub(crate) fn data_length(&self) -> DataLength {
+        self.data_length
+    }
+
     pub(crate) fn build_command(&self) -> u8 {
         let mut cmd = FunctionSetFlags::FUNCTION_SET;
 
@@ -140,7 +144,7 @@ mod tests {
         let b = FunctionSetBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, DATA_LENGTH_FLAG), false);
+        assert!(!has_bit(cmd, DATA_LENGTH_FLAG));
     }
 
     #[test]
@@ -148,7 +152,7 @@ mod tests {
         let mut b = FunctionSetBuilder::default();
 
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, DATA_LENGTH_FLAG), false);
+        assert!(!has_bit(cmd, DATA_LENGTH_FLAG));
 
         b.set_data_length(DataLength::EightBit);
 
@@ -161,7 +165,7 @@ mod tests {
         let b = FunctionSetBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, LINE_NUMBER_FLAG), false);
+        assert!(!has_bit(cmd, LINE_NUMBER_FLAG));
     }
 
     #[test]
@@ -169,7 +173,7 @@ mod tests {
         let mut b = FunctionSetBuilder::default();
 
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, LINE_NUMBER_FLAG), false);
+        assert!(!has_bit(cmd, LINE_NUMBER_FLAG));
 
         b.set_line_number(LineNumber::Two);
 
@@ -182,7 +186,7 @@ mod tests {
         let b = FunctionSetBuilder::default();
         let cmd = b.build_command();
 
-        assert_eq!(has_bit(cmd, CHARACTER_FONT_FLAG), false);
+        assert!(!has_bit(cmd, CHARACTER_FONT_FLAG));
     }
 
     #[test]
@@ -190,7 +194,7 @@ mod tests {
         let mut b = FunctionSetBuilder::default();
 
         let cmd = b.build_command();
-        assert_eq!(has_bit(cmd, CHARACTER_FONT_FLAG), false);
+        assert!(!has_bit(cmd, CHARACTER_FONT_FLAG));
 
         b.set_character_font(CharacterFont::Dots5By10);
 
@@ -1,27 +1,160 @@
-use core::marker::PhantomData;
+use core::fmt::Debug;
+
+use crate::contrast::NoContrast;
+
+/// Implements the given connection traits for a wrapper type by passing every call through to
+/// the wrapped connection in its `connection` field, whose type is the parameter after `=>`.
+macro_rules! forward_connection {
+    (impl<$($param:tt),+> $wrapper:ty => $inner:ident: $($name:ident),+) => {
+        forward_connection!(@each [$($param),+] $wrapper, $inner; $($name),+);
+    };
+    (@each $params:tt $wrapper:ty, $inner:ident; $($name:ident),+) => {
+        $(forward_connection!(@$name $params $wrapper, $inner);)+
+    };
+    (@ErrorType [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::ErrorType for $wrapper
+        where
+            $inner: $crate::hal::ErrorType,
+        {
+            type Error = <$inner as $crate::hal::ErrorType>::Error;
+        }
+    };
+    (@Init [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::Init for $wrapper
+        where
+            $inner: $crate::hal::Init,
+        {
+            fn init(&self) -> Result<(), Self::Error> {
+                self.connection.init()
+            }
+        }
+    };
+    (@Release [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::Release for $wrapper
+        where
+            $inner: $crate::hal::Release,
+        {
+            type Pins = <$inner as $crate::hal::Release>::Pins;
+
+            fn cleanup(&self) -> Result<(), Self::Error> {
+                self.connection.cleanup()
+            }
+
+            fn into_pins(self) -> Self::Pins {
+                self.connection.into_pins()
+            }
+        }
+    };
+    (@Send [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::Send for $wrapper
+        where
+            $inner: $crate::hal::Send,
+        {
+            fn send(&self, mode: $crate::hal::WriteMode) -> Result<(), Self::Error> {
+                self.connection.send(mode)
+            }
+
+            fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+                self.connection.send_upper_nibble(cmd)
+            }
+        }
+    };
+    (@Receive [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::Receive for $wrapper
+        where
+            $inner: $crate::hal::Receive,
+        {
+            fn receive(&self, mode: $crate::hal::ReadMode) -> Result<u8, Self::Error> {
+                self.connection.receive(mode)
+            }
+        }
+    };
+    (@Wait [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::hal::Wait for $wrapper
+        where
+            $inner: $crate::hal::Wait,
+        {
+            fn wait(&self, mode: $crate::hal::WriteMode) -> Result<(), Self::Error> {
+                self.connection.wait(mode)
+            }
+
+            fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+                self.connection.wait_us(us)
+            }
+        }
+    };
+    (@SelectController [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::dual_controller::SelectController for $wrapper
+        where
+            $inner: $crate::dual_controller::SelectController,
+        {
+            fn select_controller(&self, controller: $crate::dual_controller::Controller) {
+                self.connection.select_controller(controller);
+            }
+        }
+    };
+    (@Backlight [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::backlight::Backlight for $wrapper
+        where
+            $inner: $crate::backlight::Backlight,
+        {
+            fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+                self.connection.set_backlight(enabled)
+            }
+        }
+    };
+    (@Brightness [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::backlight::Brightness for $wrapper
+        where
+            $inner: $crate::backlight::Brightness,
+        {
+            fn brightness(&self) -> u8 {
+                self.connection.brightness()
+            }
+
+            fn set_brightness(&self, brightness: u8) -> Result<(), Self::Error> {
+                self.connection.set_brightness(brightness)
+            }
+        }
+    };
+    (@Contrast [$($param:tt),+] $wrapper:ty, $inner:ident) => {
+        impl<$($param),+> $crate::contrast::Contrast for $wrapper
+        where
+            $inner: $crate::contrast::Contrast,
+        {
+            fn set_contrast(&self, level: u8) -> Result<(), Self::Error> {
+                self.connection.set_contrast(level)
+            }
+        }
+    };
+}
 
 /// Enumeration possible write operations.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum WriteMode {
     Command(u8),
     Data(u8),
 }
 
+const CLEAR_DISPLAY: u8 = 0b0000_0001;
+const RETURN_HOME: u8 = 0b0000_0010;
+
 /// Enumeration possible read operations.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ReadMode {
     Data,
     BusyFlag,
 }
 
 /// Enumeration of possible data directions of a pin.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Direction {
     In,
     Out,
 }
 
 /// Enumeration of possible levels of a pin.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Level {
     Low,
     High,
@@ -36,7 +169,7 @@ impl From<WriteMode> for (Level, u8) {
     }
 }
 
-enum Nibble {
+pub(crate) enum Nibble {
     Upper(u8),
     Lower(u8),
 }
@@ -50,79 +183,370 @@ impl From<Nibble> for u8 {
     }
 }
 
+/// This trait is used to define the error type, which is shared by all traits of a [`Display`]
+/// connection.
+///
+/// [`Display`]: struct.Display.html
+pub trait ErrorType {
+    /// The error reported by the connection, e.g. if a GPIO or bus device got lost.
+    type Error: Debug;
+}
+
 /// This trait is used to provide an initialization implementation for a [`Display`] connection.
 ///
 /// [`Display`]: struct.Display.html
-pub trait Init {
+pub trait Init: ErrorType {
     /// Initializes the connection.
-    fn init(&self);
+    fn init(&self) -> Result<(), Self::Error>;
+}
+
+/// This trait is used to tear down a [`Display`] connection and to release its pins.
+///
+/// [`Display`]: struct.Display.html
+pub trait Release: ErrorType {
+    /// The pins handed back by the connection.
+    type Pins;
+
+    /// Cleans up all pins, even if cleaning up one of them fails. The first error is reported.
+    fn cleanup(&self) -> Result<(), Self::Error>;
+
+    /// Returns the pins without cleaning them up.
+    fn into_pins(self) -> Self::Pins;
 }
 
 /// This trait is used to provide an implementation for sending data via a [`Display`] connection.
 ///
 /// [`Display`]: struct.Display.html
-pub trait Send {
+pub trait Send: ErrorType {
     /// Sends data via the connection.
-    fn send(&self, mode: WriteMode);
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error>;
+
+    /// Sends only the upper nibble of a command on connections with a 4-bit interface, as needed
+    /// to initialize a display, whose interface data length is unknown. Connections with an 8-bit
+    /// interface send the whole command.
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        self.send(WriteMode::Command(cmd))
+    }
 }
 
 /// This trait is used to provide an implementation for receiving data via a [`Display`] connection.
 ///
 /// [`Display`]: struct.Display.html
-pub trait Receive {
-    fn receive(&self, mode: ReadMode) -> u8;
+pub trait Receive: ErrorType {
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error>;
 }
 
-pub trait SendRaw {
-    fn send_byte(&self, byte: u8);
+/// This trait is used to provide an implementation for waiting until a [`Display`] has executed
+/// an instruction.
+///
+/// [`Display`]: struct.Display.html
+pub trait Wait: ErrorType {
+    /// Waits until the given write operation has been executed.
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error>;
+
+    /// Waits for the given amount of microseconds.
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error>;
+}
+
+pub trait SendRaw: ErrorType {
+    fn send_byte(&self, byte: u8) -> Result<(), Self::Error>;
+
+    /// Sends only the upper nibble of the given byte on a 4-bit interface, otherwise the whole
+    /// byte.
+    fn send_nibble(&self, byte: u8) -> Result<(), Self::Error> {
+        self.send_byte(byte)
+    }
 }
 
-pub trait ReceiveRaw {
-    fn receive_byte(&self) -> u8;
+pub trait ReceiveRaw: ErrorType {
+    fn receive_byte(&self) -> Result<u8, Self::Error>;
 }
 
 /// The `DisplayHardwareLayer` trait is intended to be implemented by the library user as a thin
 /// wrapper around the hardware specific system calls.
 pub trait DisplayHardwareLayer {
+    /// The error reported by the hardware specific system calls.
+    type Error: Debug;
+
     /// Initializes an I/O pin.
-    fn init(&self) {}
+    fn init(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
     /// Cleanup an I/O pin.
-    fn cleanup(&self) {}
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 
-    fn set_direction(&self, Direction);
+    fn set_direction(&self, direction: Direction) -> Result<(), Self::Error>;
     /// Sets a value on an I/O pin.
-    fn set_level(&self, Level);
+    fn set_level(&self, level: Level) -> Result<(), Self::Error>;
 
-    fn get_value(&self) -> u8;
+    fn get_value(&self) -> Result<u8, Self::Error>;
+}
+
+/// Pins are shared by reference, e.g. the data lines of a [`SharedBus`].
+///
+/// [`SharedBus`]: struct.SharedBus.html
+impl<P: DisplayHardwareLayer> DisplayHardwareLayer for &P {
+    type Error = P::Error;
+
+    fn init(&self) -> Result<(), Self::Error> {
+        (**self).init()
+    }
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        (**self).cleanup()
+    }
+
+    fn set_direction(&self, direction: Direction) -> Result<(), Self::Error> {
+        (**self).set_direction(direction)
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), Self::Error> {
+        (**self).set_level(level)
+    }
+
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        (**self).get_value()
+    }
 }
 
 /// The `Delay` trait is used to adapt the timing to the specific hardware and must be implemented
 /// by the libary user.
+///
+/// A delay is owned by the connection, so it can hold e.g. a timer peripheral. The durations
+/// to wait are given by the [`Timing`] of the connection.
+///
+/// [`Timing`]: struct.Timing.html
 pub trait Delay {
+    /// Wait for the given amount of nanoseconds.
+    fn delay_ns(&self, ns: u32);
+
+    /// Wait for the given amount of microseconds.
+    fn delay_us(&self, us: u32) {
+        const MAX_CHUNK: u32 = u32::MAX / 1000;
+
+        let mut remaining = us;
+        while remaining > 0 {
+            let chunk = remaining.min(MAX_CHUNK);
+            self.delay_ns(chunk * 1000);
+            remaining -= chunk;
+        }
+    }
+
+    /// Wait for the given amount of milliseconds.
+    fn delay_ms(&self, ms: u32) {
+        const MAX_CHUNK: u32 = u32::MAX / 1000;
+
+        let mut remaining = ms;
+        while remaining > 0 {
+            let chunk = remaining.min(MAX_CHUNK);
+            self.delay_us(chunk * 1000);
+            remaining -= chunk;
+        }
+    }
+}
+
+/// Delays are shared by reference, e.g. by the connections of a [`SharedBus`].
+///
+/// [`SharedBus`]: struct.SharedBus.html
+impl<T: Delay> Delay for &T {
+    fn delay_ns(&self, ns: u32) {
+        (**self).delay_ns(ns)
+    }
+
+    fn delay_us(&self, us: u32) {
+        (**self).delay_us(us)
+    }
+
+    fn delay_ms(&self, ms: u32) {
+        (**self).delay_ms(ms)
+    }
+}
+
+/// The timing of the bus signals and the execution times of instructions, which depend on the
+/// supply voltage and the clock of the controller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timing {
     /// The time (ns) between register select (RS) and read/write (R/W) to enable signal (E).
-    const ADDRESS_SETUP_TIME: u16 = 60;
+    pub address_setup_time: u32,
     /// The duration (ns) the enable signal is set to `High`.
-    const ENABLE_PULSE_WIDTH: u16 = 450;
+    pub enable_pulse_width: u32,
     /// The duration (ns) the data pins will be set after the enable signal was dropped.
-    const DATA_HOLD_TIME: u16 = 20;
+    pub data_hold_time: u32,
+    /// The maximum execution time (µs) of instruction commands.
+    pub command_execution_time: u32,
+    /// The maximum execution time (µs) of the clear display command.
+    pub clear_display_execution_time: u32,
+    /// The maximum execution time (µs) of the return home command.
+    pub return_home_execution_time: u32,
+}
 
-    /// The maximum execution time of instruction commands.
-    const COMMAND_EXECUTION_TIME: u16 = 37;
+impl Timing {
+    /// The timing for a supply voltage of 4.5 V to 5.5 V.
+    pub const VCC_5V: Timing = Timing {
+        address_setup_time: 40,
+        enable_pulse_width: 230,
+        data_hold_time: 10,
+        command_execution_time: 37,
+        clear_display_execution_time: 1520,
+        return_home_execution_time: 1520,
+    };
 
-    /// Wait for the given amount of nanoseconds.
-    fn delay_ns(ns: u16);
+    /// The timing for a supply voltage of 2.7 V to 4.5 V.
+    pub const VCC_3V: Timing = Timing {
+        address_setup_time: 60,
+        enable_pulse_width: 450,
+        data_hold_time: 20,
+        command_execution_time: 37,
+        clear_display_execution_time: 1520,
+        return_home_execution_time: 1520,
+    };
 
-    /// Wait for the given amount of microseconds.
-    fn delay_us(us: u16) {
-        for _ in 0..us {
-            Self::delay_ns(1000);
+    /// Returns the maximum execution time (µs) of the given write operation.
+    pub fn execution_time(&self, mode: WriteMode) -> u32 {
+        match mode {
+            WriteMode::Command(CLEAR_DISPLAY) => self.clear_display_execution_time,
+            WriteMode::Command(cmd) if cmd & !0b0000_0001 == RETURN_HOME => {
+                self.return_home_execution_time
+            }
+            _ => self.command_execution_time,
         }
     }
 }
 
+impl Default for Timing {
+    /// Returns the timing for the lower supply voltage, which works at any voltage.
+    fn default() -> Self {
+        Timing::VCC_3V
+    }
+}
+
+/// A marker for the read/write (R/W) pin of displays which are wired write-only, i.e. with R/W
+/// tied to ground.
+///
+/// A [`ParallelConnection`] without a read pin is able to send to the display, but not to
+/// receive from it, so reading operations of `Display` are not available for it.
+///
+/// ```
+/// # use clerk::{DataPins4Lines, Delay, Direction, DisplayHardwareLayer, Grounded, Level, Pins};
+/// # struct Pin;
+/// # impl DisplayHardwareLayer for Pin {
+/// #     type Error = ();
+/// #     fn set_direction(&self, _: Direction) -> Result<(), ()> { Ok(()) }
+/// #     fn set_level(&self, _: Level) -> Result<(), ()> { Ok(()) }
+/// #     fn get_value(&self) -> Result<u8, ()> { Ok(0) }
+/// # }
+/// # struct CustomDelay;
+/// # impl Delay for CustomDelay {
+/// #     fn delay_ns(&self, _: u32) {}
+/// # }
+/// let pins = Pins {
+///     register_select: Pin,
+///     read: Grounded,
+///     enable: Pin,
+///     data: DataPins4Lines {
+///         data4: Pin,
+///         data5: Pin,
+///         data6: Pin,
+///         data7: Pin,
+///     },
+/// };
+///
+/// let connection = pins.into_connection(CustomDelay);
+/// ```
+///
+/// Reading from a write-only connection is rejected at compile time:
+///
+/// ```compile_fail
+/// # use clerk::{DataPins4Lines, DefaultLines, Delay, Direction, Display, DisplayHardwareLayer,
+/// #             Grounded, Level, Pins};
+/// # struct Pin;
+/// # impl DisplayHardwareLayer for Pin {
+/// #     type Error = ();
+/// #     fn set_direction(&self, _: Direction) -> Result<(), ()> { Ok(()) }
+/// #     fn set_level(&self, _: Level) -> Result<(), ()> { Ok(()) }
+/// #     fn get_value(&self) -> Result<u8, ()> { Ok(0) }
+/// # }
+/// # struct CustomDelay;
+/// # impl Delay for CustomDelay {
+/// #     fn delay_ns(&self, _: u32) {}
+/// # }
+/// # let pins = Pins {
+/// #     register_select: Pin,
+/// #     read: Grounded,
+/// #     enable: Pin,
+/// #     data: DataPins4Lines { data4: Pin, data5: Pin, data6: Pin, data7: Pin },
+/// # };
+/// let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(CustomDelay));
+///
+/// lcd.read_byte();
+/// ```
+///
+/// [`ParallelConnection`]: struct.ParallelConnection.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grounded;
+
+/// This trait is used to drive the read/write (R/W) signal of a [`ParallelConnection`], which
+/// reports errors of type `E`.
+///
+/// It is implemented for every [`DisplayHardwareLayer`] and for [`Grounded`].
+///
+/// [`ParallelConnection`]: struct.ParallelConnection.html
+/// [`DisplayHardwareLayer`]: trait.DisplayHardwareLayer.html
+/// [`Grounded`]: struct.Grounded.html
+pub trait ReadWriteSignal<E> {
+    /// Initializes the signal as output.
+    fn init_signal(&self) -> Result<(), E>;
+    /// Selects write operations.
+    fn select_write(&self) -> Result<(), E>;
+    /// Cleans up the signal.
+    fn cleanup_signal(&self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl<P: DisplayHardwareLayer> ReadWriteSignal<P::Error> for P {
+    fn init_signal(&self) -> Result<(), P::Error> {
+        self.init()?;
+        self.set_direction(Direction::Out)
+    }
+
+    fn cleanup_signal(&self) -> Result<(), P::Error> {
+        self.cleanup()
+    }
+
+    fn select_write(&self) -> Result<(), P::Error> {
+        self.set_level(Level::Low)
+    }
+}
+
+impl<E> ReadWriteSignal<E> for Grounded {
+    fn init_signal(&self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn select_write(&self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl<E> ReadWriteSignal<E> for &Grounded {
+    fn init_signal(&self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn select_write(&self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
 /// This struct is used for easily setting up [`ParallelConnection`]s.
 ///
+/// Displays wired write-only use [`Grounded`] as `read` pin.
+///
 /// [`ParallelConnection`]: struct.ParallelConnection.html
+/// [`Grounded`]: struct.Grounded.html
 pub struct Pins<RS, R, E, D> {
     pub register_select: RS,
     pub read: R,
@@ -135,13 +559,15 @@ impl<RS, R, E, D> Pins<RS, R, E, D> {
     /// with the LCD device.
     ///
     /// [`ParallelConnection`]: struct.ParallelConnection.html
-    pub fn into_connection<T>(self) -> ParallelConnection<RS, R, E, D, T> {
+    pub fn into_connection<T>(self, delay: T) -> ParallelConnection<RS, R, E, D, T> {
         ParallelConnection {
             register_select: self.register_select,
             read: self.read,
             enable: self.enable,
             data: self.data,
-            _delay_marker: PhantomData,
+            contrast: NoContrast,
+            delay,
+            timing: Timing::default(),
         }
     }
 }
@@ -149,107 +575,273 @@ impl<RS, R, E, D> Pins<RS, R, E, D> {
 /// The parallel connection mode is the most common wiring mode for HD44780 compliant displays.
 /// It can be used with either four ([`DataPins4Lines`]) or eight ([`DataPins8Lines`]) data lines.
 ///
+/// All pins have to report the same error type, which is used as error type of the connection.
+///
+/// The contrast voltage is set by a potentiometer ([`NoContrast`]), unless a contrast control is
+/// added by [`with_contrast()`].
+///
 /// [`DataPins4Lines`]: struct.DataPins4Lines.html
 /// [`DataPins8Lines`]: struct.DataPins8Lines.html
-pub struct ParallelConnection<RS, R, E, D, T> {
+/// [`NoContrast`]: struct.NoContrast.html
+/// [`with_contrast()`]: struct.ParallelConnection.html#method.with_contrast
+pub struct ParallelConnection<RS, R, E, D, T, C = NoContrast> {
     register_select: RS,
     read: R,
     enable: E,
     data: D,
-    _delay_marker: PhantomData<T>,
+    contrast: C,
+    delay: T,
+    timing: Timing,
+}
+
+impl<RS, R, E, D, T, C> ParallelConnection<RS, R, E, D, T, C> {
+    /// Adds a contrast control (e.g. a [`PwmContrast`] or [`DacContrast`]), which drives the
+    /// contrast voltage (V0) instead of a potentiometer.
+    ///
+    /// [`PwmContrast`]: struct.PwmContrast.html
+    /// [`DacContrast`]: struct.DacContrast.html
+    pub fn with_contrast<N>(self, contrast: N) -> ParallelConnection<RS, R, E, D, T, N> {
+        ParallelConnection {
+            register_select: self.register_select,
+            read: self.read,
+            enable: self.enable,
+            data: self.data,
+            contrast,
+            delay: self.delay,
+            timing: self.timing,
+        }
+    }
+
+    /// Sets the timing of the bus, which defaults to the timing for the lower supply voltage
+    /// ([`Timing::VCC_3V`]).
+    ///
+    /// [`Timing::VCC_3V`]: struct.Timing.html#associatedconstant.VCC_3V
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    pub(crate) fn enable(&self) -> &E {
+        &self.enable
+    }
+
+    pub(crate) fn into_enable(self) -> E {
+        self.enable
+    }
+
+    pub(crate) fn contrast(&self) -> &C {
+        &self.contrast
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn delay(&self) -> &T {
+        &self.delay
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn timing(&self) -> &Timing {
+        &self.timing
+    }
 }
 
-impl<RS, R, E, D, T> Init for ParallelConnection<RS, R, E, D, T>
+impl<RS, R, E, D, T, C> ErrorType for ParallelConnection<RS, R, E, D, T, C>
 where
     RS: DisplayHardwareLayer,
-    R: DisplayHardwareLayer,
-    E: DisplayHardwareLayer,
-    D: Init,
+{
+    type Error = RS::Error;
+}
+
+impl<RS, R, E, D, T, C> Init for ParallelConnection<RS, R, E, D, T, C>
+where
+    RS: DisplayHardwareLayer,
+    R: ReadWriteSignal<RS::Error>,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    D: Init<Error = RS::Error>,
     T: Delay,
 {
-    fn init(&self) {
-        self.register_select.init();
-        self.register_select.set_direction(Direction::Out);
+    fn init(&self) -> Result<(), Self::Error> {
+        self.register_select.init()?;
+        self.register_select.set_direction(Direction::Out)?;
+
+        self.read.init_signal()?;
+
+        self.enable.init()?;
+        self.enable.set_direction(Direction::Out)?;
+
+        self.data.init()
+    }
+}
 
-        self.read.init();
-        self.read.set_direction(Direction::Out);
+impl<RS, R, E, D, T, C> Release for ParallelConnection<RS, R, E, D, T, C>
+where
+    RS: DisplayHardwareLayer,
+    R: ReadWriteSignal<RS::Error>,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    D: Release<Error = RS::Error>,
+{
+    type Pins = Pins<RS, R, E, D>;
 
-        self.enable.init();
-        self.enable.set_direction(Direction::Out);
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        first_error([
+            self.register_select.cleanup(),
+            self.read.cleanup_signal(),
+            self.enable.cleanup(),
+            self.data.cleanup(),
+        ])
+    }
 
-        self.data.init();
+    fn into_pins(self) -> Self::Pins {
+        Pins {
+            register_select: self.register_select,
+            read: self.read,
+            enable: self.enable,
+            data: self.data,
+        }
     }
 }
 
-impl<RS, R, E, D, T> Send for ParallelConnection<RS, R, E, D, T>
+impl<RS, R, E, D, T, C> Send for ParallelConnection<RS, R, E, D, T, C>
 where
-    Self: SendRaw,
+    Self: SendRaw<Error = RS::Error>,
     RS: DisplayHardwareLayer,
-    R: DisplayHardwareLayer,
+    R: ReadWriteSignal<RS::Error>,
 {
-    fn send(&self, mode: WriteMode) {
-        self.read.set_level(Level::Low);
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.read.select_write()?;
 
         let (level, value) = mode.into();
-        self.register_select.set_level(level);
+        self.register_select.set_level(level)?;
+
+        self.send_byte(value)
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        self.read.select_write()?;
+        self.register_select.set_level(Level::Low)?;
 
-        self.send_byte(value);
+        self.send_nibble(cmd)
     }
 }
 
-impl<RS, R, E, D, T> Receive for ParallelConnection<RS, R, E, D, T>
+impl<RS, R, E, D, T, C> Receive for ParallelConnection<RS, R, E, D, T, C>
 where
-    Self: ReceiveRaw,
+    Self: ReceiveRaw<Error = RS::Error>,
     RS: DisplayHardwareLayer,
-    R: DisplayHardwareLayer,
+    R: DisplayHardwareLayer<Error = RS::Error>,
 {
-    fn receive(&self, mode: ReadMode) -> u8 {
-        self.read.set_level(Level::High);
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+        self.read.set_level(Level::High)?;
 
         match mode {
-            ReadMode::Data => self.register_select.set_level(Level::High),
-            ReadMode::BusyFlag => self.register_select.set_level(Level::Low),
+            ReadMode::Data => self.register_select.set_level(Level::High)?,
+            ReadMode::BusyFlag => self.register_select.set_level(Level::Low)?,
         };
 
         self.receive_byte()
     }
 }
 
-// FIXME: WARNING - dummy implementation, not tested
-impl<RS, R, E, T, P0, P1, P2, P3, P4, P5, P6, P7> SendRaw
-    for ParallelConnection<RS, R, E, DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>, T>
+impl<RS, R, E, D, T, C> Wait for ParallelConnection<RS, R, E, D, T, C>
 where
-    E: DisplayHardwareLayer,
+    RS: DisplayHardwareLayer,
     T: Delay,
-    P0: DisplayHardwareLayer,
-    P0: DisplayHardwareLayer,
-    P1: DisplayHardwareLayer,
-    P2: DisplayHardwareLayer,
-    P3: DisplayHardwareLayer,
-    P4: DisplayHardwareLayer,
-    P5: DisplayHardwareLayer,
-    P6: DisplayHardwareLayer,
-    P7: DisplayHardwareLayer,
 {
-    fn send_byte(&self, byte: u8) {
-        T::delay_ns(T::ADDRESS_SETUP_TIME);
-        self.enable.set_level(Level::High);
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.delay.delay_us(self.timing.execution_time(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+}
+
+impl<RS, R, E, T, C, P0, P1, P2, P3, P4, P5, P6, P7> SendRaw
+    for ParallelConnection<RS, R, E, DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>, T, C>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    T: Delay,
+    P0: DisplayHardwareLayer<Error = RS::Error>,
+    P1: DisplayHardwareLayer<Error = RS::Error>,
+    P2: DisplayHardwareLayer<Error = RS::Error>,
+    P3: DisplayHardwareLayer<Error = RS::Error>,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
+{
+    fn send_byte(&self, byte: u8) -> Result<(), Self::Error> {
+        self.data.set_direction(Direction::Out)?;
 
-        self.data.data0.set_level(get_bit(byte, 0b0000_0001));
-        self.data.data1.set_level(get_bit(byte, 0b0000_0010));
-        self.data.data2.set_level(get_bit(byte, 0b0000_0100));
-        self.data.data3.set_level(get_bit(byte, 0b0000_1000));
-        self.data.data4.set_level(get_bit(byte, 0b0001_0000));
-        self.data.data5.set_level(get_bit(byte, 0b0010_0000));
-        self.data.data6.set_level(get_bit(byte, 0b0100_0000));
-        self.data.data7.set_level(get_bit(byte, 0b1000_0000));
+        self.delay.delay_ns(self.timing.address_setup_time);
+        self.enable.set_level(Level::High)?;
 
-        T::delay_ns(T::ENABLE_PULSE_WIDTH);
-        self.enable.set_level(Level::Low);
-        T::delay_ns(T::DATA_HOLD_TIME);
+        self.data.data0.set_level(get_bit(byte, 0b0000_0001))?;
+        self.data.data1.set_level(get_bit(byte, 0b0000_0010))?;
+        self.data.data2.set_level(get_bit(byte, 0b0000_0100))?;
+        self.data.data3.set_level(get_bit(byte, 0b0000_1000))?;
+        self.data.data4.set_level(get_bit(byte, 0b0001_0000))?;
+        self.data.data5.set_level(get_bit(byte, 0b0010_0000))?;
+        self.data.data6.set_level(get_bit(byte, 0b0100_0000))?;
+        self.data.data7.set_level(get_bit(byte, 0b1000_0000))?;
+
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+        self.enable.set_level(Level::Low)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(())
+    }
+}
+
+impl<RS, R, E, T, C, P0, P1, P2, P3, P4, P5, P6, P7> ReceiveRaw
+    for ParallelConnection<RS, R, E, DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>, T, C>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    T: Delay,
+    P0: DisplayHardwareLayer<Error = RS::Error>,
+    P1: DisplayHardwareLayer<Error = RS::Error>,
+    P2: DisplayHardwareLayer<Error = RS::Error>,
+    P3: DisplayHardwareLayer<Error = RS::Error>,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
+{
+    fn receive_byte(&self) -> Result<u8, Self::Error> {
+        self.data.set_direction(Direction::In)?;
+
+        let mut result = 0u8;
+
+        self.delay.delay_ns(self.timing.address_setup_time);
+        self.enable.set_level(Level::High)?;
+
+        result |= self.data.data7.get_value()? << 7;
+        result |= self.data.data6.get_value()? << 6;
+        result |= self.data.data5.get_value()? << 5;
+        result |= self.data.data4.get_value()? << 4;
+        result |= self.data.data3.get_value()? << 3;
+        result |= self.data.data2.get_value()? << 2;
+        result |= self.data.data1.get_value()? << 1;
+        result |= self.data.data0.get_value()?;
+
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+        self.enable.set_level(Level::Low)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        self.data.set_direction(Direction::Out)?;
+
+        Ok(result)
     }
 }
 
+/// Returns the first error of the given results, which have all been evaluated.
+pub(crate) fn first_error<E, const N: usize>(results: [Result<(), E>; N]) -> Result<(), E> {
+    IntoIterator::into_iter(results).collect()
+}
+
 fn get_bit(val: u8, bit: u8) -> Level {
     if val & bit == bit {
         Level::High
@@ -280,9 +872,8 @@ where
     pub data7: P7,
 }
 
-impl<P0, P1, P2, P3, P4, P5, P6, P7> Init for DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
+impl<P0, P1, P2, P3, P4, P5, P6, P7> ErrorType for DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
 where
-    P0: DisplayHardwareLayer,
     P0: DisplayHardwareLayer,
     P1: DisplayHardwareLayer,
     P2: DisplayHardwareLayer,
@@ -292,16 +883,84 @@ where
     P6: DisplayHardwareLayer,
     P7: DisplayHardwareLayer,
 {
-    fn init(&self) {
+    type Error = P0::Error;
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> Init for DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: DisplayHardwareLayer,
+    P1: DisplayHardwareLayer<Error = P0::Error>,
+    P2: DisplayHardwareLayer<Error = P0::Error>,
+    P3: DisplayHardwareLayer<Error = P0::Error>,
+    P4: DisplayHardwareLayer<Error = P0::Error>,
+    P5: DisplayHardwareLayer<Error = P0::Error>,
+    P6: DisplayHardwareLayer<Error = P0::Error>,
+    P7: DisplayHardwareLayer<Error = P0::Error>,
+{
+    fn init(&self) -> Result<(), Self::Error> {
         // TODO maybe not needed because of pin state config
-        self.data0.init();
-        self.data1.init();
-        self.data2.init();
-        self.data3.init();
-        self.data4.init();
-        self.data5.init();
-        self.data6.init();
-        self.data7.init();
+        self.data0.init()?;
+        self.data1.init()?;
+        self.data2.init()?;
+        self.data3.init()?;
+        self.data4.init()?;
+        self.data5.init()?;
+        self.data6.init()?;
+        self.data7.init()
+    }
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> Release for DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: DisplayHardwareLayer,
+    P1: DisplayHardwareLayer<Error = P0::Error>,
+    P2: DisplayHardwareLayer<Error = P0::Error>,
+    P3: DisplayHardwareLayer<Error = P0::Error>,
+    P4: DisplayHardwareLayer<Error = P0::Error>,
+    P5: DisplayHardwareLayer<Error = P0::Error>,
+    P6: DisplayHardwareLayer<Error = P0::Error>,
+    P7: DisplayHardwareLayer<Error = P0::Error>,
+{
+    type Pins = Self;
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        first_error([
+            self.data0.cleanup(),
+            self.data1.cleanup(),
+            self.data2.cleanup(),
+            self.data3.cleanup(),
+            self.data4.cleanup(),
+            self.data5.cleanup(),
+            self.data6.cleanup(),
+            self.data7.cleanup(),
+        ])
+    }
+
+    fn into_pins(self) -> Self::Pins {
+        self
+    }
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: DisplayHardwareLayer,
+    P1: DisplayHardwareLayer<Error = P0::Error>,
+    P2: DisplayHardwareLayer<Error = P0::Error>,
+    P3: DisplayHardwareLayer<Error = P0::Error>,
+    P4: DisplayHardwareLayer<Error = P0::Error>,
+    P5: DisplayHardwareLayer<Error = P0::Error>,
+    P6: DisplayHardwareLayer<Error = P0::Error>,
+    P7: DisplayHardwareLayer<Error = P0::Error>,
+{
+    fn set_direction(&self, direction: Direction) -> Result<(), P0::Error> {
+        self.data0.set_direction(direction)?;
+        self.data1.set_direction(direction)?;
+        self.data2.set_direction(direction)?;
+        self.data3.set_direction(direction)?;
+        self.data4.set_direction(direction)?;
+        self.data5.set_direction(direction)?;
+        self.data6.set_direction(direction)?;
+        self.data7.set_direction(direction)
     }
 }
 
@@ -319,137 +978,393 @@ where
     pub data7: P7,
 }
 
-impl<P4, P5, P6, P7> Init for DataPins4Lines<P4, P5, P6, P7>
+impl<P4, P5, P6, P7> ErrorType for DataPins4Lines<P4, P5, P6, P7>
 where
     P4: DisplayHardwareLayer,
     P5: DisplayHardwareLayer,
     P6: DisplayHardwareLayer,
     P7: DisplayHardwareLayer,
 {
-    fn init(&self) {
-        self.data4.init();
-        self.data5.init();
-        self.data6.init();
-        self.data7.init();
+    type Error = P4::Error;
+}
+
+impl<P4, P5, P6, P7> Init for DataPins4Lines<P4, P5, P6, P7>
+where
+    P4: DisplayHardwareLayer,
+    P5: DisplayHardwareLayer<Error = P4::Error>,
+    P6: DisplayHardwareLayer<Error = P4::Error>,
+    P7: DisplayHardwareLayer<Error = P4::Error>,
+{
+    fn init(&self) -> Result<(), Self::Error> {
+        self.data4.init()?;
+        self.data5.init()?;
+        self.data6.init()?;
+        self.data7.init()
     }
 }
 
-impl<RS, R, E, T, P4, P5, P6, P7> SendRaw
-    for ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T>
+impl<P4, P5, P6, P7> Release for DataPins4Lines<P4, P5, P6, P7>
 where
-    E: DisplayHardwareLayer,
-    T: Delay,
     P4: DisplayHardwareLayer,
-    P5: DisplayHardwareLayer,
-    P6: DisplayHardwareLayer,
-    P7: DisplayHardwareLayer,
+    P5: DisplayHardwareLayer<Error = P4::Error>,
+    P6: DisplayHardwareLayer<Error = P4::Error>,
+    P7: DisplayHardwareLayer<Error = P4::Error>,
 {
-    fn send_byte(&self, byte: u8) {
-        self.data.data4.set_direction(Direction::Out);
-        self.data.data5.set_direction(Direction::Out);
-        self.data.data6.set_direction(Direction::Out);
-        self.data.data7.set_direction(Direction::Out);
+    type Pins = Self;
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        first_error([
+            self.data4.cleanup(),
+            self.data5.cleanup(),
+            self.data6.cleanup(),
+            self.data7.cleanup(),
+        ])
+    }
 
-        write_4bit(self, Nibble::Upper(byte));
-        write_4bit(self, Nibble::Lower(byte));
+    fn into_pins(self) -> Self::Pins {
+        self
     }
 }
 
-fn write_4bit<RS, R, E, T, P4, P5, P6, P7>(
-    pins: &ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T>,
+type ParallelConnection4Lines<RS, R, E, T, C, P4, P5, P6, P7> =
+    ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T, C>;
+
+impl<RS, R, E, T, C, P4, P5, P6, P7> SendRaw
+    for ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T, C>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    T: Delay,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
+{
+    fn send_byte(&self, byte: u8) -> Result<(), Self::Error> {
+        self.send_nibble(byte)?;
+        write_4bit(self, Nibble::Lower(byte))
+    }
+
+    fn send_nibble(&self, byte: u8) -> Result<(), Self::Error> {
+        self.data.data4.set_direction(Direction::Out)?;
+        self.data.data5.set_direction(Direction::Out)?;
+        self.data.data6.set_direction(Direction::Out)?;
+        self.data.data7.set_direction(Direction::Out)?;
+
+        write_4bit(self, Nibble::Upper(byte))
+    }
+}
+
+fn write_4bit<RS, R, E, T, C, P4, P5, P6, P7>(
+    pins: &ParallelConnection4Lines<RS, R, E, T, C, P4, P5, P6, P7>,
     nibble: Nibble,
-) where
-    E: DisplayHardwareLayer,
+) -> Result<(), RS::Error>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
     T: Delay,
-    P4: DisplayHardwareLayer,
-    P5: DisplayHardwareLayer,
-    P6: DisplayHardwareLayer,
-    P7: DisplayHardwareLayer,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
 {
     let value: u8 = nibble.into();
 
-    T::delay_ns(T::ADDRESS_SETUP_TIME);
-    pins.enable.set_level(Level::High);
+    pins.delay.delay_ns(pins.timing.address_setup_time);
+    pins.enable.set_level(Level::High)?;
 
     if value & 0x01 == 0x01 {
-        pins.data.data4.set_level(Level::High);
+        pins.data.data4.set_level(Level::High)?;
     } else {
-        pins.data.data4.set_level(Level::Low);
+        pins.data.data4.set_level(Level::Low)?;
     }
 
     if value & 0x02 == 0x02 {
-        pins.data.data5.set_level(Level::High);
+        pins.data.data5.set_level(Level::High)?;
     } else {
-        pins.data.data5.set_level(Level::Low);
+        pins.data.data5.set_level(Level::Low)?;
     }
 
     if value & 0x04 == 0x04 {
-        pins.data.data6.set_level(Level::High);
+        pins.data.data6.set_level(Level::High)?;
     } else {
-        pins.data.data6.set_level(Level::Low);
+        pins.data.data6.set_level(Level::Low)?;
     }
 
     if value & 0x08 == 0x08 {
-        pins.data.data7.set_level(Level::High);
+        pins.data.data7.set_level(Level::High)?;
     } else {
-        pins.data.data7.set_level(Level::Low);
+        pins.data.data7.set_level(Level::Low)?;
     }
 
-    T::delay_ns(T::ENABLE_PULSE_WIDTH);
-    pins.enable.set_level(Level::Low);
-    T::delay_ns(T::DATA_HOLD_TIME);
+    pins.delay.delay_ns(pins.timing.enable_pulse_width);
+    pins.enable.set_level(Level::Low)?;
+    pins.delay.delay_ns(pins.timing.data_hold_time);
+
+    Ok(())
 }
 
-impl<RS, R, E, T, P4, P5, P6, P7> ReceiveRaw
-    for ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T>
+impl<RS, R, E, T, C, P4, P5, P6, P7> ReceiveRaw
+    for ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T, C>
 where
-    E: DisplayHardwareLayer,
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
     T: Delay,
-    P4: DisplayHardwareLayer,
-    P5: DisplayHardwareLayer,
-    P6: DisplayHardwareLayer,
-    P7: DisplayHardwareLayer,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
 {
-    fn receive_byte(&self) -> u8 {
-        self.data.data4.set_direction(Direction::In);
-        self.data.data5.set_direction(Direction::In);
-        self.data.data6.set_direction(Direction::In);
-        self.data.data7.set_direction(Direction::In);
+    fn receive_byte(&self) -> Result<u8, Self::Error> {
+        self.data.data4.set_direction(Direction::In)?;
+        self.data.data5.set_direction(Direction::In)?;
+        self.data.data6.set_direction(Direction::In)?;
+        self.data.data7.set_direction(Direction::In)?;
 
-        let upper = read_single_nibble(self);
-        let lower = read_single_nibble(self);
+        let upper = read_single_nibble(self)?;
+        let lower = read_single_nibble(self)?;
 
         let mut result = upper << 4;
         result |= lower & 0x0f;
 
-        result
+        Ok(result)
     }
 }
 
-fn read_single_nibble<RS, R, E, T, P4, P5, P6, P7>(
-    pins: &ParallelConnection<RS, R, E, DataPins4Lines<P4, P5, P6, P7>, T>,
-) -> u8
+fn read_single_nibble<RS, R, E, T, C, P4, P5, P6, P7>(
+    pins: &ParallelConnection4Lines<RS, R, E, T, C, P4, P5, P6, P7>,
+) -> Result<u8, RS::Error>
 where
-    E: DisplayHardwareLayer,
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
     T: Delay,
-    P4: DisplayHardwareLayer,
-    P5: DisplayHardwareLayer,
-    P6: DisplayHardwareLayer,
-    P7: DisplayHardwareLayer,
+    P4: DisplayHardwareLayer<Error = RS::Error>,
+    P5: DisplayHardwareLayer<Error = RS::Error>,
+    P6: DisplayHardwareLayer<Error = RS::Error>,
+    P7: DisplayHardwareLayer<Error = RS::Error>,
 {
     let mut result = 0u8;
 
-    T::delay_ns(T::ADDRESS_SETUP_TIME);
-    pins.enable.set_level(Level::High);
+    pins.delay.delay_ns(pins.timing.address_setup_time);
+    pins.enable.set_level(Level::High)?;
+
+    result |= pins.data.data7.get_value()? << 3;
+    result |= pins.data.data6.get_value()? << 2;
+    result |= pins.data.data5.get_value()? << 1;
+    result |= pins.data.data4.get_value()?;
+
+    pins.delay.delay_ns(pins.timing.enable_pulse_width);
+    pins.enable.set_level(Level::Low)?;
+    pins.delay.delay_ns(pins.timing.data_hold_time);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::cell::Cell;
+
+    struct PinMock {
+        direction: Cell<Direction>,
+        level: Cell<u8>,
+        value: u8,
+    }
+
+    impl PinMock {
+        fn new(value: u8) -> Self {
+            PinMock {
+                direction: Cell::new(Direction::In),
+                level: Cell::new(0),
+                value,
+            }
+        }
+    }
+
+    impl DisplayHardwareLayer for PinMock {
+        type Error = ();
+
+        fn set_direction(&self, direction: Direction) -> Result<(), ()> {
+            self.direction.set(direction);
+            Ok(())
+        }
+
+        fn set_level(&self, level: Level) -> Result<(), ()> {
+            self.level.set(match level {
+                Level::Low => 0,
+                Level::High => 1,
+            });
+            Ok(())
+        }
+
+        fn get_value(&self) -> Result<u8, ()> {
+            assert_eq!(self.direction.get(), Direction::In);
+            Ok(self.value)
+        }
+    }
+
+    struct DelayMock;
+
+    impl Delay for DelayMock {
+        fn delay_ns(&self, _: u32) {}
+    }
+
+    type Connection8Lines = ParallelConnection<
+        PinMock,
+        PinMock,
+        PinMock,
+        DataPins8Lines<PinMock, PinMock, PinMock, PinMock, PinMock, PinMock, PinMock, PinMock>,
+        DelayMock,
+    >;
+
+    fn setup_8lines(value: u8) -> Connection8Lines {
+        let bit = |n: u8| (value >> n) & 0x01;
+
+        let pins = Pins {
+            register_select: PinMock::new(0),
+            read: PinMock::new(0),
+            enable: PinMock::new(0),
+            data: DataPins8Lines {
+                data0: PinMock::new(bit(0)),
+                data1: PinMock::new(bit(1)),
+                data2: PinMock::new(bit(2)),
+                data3: PinMock::new(bit(3)),
+                data4: PinMock::new(bit(4)),
+                data5: PinMock::new(bit(5)),
+                data6: PinMock::new(bit(6)),
+                data7: PinMock::new(bit(7)),
+            },
+        };
+
+        pins.into_connection(DelayMock)
+    }
+
+    fn data_levels(connection: &Connection8Lines) -> u8 {
+        let data = &connection.data;
+
+        data.data7.level.get() << 7 | data.data6.level.get() << 6 | data.data5.level.get() << 5
+            | data.data4.level.get() << 4 | data.data3.level.get() << 3
+            | data.data2.level.get() << 2 | data.data1.level.get() << 1
+            | data.data0.level.get()
+    }
+
+    #[test]
+    fn execution_time() {
+        let timing = Timing::default();
+
+        assert_eq!(timing.execution_time(WriteMode::Command(0b0000_0001)), 1520);
+        assert_eq!(timing.execution_time(WriteMode::Command(0b0000_0010)), 1520);
+        assert_eq!(timing.execution_time(WriteMode::Command(0b0000_0011)), 1520);
+        assert_eq!(timing.execution_time(WriteMode::Command(0b0000_0110)), 37);
+        assert_eq!(timing.execution_time(WriteMode::Data(0b0000_0001)), 37);
+    }
+
+    #[test]
+    fn delay_ms_exceeding_nanoseconds_range() {
+        struct CountingDelay(Cell<u64>);
+
+        impl Delay for CountingDelay {
+            fn delay_ns(&self, ns: u32) {
+                self.0.set(self.0.get() + u64::from(ns));
+            }
+        }
+
+        let delay = CountingDelay(Cell::new(0));
+        delay.delay_ms(5_000);
+
+        assert_eq!(delay.0.get(), 5_000_000_000);
+    }
+
+    #[test]
+    fn send_8lines() {
+        let connection = setup_8lines(0);
+
+        connection.send(WriteMode::Data(0b1010_0110)).unwrap();
+
+        assert_eq!(data_levels(&connection), 0b1010_0110);
+        assert_eq!(connection.data.data0.direction.get(), Direction::Out);
+        assert_eq!(connection.data.data7.direction.get(), Direction::Out);
+        assert_eq!(connection.register_select.level.get(), 1);
+        assert_eq!(connection.read.level.get(), 0);
+        assert_eq!(connection.enable.level.get(), 0);
+    }
+
+    #[test]
+    fn send_8lines_write_only() {
+        let pins = Pins {
+            register_select: PinMock::new(0),
+            read: Grounded,
+            enable: PinMock::new(0),
+            data: DataPins8Lines {
+                data0: PinMock::new(0),
+                data1: PinMock::new(0),
+                data2: PinMock::new(0),
+                data3: PinMock::new(0),
+                data4: PinMock::new(0),
+                data5: PinMock::new(0),
+                data6: PinMock::new(0),
+                data7: PinMock::new(0),
+            },
+        };
+        let connection = pins.into_connection(DelayMock);
+
+        connection.init().unwrap();
+        connection.send(WriteMode::Command(0b0011_1000)).unwrap();
+
+        assert_eq!(connection.data.data3.level.get(), 1);
+        assert_eq!(connection.data.data4.level.get(), 1);
+        assert_eq!(connection.data.data5.level.get(), 1);
+        assert_eq!(connection.data.data7.level.get(), 0);
+        assert_eq!(connection.register_select.level.get(), 0);
+    }
 
-    result |= pins.data.data7.get_value() << 3;
-    result |= pins.data.data6.get_value() << 2;
-    result |= pins.data.data5.get_value() << 1;
-    result |= pins.data.data4.get_value();
+    #[test]
+    fn send_upper_nibble_4lines() {
+        let pins = Pins {
+            register_select: PinMock::new(1),
+            read: PinMock::new(0),
+            enable: PinMock::new(0),
+            data: DataPins4Lines {
+                data4: PinMock::new(0),
+                data5: PinMock::new(0),
+                data6: PinMock::new(0),
+                data7: PinMock::new(0),
+            },
+        };
+        let connection = pins.into_connection(DelayMock);
+
+        connection.send_upper_nibble(0b0010_1111).unwrap();
+
+        // the lower nibble would have left all data lines `High`
+        assert_eq!(connection.data.data4.level.get(), 0);
+        assert_eq!(connection.data.data5.level.get(), 1);
+        assert_eq!(connection.data.data6.level.get(), 0);
+        assert_eq!(connection.data.data7.level.get(), 0);
+        assert_eq!(connection.register_select.level.get(), 0);
+        assert_eq!(connection.enable.level.get(), 0);
+    }
+
+    #[test]
+    fn receive_8lines() {
+        let connection = setup_8lines(0b1100_0101);
 
-    T::delay_ns(T::ENABLE_PULSE_WIDTH);
-    pins.enable.set_level(Level::Low);
-    T::delay_ns(T::DATA_HOLD_TIME);
+        let value = connection.receive(ReadMode::Data);
 
-    result
+        assert_eq!(value, Ok(0b1100_0101));
+        assert_eq!(connection.register_select.level.get(), 1);
+        assert_eq!(connection.read.level.get(), 1);
+        assert_eq!(connection.enable.level.get(), 0);
+    }
+
+    #[test]
+    fn receive_8lines_restores_output_direction() {
+        let connection = setup_8lines(0b1000_0000);
+
+        connection.receive(ReadMode::BusyFlag).unwrap();
+
+        assert_eq!(connection.data.data0.direction.get(), Direction::Out);
+        assert_eq!(connection.data.data7.direction.get(), Direction::Out);
+        assert_eq!(connection.register_select.level.get(), 0);
+    }
 }
@@ -0,0 +1,369 @@
+use core::cell::Cell;
+use core::fmt::Debug;
+
+use crate::backlight::Backlight;
+use crate::error::{check_pins, PinError};
+use crate::hal::{Delay, ErrorType, Init, Nibble, ReadMode, Receive, Send, Timing, Wait,
+                 WriteMode};
+
+/// The `I2cBus` trait is intended to be implemented by the library user as a thin wrapper around
+/// the hardware specific I2C bus.
+pub trait I2cBus {
+    /// The error reported by the I2C bus, e.g. if the device does not acknowledge.
+    type Error: Debug;
+
+    /// Writes a single byte to the device with the given address.
+    fn write_byte(&self, address: u8, byte: u8) -> Result<(), Self::Error>;
+
+    /// Reads a single byte from the device with the given address.
+    fn read_byte(&self, address: u8) -> Result<u8, Self::Error>;
+}
+
+/// This struct describes which port bit (`0` to `7`) of an I/O expander is wired to which signal
+/// of the LCD.
+///
+/// Presets for the most common PCF8574 backpacks are available as associated constants.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExpanderPins {
+    pub register_select: u8,
+    pub read: u8,
+    pub enable: u8,
+    pub backlight: u8,
+    pub data4: u8,
+    pub data5: u8,
+    pub data6: u8,
+    pub data7: u8,
+    /// Whether the backlight is switched on by a `Low` level on its port bit.
+    pub backlight_active_low: bool,
+}
+
+impl ExpanderPins {
+    /// The wiring used by most PCF8574 backpacks (e.g. the ones sold as `LCM1602`, `YwRobot` or
+    /// `DFRobot`).
+    ///
+    ///  - **P0:** RS
+    ///  - **P1:** R/W
+    ///  - **P2:** E
+    ///  - **P3:** backlight
+    ///  - **P4-P7:** D4-D7
+    pub const GENERIC: ExpanderPins = ExpanderPins {
+        register_select: 0,
+        read: 1,
+        enable: 2,
+        backlight: 3,
+        data4: 4,
+        data5: 5,
+        data6: 6,
+        data7: 7,
+        backlight_active_low: false,
+    };
+
+    /// The wiring used by `mjkdz` PCF8574 backpacks.
+    ///
+    ///  - **P0-P3:** D4-D7
+    ///  - **P4:** E
+    ///  - **P5:** R/W
+    ///  - **P6:** RS
+    ///  - **P7:** backlight (active low)
+    pub const MJKDZ: ExpanderPins = ExpanderPins {
+        register_select: 6,
+        read: 5,
+        enable: 4,
+        backlight: 7,
+        data4: 0,
+        data5: 1,
+        data6: 2,
+        data7: 3,
+        backlight_active_low: true,
+    };
+
+    /// Checks that all signals are wired to distinct port bits of the expander.
+    fn check(&self) -> Result<(), PinError> {
+        let pins = [
+            self.register_select,
+            self.read,
+            self.enable,
+            self.backlight,
+            self.data4,
+            self.data5,
+            self.data6,
+            self.data7,
+        ];
+
+        check_pins(&pins, PORT_BITS)
+    }
+
+    fn data_mask(&self) -> u8 {
+        bit(self.data4) | bit(self.data5) | bit(self.data6) | bit(self.data7)
+    }
+
+    fn nibble_to_port(&self, value: u8) -> u8 {
+        let mut port = 0;
+
+        if value & 0x01 == 0x01 {
+            port |= bit(self.data4);
+        }
+
+        if value & 0x02 == 0x02 {
+            port |= bit(self.data5);
+        }
+
+        if value & 0x04 == 0x04 {
+            port |= bit(self.data6);
+        }
+
+        if value & 0x08 == 0x08 {
+            port |= bit(self.data7);
+        }
+
+        port
+    }
+
+    fn port_to_nibble(&self, port: u8) -> u8 {
+        let mut value = 0;
+
+        if port & bit(self.data7) != 0 {
+            value |= 0x08;
+        }
+
+        if port & bit(self.data6) != 0 {
+            value |= 0x04;
+        }
+
+        if port & bit(self.data5) != 0 {
+            value |= 0x02;
+        }
+
+        if port & bit(self.data4) != 0 {
+            value |= 0x01;
+        }
+
+        value
+    }
+}
+
+const PORT_BITS: u8 = 8;
+
+fn bit(pin: u8) -> u8 {
+    1 << pin
+}
+
+/// A connection to a display which is wired to a PCF8574 I2C I/O expander (also known as I2C
+/// backpack).
+///
+/// The expander drives the display in 4-bit mode, so the display has to be initialized with
+/// `DataLength::FourBit`.
+pub struct I2cExpanderConnection<B, T> {
+    bus: B,
+    address: u8,
+    pins: ExpanderPins,
+    backlight: Cell<bool>,
+    delay: T,
+    timing: Timing,
+}
+
+impl<B, T> I2cExpanderConnection<B, T> {
+    /// Creates a new connection to the expander with the given I2C address (usually `0x27` for
+    /// the PCF8574 and `0x3f` for the PCF8574A).
+    ///
+    /// The backlight is switched on by default.
+    ///
+    /// Fails if a signal is not wired to a port bit (`0` to `7`) or two signals share a port bit.
+    pub fn new(bus: B, address: u8, pins: ExpanderPins, delay: T) -> Result<Self, PinError> {
+        pins.check()?;
+
+        Ok(I2cExpanderConnection {
+            bus,
+            address,
+            pins,
+            backlight: Cell::new(true),
+            delay,
+            timing: Timing::default(),
+        })
+    }
+
+    /// Sets the timing of the bus, which defaults to the timing for the lower supply voltage
+    /// ([`Timing::VCC_3V`]).
+    ///
+    /// [`Timing::VCC_3V`]: struct.Timing.html#associatedconstant.VCC_3V
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Returns the underlying I2C bus.
+    pub fn into_bus(self) -> B {
+        self.bus
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn delay(&self) -> &T {
+        &self.delay
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    fn control_bits(&self, register_select: bool, read: bool) -> u8 {
+        let mut port = 0;
+
+        if register_select {
+            port |= bit(self.pins.register_select);
+        }
+
+        if read {
+            port |= bit(self.pins.read);
+        }
+
+        if self.backlight.get() != self.pins.backlight_active_low {
+            port |= bit(self.pins.backlight);
+        }
+
+        port
+    }
+}
+
+impl<B, T> I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+{
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    pub fn set_backlight(&self, enabled: bool) -> Result<(), B::Error> {
+        self.backlight.set(enabled);
+
+        let port = self.control_bits(false, false);
+        self.bus.write_byte(self.address, port)
+    }
+}
+
+impl<B, T> I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+    T: Delay,
+{
+    fn write_nibble(&self, control: u8, nibble: Nibble) -> Result<(), B::Error> {
+        let port = control | self.pins.nibble_to_port(nibble.into());
+        let enable = bit(self.pins.enable);
+
+        self.bus.write_byte(self.address, port)?;
+        self.delay.delay_ns(self.timing.address_setup_time);
+
+        self.bus.write_byte(self.address, port | enable)?;
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+
+        self.bus.write_byte(self.address, port)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(())
+    }
+
+    fn read_nibble(&self, control: u8) -> Result<u8, B::Error> {
+        // the PCF8574 port bits are quasi-bidirectional, writing a `High` level to them turns
+        // them into inputs
+        let port = control | self.pins.data_mask();
+        let enable = bit(self.pins.enable);
+
+        self.bus.write_byte(self.address, port)?;
+        self.delay.delay_ns(self.timing.address_setup_time);
+
+        self.bus.write_byte(self.address, port | enable)?;
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+
+        let value = self.bus.read_byte(self.address)?;
+
+        self.bus.write_byte(self.address, port)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(self.pins.port_to_nibble(value))
+    }
+}
+
+impl<B, T> ErrorType for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+{
+    type Error = B::Error;
+}
+
+impl<B, T> Backlight for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+{
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+        I2cExpanderConnection::set_backlight(self, enabled)
+    }
+}
+
+impl<B, T> Init for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+{
+    fn init(&self) -> Result<(), Self::Error> {
+        let port = self.control_bits(false, false);
+        self.bus.write_byte(self.address, port)
+    }
+}
+
+impl<B, T> Send for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+    T: Delay,
+{
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        let (register_select, value) = match mode {
+            WriteMode::Command(value) => (false, value),
+            WriteMode::Data(value) => (true, value),
+        };
+
+        let control = self.control_bits(register_select, false);
+
+        self.write_nibble(control, Nibble::Upper(value))?;
+        self.write_nibble(control, Nibble::Lower(value))
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        let control = self.control_bits(false, false);
+        self.write_nibble(control, Nibble::Upper(cmd))
+    }
+}
+
+impl<B, T> Receive for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+    T: Delay,
+{
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+        let register_select = match mode {
+            ReadMode::Data => true,
+            ReadMode::BusyFlag => false,
+        };
+
+        let control = self.control_bits(register_select, true);
+
+        let upper = self.read_nibble(control)?;
+        let lower = self.read_nibble(control)?;
+
+        let mut result = upper << 4;
+        result |= lower & 0x0f;
+
+        Ok(result)
+    }
+}
+
+impl<B, T> Wait for I2cExpanderConnection<B, T>
+where
+    B: I2cBus,
+    T: Delay,
+{
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.delay.delay_us(self.timing.execution_time(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+}
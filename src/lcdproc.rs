@@ -0,0 +1,896 @@
+//! A server for the text protocol of LCDproc's `LCDd`, which renders the screens of its clients
+//! on a display.
+
+use core::fmt;
+
+use std::format;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::string::{String, ToString};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::vec;
+use std::vec::Vec;
+
+use crate::address::Address;
+use crate::display::{CgRam, DdRam, DdRamDisplay};
+use crate::error::Error;
+use crate::hal::{Send, Wait};
+use crate::lines::Home;
+use crate::matrix_orbital::{self, MatrixOrbitalInterpreter};
+
+const CELL_WIDTH: u8 = 5;
+const CELL_HEIGHT: u8 = 8;
+
+/// The duration of a frame, `LCDd` renders eight frames per second.
+const FRAME_DURATION: Duration = Duration::from_millis(125);
+/// The number of frames a screen is shown, before the next one of the same priority follows.
+const DEFAULT_DURATION: u32 = 32;
+/// The maximum number of lines of a display, the interpreter wraps further lines.
+const MAX_HEIGHT: u8 = 4;
+/// The maximum length of a line received from a client, like the buffer size of `LCDd`.
+const MAX_LINE_LENGTH: usize = 8192;
+
+const FULL_BLOCK: u8 = 0xff;
+const ARROW_RIGHT: u8 = 0x7e;
+const ARROW_LEFT: u8 = 0x7f;
+/// The first of four custom characters, which fill one to four columns of a cell.
+const HBAR_CHARACTERS: u8 = 0;
+/// The first of three custom characters, which fill two, four and six rows of a cell.
+const VBAR_CHARACTERS: u8 = 4;
+const HEART: u8 = 7;
+
+const HEART_PATTERN: [u8; 8] = [0x00, 0x0a, 0x1f, 0x1f, 0x0e, 0x04, 0x00, 0x00];
+
+/// Returns the patterns of the custom characters used for bars and icons.
+fn custom_characters() -> [[u8; CELL_HEIGHT as usize]; 8] {
+    let mut characters = [[0; CELL_HEIGHT as usize]; 8];
+
+    for columns in 1..CELL_WIDTH {
+        let row = (0x1f << (CELL_WIDTH - columns)) & 0x1f;
+        characters[usize::from(HBAR_CHARACTERS + columns - 1)] = [row; CELL_HEIGHT as usize];
+    }
+
+    for level in 1..4 {
+        let character = &mut characters[usize::from(VBAR_CHARACTERS + level - 1)];
+        for row in character.iter_mut().skip(usize::from(CELL_HEIGHT - 2 * level)) {
+            *row = 0x1f;
+        }
+    }
+
+    characters[usize::from(HEART)] = HEART_PATTERN;
+
+    characters
+}
+
+/// Returns the characters shown for the given icon name.
+fn icon(name: &str) -> Option<&'static [u8]> {
+    let characters: &'static [u8] = match name {
+        "BLOCK_FILLED" => &[FULL_BLOCK],
+        "HEART_OPEN" => b"-",
+        "HEART_FILLED" => &[HEART],
+        "ARROW_UP" => b"^",
+        "ARROW_DOWN" => b"v",
+        "ARROW_LEFT" => &[ARROW_LEFT],
+        "ARROW_RIGHT" => &[ARROW_RIGHT],
+        "CHECKBOX_OFF" => b"N",
+        "CHECKBOX_ON" => b"Y",
+        "CHECKBOX_GRAY" => b"o",
+        "SELECTOR_AT_LEFT" => &[ARROW_RIGHT],
+        "SELECTOR_AT_RIGHT" => &[ARROW_LEFT],
+        "ELLIPSIS" => b"_",
+        "STOP" => b"[]",
+        "PAUSE" => b"||",
+        "PLAY" => b">",
+        "PLAYR" => b"<",
+        "FF" => b">>",
+        "FR" => b"<<",
+        "NEXT" => b">|",
+        "PREV" => b"|<",
+        "REC" => b"()",
+        _ => return None,
+    };
+
+    Some(characters)
+}
+
+/// Splits a command line into its arguments, which may be quoted by `"` or braces.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+
+        let arg = match chars.next() {
+            None => return args,
+            Some('"') => {
+                let mut arg = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => arg.extend(chars.next()),
+                        c => arg.push(c),
+                    }
+                }
+                arg
+            }
+            Some('{') => chars.by_ref().take_while(|&c| c != '}').collect(),
+            Some(c) => {
+                let mut arg = String::new();
+                arg.push(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    arg.push(c);
+                    chars.next();
+                }
+                arg
+            }
+        };
+
+        args.push(arg);
+    }
+}
+
+fn parse_number(arg: &str) -> Result<i32, String> {
+    arg.parse().map_err(|_| format!("Invalid number \"{}\"", arg))
+}
+
+/// Returns the bytes of the given text, characters outside of ASCII are replaced by `?`.
+fn text_bytes(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c as u8
+            } else {
+                b'?'
+            }
+        })
+        .collect()
+}
+
+/// Enumeration of errors reported by a [`LcdProcServer`].
+///
+/// [`LcdProcServer`]: struct.LcdProcServer.html
+#[derive(Debug)]
+pub enum LcdProcError<E> {
+    /// Accepting or reading clients failed.
+    Io(io::Error),
+    /// Rendering on the display failed.
+    Display(Error<E>),
+}
+
+impl<E: fmt::Debug> fmt::Display for LcdProcError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LcdProcError::Io(ref e) => write!(f, "serving clients failed: {}", e),
+            LcdProcError::Display(ref e) => write!(f, "rendering failed: {}", e),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    Hidden,
+    Background,
+    Info,
+    Foreground,
+    Alert,
+    Input,
+}
+
+impl Priority {
+    fn parse(arg: &str) -> Result<Self, String> {
+        let priority = match arg {
+            "hidden" => Priority::Hidden,
+            "background" => Priority::Background,
+            "info" => Priority::Info,
+            "foreground" => Priority::Foreground,
+            "alert" => Priority::Alert,
+            "input" => Priority::Input,
+            // numeric priorities of older clients, where lower numbers are more important
+            _ => match parse_number(arg)? {
+                i32::MIN..=64 => Priority::Foreground,
+                65..=192 => Priority::Info,
+                _ => Priority::Background,
+            },
+        };
+
+        Ok(priority)
+    }
+}
+
+enum Widget {
+    String { x: i32, y: i32, text: String },
+    Title { text: String },
+    HBar { x: i32, y: i32, length: i32 },
+    VBar { x: i32, y: i32, length: i32 },
+    Icon { x: i32, y: i32, icon: &'static [u8] },
+    Scroller {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+        direction: char,
+        speed: i32,
+        text: String,
+    },
+}
+
+impl Widget {
+    fn new(kind: &str) -> Result<Self, String> {
+        let widget = match kind {
+            "string" => Widget::String {
+                x: 1,
+                y: 1,
+                text: String::new(),
+            },
+            "title" => Widget::Title {
+                text: String::new(),
+            },
+            "hbar" => Widget::HBar {
+                x: 1,
+                y: 1,
+                length: 0,
+            },
+            "vbar" => Widget::VBar {
+                x: 1,
+                y: 1,
+                length: 0,
+            },
+            "icon" => Widget::Icon {
+                x: 1,
+                y: 1,
+                icon: b" ",
+            },
+            "scroller" => Widget::Scroller {
+                left: 1,
+                top: 1,
+                right: 1,
+                bottom: 1,
+                direction: 'h',
+                speed: 1,
+                text: String::new(),
+            },
+            _ => return Err(format!("Invalid widget type \"{}\"", kind)),
+        };
+
+        Ok(widget)
+    }
+
+    fn set(&mut self, args: &[String]) -> Result<(), String> {
+        let expected = match *self {
+            Widget::Title { .. } => 1,
+            Widget::Scroller { .. } => 7,
+            _ => 3,
+        };
+        if args.len() != expected {
+            return Err("Wrong number of arguments".to_string());
+        }
+
+        match *self {
+            Widget::String {
+                ref mut x,
+                ref mut y,
+                ref mut text,
+            } => {
+                *x = parse_number(&args[0])?;
+                *y = parse_number(&args[1])?;
+                *text = args[2].clone();
+            }
+            Widget::Title { ref mut text } => *text = args[0].clone(),
+            Widget::HBar {
+                ref mut x,
+                ref mut y,
+                ref mut length,
+            }
+            | Widget::VBar {
+                ref mut x,
+                ref mut y,
+                ref mut length,
+            } => {
+                *x = parse_number(&args[0])?;
+                *y = parse_number(&args[1])?;
+                *length = parse_number(&args[2])?;
+            }
+            Widget::Icon {
+                ref mut x,
+                ref mut y,
+                ref mut icon,
+            } => {
+                *x = parse_number(&args[0])?;
+                *y = parse_number(&args[1])?;
+                *icon = self::icon(&args[2])
+                    .ok_or_else(|| format!("Invalid icon name \"{}\"", args[2]))?;
+            }
+            Widget::Scroller {
+                ref mut left,
+                ref mut top,
+                ref mut right,
+                ref mut bottom,
+                ref mut direction,
+                ref mut speed,
+                ref mut text,
+            } => {
+                *left = parse_number(&args[0])?;
+                *top = parse_number(&args[1])?;
+                *right = parse_number(&args[2])?;
+                *bottom = parse_number(&args[3])?;
+                *direction = match args[4].as_str() {
+                    "h" => 'h',
+                    "v" => 'v',
+                    "m" => 'm',
+                    _ => return Err(format!("Invalid direction \"{}\"", args[4])),
+                };
+                *speed = parse_number(&args[5])?;
+                *text = args[6].clone();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Screen {
+    client: usize,
+    id: String,
+    priority: Priority,
+    duration: u32,
+    widgets: Vec<(String, Widget)>,
+}
+
+impl Screen {
+    fn set(&mut self, args: &[String]) -> Result<(), String> {
+        let mut options = args.iter();
+
+        while let Some(option) = options.next() {
+            let value = options
+                .next()
+                .ok_or_else(|| format!("Missing value of \"{}\"", option))?;
+
+            match option.as_str() {
+                "-priority" => self.priority = Priority::parse(value)?,
+                "-duration" => self.duration = parse_number(value)?.max(1) as u32,
+                // options, which do not affect the rendering
+                "-name" | "-heartbeat" | "-backlight" | "-wid" | "-hgt" | "-timeout"
+                | "-cursor" | "-cursor_x" | "-cursor_y" => {}
+                _ => return Err(format!("Invalid parameter \"{}\"", option)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn widget(&mut self, id: &str) -> Result<&mut Widget, String> {
+        self.widgets
+            .iter_mut()
+            .find(|(widget_id, _)| widget_id == id)
+            .map(|(_, widget)| widget)
+            .ok_or_else(|| format!("Invalid widget id \"{}\"", id))
+    }
+}
+
+struct Client {
+    id: usize,
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    greeted: bool,
+    closed: bool,
+}
+
+impl Client {
+    fn send(&mut self, message: &str) {
+        let mut bytes = Vec::with_capacity(message.len() + 1);
+        bytes.extend_from_slice(message.as_bytes());
+        bytes.push(b'\n');
+
+        if self.stream.write_all(&bytes).is_err() {
+            self.closed = true;
+        }
+    }
+
+    /// Reads all available bytes and returns the complete lines received. A client, which sends
+    /// a line longer than `MAX_LINE_LENGTH`, is closed.
+    fn receive_lines(&mut self) -> Vec<String> {
+        let mut chunk = [0; 256];
+        let mut lines = Vec::new();
+
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => {
+                    self.closed = true;
+                    break;
+                }
+            }
+
+            while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line[..end]);
+                lines.push(line.trim_end_matches('\r').to_string());
+            }
+
+            if self.buffer.len() > MAX_LINE_LENGTH {
+                self.buffer.clear();
+                self.closed = true;
+                break;
+            }
+        }
+
+        lines
+    }
+}
+
+/// A server, which speaks the text protocol of LCDproc's `LCDd` over TCP and renders the screens
+/// of its clients on a display, so that LCDproc clients can be used with clerk.
+///
+/// It supports `hello`, `client_set`, `screen_add`, `screen_set`, `screen_del`, `widget_add`,
+/// `widget_set`, `widget_del`, `noop` and `bye`, with string, title, hbar, vbar, icon and
+/// scroller widgets. Of all screens with the highest priority one is shown at a time, they are
+/// rotated after their duration. Clients are told by `listen` and `ignore`, when their screens
+/// become visible or hidden.
+///
+/// Bars and the heart icon are drawn with custom characters, which replace the ones defined
+/// before. Other icons are approximated by ROM characters.
+pub struct LcdProcServer<P, U>
+where
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    listener: TcpListener,
+    interpreter: MatrixOrbitalInterpreter<P, U>,
+    width: u8,
+    height: u8,
+    characters_loaded: bool,
+    clients: Vec<Client>,
+    next_client: usize,
+    screens: Vec<Screen>,
+    active: Option<(usize, String)>,
+    shown_for: u32,
+    frame: u32,
+    drawn: Vec<u8>,
+}
+
+impl<P, U> LcdProcServer<P, U>
+where
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Creates a new server listening on the given address, which renders on the given display
+    /// with the given size. `LCDd` listens on port 13666.
+    ///
+    /// The display is expected to be initialized and switched on with the cursor turned off.
+    /// Displays with one to four lines are supported, other sizes fail with
+    /// `ErrorKind::InvalidInput`.
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        display: DdRamDisplay<P, U>,
+        width: u8,
+        height: u8,
+    ) -> io::Result<Self> {
+        if width == 0 || height == 0 || height > MAX_HEIGHT {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "the display has to have at least one column and one to four lines",
+            ));
+        }
+
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(LcdProcServer {
+            listener,
+            interpreter: MatrixOrbitalInterpreter::new(display, width),
+            width,
+            height,
+            characters_loaded: false,
+            clients: Vec::new(),
+            next_client: 0,
+            screens: Vec::new(),
+            active: None,
+            shown_for: 0,
+            frame: 0,
+            drawn: Vec::new(),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts new clients and executes the commands received from all clients.
+    pub fn handle_clients(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true)?;
+                    stream.set_nodelay(true)?;
+
+                    self.clients.push(Client {
+                        id: self.next_client,
+                        stream,
+                        buffer: Vec::new(),
+                        greeted: false,
+                        closed: false,
+                    });
+                    self.next_client += 1;
+                }
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        for index in 0..self.clients.len() {
+            let lines = self.clients[index].receive_lines();
+
+            for line in lines {
+                if self.clients[index].closed {
+                    break;
+                }
+
+                let args = tokenize(&line);
+                if args.is_empty() {
+                    continue;
+                }
+                if args[0] == "bye" {
+                    self.clients[index].closed = true;
+                    break;
+                }
+
+                let reply = match self.execute(index, &args) {
+                    Ok(reply) => reply,
+                    Err(message) => format!("huh? {}", message),
+                };
+                self.clients[index].send(&reply);
+            }
+        }
+
+        let closed: Vec<usize> = self
+            .clients
+            .iter()
+            .filter(|client| client.closed)
+            .map(|client| client.id)
+            .collect();
+        self.clients.retain(|client| !client.closed);
+        self.screens.retain(|screen| !closed.contains(&screen.client));
+
+        Ok(())
+    }
+
+    /// Executes a command of the client at the given index and returns the reply.
+    fn execute(&mut self, index: usize, args: &[String]) -> Result<String, String> {
+        let client = self.clients[index].id;
+
+        if args[0] == "hello" {
+            self.clients[index].greeted = true;
+
+            return Ok(format!(
+                "connect LCDproc 0.5.9 protocol 0.3 lcd wid {} hgt {} cellwid {} cellhgt {}",
+                self.width, self.height, CELL_WIDTH, CELL_HEIGHT
+            ));
+        }
+        if !self.clients[index].greeted {
+            return Err("Please send hello first".to_string());
+        }
+
+        match (args[0].as_str(), &args[1..]) {
+            ("noop", []) => {}
+            ("client_set", options) => {
+                if options.len() % 2 != 0 || options.iter().step_by(2).any(|o| o != "-name") {
+                    return Err("Invalid parameters".to_string());
+                }
+            }
+            ("screen_add", [id]) => {
+                if self.screen(client, id).is_ok() {
+                    return Err(format!("Screen \"{}\" already exists", id));
+                }
+
+                self.screens.push(Screen {
+                    client,
+                    id: id.clone(),
+                    priority: Priority::Info,
+                    duration: DEFAULT_DURATION,
+                    widgets: Vec::new(),
+                });
+            }
+            ("screen_del", [id]) => {
+                self.screen(client, id)?;
+                self.screens
+                    .retain(|screen| screen.client != client || &screen.id != id);
+            }
+            ("screen_set", [id, options @ ..]) => self.screen(client, id)?.set(options)?,
+            ("widget_add", [screen, id, kind, options @ ..]) => {
+                if !options.is_empty() {
+                    return Err("Frames are not supported".to_string());
+                }
+
+                let widget = Widget::new(kind)?;
+                let screen = self.screen(client, screen)?;
+                if screen.widget(id).is_ok() {
+                    return Err(format!("Widget \"{}\" already exists", id));
+                }
+                screen.widgets.push((id.clone(), widget));
+            }
+            ("widget_set", [screen, id, values @ ..]) => {
+                self.screen(client, screen)?.widget(id)?.set(values)?
+            }
+            ("widget_del", [screen, id]) => {
+                let screen = self.screen(client, screen)?;
+                screen.widget(id)?;
+                screen.widgets.retain(|(widget_id, _)| widget_id != id);
+            }
+            ("noop", _)
+            | ("screen_add", _)
+            | ("screen_del", _)
+            | ("screen_set", _)
+            | ("widget_add", _)
+            | ("widget_set", _)
+            | ("widget_del", _) => return Err("Wrong number of arguments".to_string()),
+            (command, _) => return Err(format!("Invalid command \"{}\"", command)),
+        }
+
+        Ok("success".to_string())
+    }
+
+    fn screen(&mut self, client: usize, id: &str) -> Result<&mut Screen, String> {
+        self.screens
+            .iter_mut()
+            .find(|screen| screen.client == client && screen.id == id)
+            .ok_or_else(|| format!("Invalid screen id \"{}\"", id))
+    }
+
+    fn notify(&mut self, screen: &Option<(usize, String)>, event: &str) {
+        if let Some((client, ref id)) = *screen {
+            if let Some(client) = self.clients.iter_mut().find(|c| c.id == client) {
+                client.send(&format!("{} {}", event, id));
+            }
+        }
+    }
+
+    /// Selects the screen to show, which is one of the screens with the highest priority.
+    fn rotate(&mut self) {
+        let top = self
+            .screens
+            .iter()
+            .map(|screen| screen.priority)
+            .filter(|&priority| priority != Priority::Hidden)
+            .max();
+        let candidates: Vec<usize> = (0..self.screens.len())
+            .filter(|&i| Some(self.screens[i].priority) == top)
+            .collect();
+
+        let current = candidates.iter().position(|&i| {
+            let screen = &self.screens[i];
+            self.active.as_ref() == Some(&(screen.client, screen.id.clone()))
+        });
+
+        let next = match current {
+            Some(position) if self.shown_for < self.screens[candidates[position]].duration => {
+                Some(candidates[position])
+            }
+            Some(position) => {
+                self.shown_for = 0;
+                Some(candidates[(position + 1) % candidates.len()])
+            }
+            None => candidates.first().cloned(),
+        };
+
+        let next = next.map(|i| (self.screens[i].client, self.screens[i].id.clone()));
+        if next != self.active {
+            let previous = self.active.take();
+            self.notify(&previous, "ignore");
+            self.notify(&next, "listen");
+
+            self.active = next;
+            self.shown_for = 0;
+        }
+
+        self.shown_for += 1;
+    }
+
+    /// Renders the active screen into a buffer with a byte per character.
+    ///
+    /// The coordinates are received from the clients, so they are combined by saturating
+    /// arithmetic and widgets are clipped to the display.
+    fn render(&self) -> Vec<u8> {
+        let width = i32::from(self.width);
+        let height = i32::from(self.height);
+        let mut frame = vec![b' '; (width * height) as usize];
+
+        let mut put = |x: i32, y: i32, bytes: &[u8]| {
+            if y < 1 || y > height {
+                return;
+            }
+            for (i, &byte) in bytes.iter().enumerate() {
+                let column = x.saturating_add(i as i32);
+                if column >= 1 && column <= width {
+                    frame[((y - 1) * width + column - 1) as usize] = byte;
+                }
+            }
+        };
+
+        let active = self.active.as_ref().and_then(|(client, id)| {
+            self.screens
+                .iter()
+                .find(|screen| screen.client == *client && &screen.id == id)
+        });
+
+        let screen = match active {
+            Some(screen) => screen,
+            None => {
+                put(1, 1, b"LCDproc Server");
+                let status = format!("Clients: {}", self.clients.len());
+                put(1, 2, status.as_bytes());
+                return frame;
+            }
+        };
+
+        for (_, widget) in &screen.widgets {
+            match *widget {
+                Widget::String { x, y, ref text } => put(x, y, &text_bytes(text)),
+                Widget::Title { ref text } => {
+                    let mut title = b"## ".to_vec();
+                    title.extend(text_bytes(text));
+                    title.push(b' ');
+                    title.resize(title.len().max(self.width as usize), b'#');
+                    put(1, 1, &title);
+                }
+                Widget::HBar { x, y, length } => {
+                    let cell_width = i32::from(CELL_WIDTH);
+                    let length = length.clamp(0, width * cell_width);
+                    let mut bar = vec![FULL_BLOCK; (length / cell_width) as usize];
+                    if length % cell_width > 0 {
+                        bar.push(HBAR_CHARACTERS + (length % cell_width) as u8 - 1);
+                    }
+                    put(x, y, &bar);
+                }
+                Widget::VBar { x, y, length } => {
+                    let cell_height = i32::from(CELL_HEIGHT);
+                    let length = length.clamp(0, height * cell_height);
+                    let full = length / cell_height;
+                    for row in 0..full {
+                        put(x, y.saturating_sub(row), &[FULL_BLOCK]);
+                    }
+                    let level = (length % cell_height) / 2;
+                    if level > 0 {
+                        put(x, y.saturating_sub(full), &[VBAR_CHARACTERS + level as u8 - 1]);
+                    }
+                }
+                Widget::Icon { x, y, icon } => put(x, y, icon),
+                Widget::Scroller {
+                    left,
+                    top,
+                    right,
+                    bottom,
+                    direction,
+                    speed,
+                    ref text,
+                } => {
+                    let columns = right.saturating_sub(left).saturating_add(1);
+                    let lines = bottom.saturating_sub(top).saturating_add(1);
+                    if columns < 1 || lines < 1 {
+                        continue;
+                    }
+
+                    let text = text_bytes(text);
+                    let steps = match speed {
+                        0 => 0,
+                        speed if speed > 0 => self.frame / speed as u32,
+                        speed => self.frame.wrapping_mul(speed.unsigned_abs()),
+                    } as usize;
+                    let columns = columns as usize;
+
+                    match direction {
+                        'v' => {
+                            let chunks: Vec<&[u8]> = text.chunks(columns).collect();
+                            let positions = chunks.len().saturating_sub(lines as usize) + 1;
+                            let offset = steps % positions;
+                            for (row, chunk) in chunks.iter().skip(offset).enumerate() {
+                                if row < lines as usize {
+                                    put(left, top.saturating_add(row as i32), chunk);
+                                }
+                            }
+                        }
+                        _ if text.len() <= columns => put(left, top, &text),
+                        'm' => {
+                            let mut marquee = text.clone();
+                            marquee.push(b' ');
+                            let offset = steps % marquee.len();
+                            let window: Vec<u8> = (0..columns)
+                                .map(|i| marquee[(offset + i) % marquee.len()])
+                                .collect();
+                            put(left, top, &window);
+                        }
+                        _ => {
+                            let offset = steps % (text.len() - columns + 1);
+                            put(left, top, &text[offset..offset + columns]);
+                        }
+                    }
+                }
+            }
+        }
+
+        frame
+    }
+}
+
+impl<P, U> LcdProcServer<P, U>
+where
+    P: Send + Wait,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Advances by one frame, rotates the screens and draws the changed lines of the active
+    /// screen.
+    pub fn tick(&mut self) -> Result<(), Error<P::Error>> {
+        if !self.characters_loaded {
+            for (character, pattern) in custom_characters().iter().enumerate() {
+                self.feed(&[matrix_orbital::PREFIX, matrix_orbital::DEFINE_CHARACTER])?;
+                self.feed(&[character as u8])?;
+                self.feed(pattern)?;
+            }
+            self.characters_loaded = true;
+        }
+
+        self.rotate();
+        let frame = self.render();
+
+        for (row, line) in frame.chunks(usize::from(self.width)).enumerate() {
+            if self.drawn.chunks(usize::from(self.width)).nth(row) == Some(line) {
+                continue;
+            }
+
+            self.feed(&[matrix_orbital::PREFIX, matrix_orbital::SET_CURSOR, 1, row as u8 + 1])?;
+            for &byte in line {
+                // the command prefix can not be written as text
+                let byte = if byte == matrix_orbital::PREFIX { b' ' } else { byte };
+                self.feed(&[byte])?;
+            }
+        }
+
+        self.drawn = frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        Ok(())
+    }
+
+    /// Serves clients and renders eight frames per second, until an error occurs.
+    pub fn run(&mut self) -> Result<(), LcdProcError<P::Error>> {
+        loop {
+            let start = Instant::now();
+
+            self.handle_clients().map_err(LcdProcError::Io)?;
+            self.tick().map_err(LcdProcError::Display)?;
+
+            if let Some(rest) = FRAME_DURATION.checked_sub(start.elapsed()) {
+                thread::sleep(rest);
+            }
+        }
+    }
+
+    /// Returns the display. On failure the unchanged server is returned together with the error,
+    /// so that the connection is not lost.
+    #[allow(clippy::type_complexity, clippy::result_large_err)]
+    pub fn into_display(self) -> Result<DdRamDisplay<P, U>, (Self, Error<P::Error>)> {
+        match self.interpreter.into_display() {
+            Ok(display) => Ok(display),
+            Err((interpreter, e)) => Err((LcdProcServer { interpreter, ..self }, e)),
+        }
+    }
+
+    fn feed(&mut self, bytes: &[u8]) -> Result<(), Error<P::Error>> {
+        for &byte in bytes {
+            self.interpreter.feed(byte)?;
+        }
+
+        Ok(())
+    }
+}
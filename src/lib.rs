@@ -5,21 +5,85 @@
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[macro_use]
 extern crate bitflags;
+#[cfg(feature = "embedded-hal")]
+extern crate embedded_hal;
 
+#[macro_use]
 mod hal;
+mod error;
 mod lines;
 mod display;
 mod function_set;
 mod entry_mode;
 mod display_control;
 mod address;
+mod i2c_expander;
+mod mcp230xx;
+mod shift_register;
+mod serial_lcd;
+mod matrix_orbital;
+mod text;
+mod busy_flag;
+mod backlight;
+mod contrast;
+mod dual_controller;
+mod simulator;
+mod shared_bus;
+#[cfg(feature = "embedded-hal")]
+mod adapter;
+#[cfg(feature = "std")]
+mod timing;
+#[cfg(feature = "std")]
+mod vcd;
+#[cfg(feature = "std")]
+mod lcdproc;
+#[cfg(feature = "async")]
+mod async_hal;
+#[cfg(feature = "async")]
+mod async_display;
 
 pub use lines::{DefaultLines, Home};
 pub use display_control::{CursorBlinking, CursorState, DisplayControlBuilder, DisplayState};
 pub use entry_mode::EntryModeBuilder;
-pub use function_set::{FunctionSetBuilder, LineNumber};
-pub use display::{DdRamDisplay as Display, SeekCgRamFrom, SeekFrom, SetFrom, ShiftTo};
-pub use hal::{DataPins4Lines, DataPins8Lines, Delay, Direction, DisplayHardwareLayer, Init, Level,
-              ParallelConnection, Pins, ReadMode, Receive, Send, WriteMode};
+pub use function_set::{CharacterFont, DataLength, FunctionSetBuilder, LineNumber};
+pub use display::{DdRamDisplay as Display, ReleaseOnDrop, SeekCgRamFrom, SeekFrom, SetFrom,
+                  ShiftTo, SwitchRamResult};
+pub use busy_flag::BusyFlagPolling;
+pub use backlight::{Backlight, BacklightConnection, BacklightControl, Brightness,
+                    BrightnessControl, PwmBacklight, PwmPin};
+pub use contrast::{Contrast, ContrastControl, DacContrast, DacPin, NoContrast, PwmContrast,
+                   St7032Contrast};
+pub use dual_controller::{Controller, DdRamDisplay as DualControllerDisplay, DualControllerLines,
+                          DualEnable, SelectController,
+                          SwitchRamResult as DualControllerSwitchRamResult};
+pub use simulator::{BusLine, BusSimulator, SimulatedPin, SimulatedPins4Lines, SimulatedPins8Lines};
+pub use shared_bus::{ShareData, SharedBus, SharedBusConnection};
+pub use error::{Error, PinError};
+pub use i2c_expander::{ExpanderPins, I2cBus, I2cExpanderConnection};
+pub use mcp230xx::{Mcp230xx, Mcp230xxBus, Mcp230xxConnection, McpDataPins, McpPins,
+                   McpReadPin};
+pub use shift_register::{ShiftRegisterConnection, ShiftRegisterDataBits, ShiftRegisterLayout,
+                         ShiftRegisterPins};
+pub use serial_lcd::{SerialLcdConnection, SerialProtocol, SerialWrite};
+pub use matrix_orbital::{MatrixOrbitalInterpreter, ModuleSetting};
+pub use text::{LineOverflow, Unencodable};
+#[cfg(feature = "embedded-hal")]
+pub use adapter::{DelayAdapter, InputPinAdapter, IoPinAdapter, OutputPinAdapter};
+#[cfg(feature = "std")]
+pub use timing::{check_timing, Operation, RecordingPin, RecordingPins4Lines,
+                 RecordingPins8Lines, TimingConstraints, TraceRecorder, TracedOperation,
+                 TracingConnection, Transition, Violation, ViolationKind, VirtualDelay};
+#[cfg(feature = "std")]
+pub use lcdproc::{LcdProcError, LcdProcServer};
+#[cfg(feature = "async")]
+pub use async_hal::{AsyncDelay, AsyncInit, AsyncReceive, AsyncSend, AsyncWait};
+#[cfg(feature = "async")]
+pub use async_display::{DdRamDisplay as AsyncDisplay, SwitchRamResult as AsyncSwitchRamResult};
+pub use hal::{DataPins4Lines, DataPins8Lines, Delay, Direction, DisplayHardwareLayer, ErrorType,
+              Grounded, Init, Level, ParallelConnection, Pins, ReadMode, ReadWriteSignal, Receive,
+              Release, Send, Timing, Wait, WriteMode};
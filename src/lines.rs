@@ -1,4 +1,4 @@
-use address::{Address, Overflow};
+use crate::address::{Address, Overflow};
 
 const SECOND_LINE_ADDRESS: u8 = 0x40;
 
@@ -0,0 +1,318 @@
+use crate::address::Address;
+use crate::display::{CgRam, DdRam, DdRamDisplay, Display, SeekFrom, SetFrom, ShiftTo};
+use crate::display_control::{CursorBlinking, CursorState, DisplayControlBuilder};
+use crate::error::Error;
+use crate::hal::{Send, Wait};
+use crate::lines::Home;
+
+pub(crate) const PREFIX: u8 = 0xfe;
+pub(crate) const BACKLIGHT_ON: u8 = 0x42;
+pub(crate) const BACKLIGHT_OFF: u8 = 0x46;
+pub(crate) const SET_CURSOR: u8 = 0x47;
+pub(crate) const HOME: u8 = 0x48;
+pub(crate) const UNDERLINE_ON: u8 = 0x4a;
+pub(crate) const UNDERLINE_OFF: u8 = 0x4b;
+pub(crate) const CURSOR_LEFT: u8 = 0x4c;
+pub(crate) const CURSOR_RIGHT: u8 = 0x4d;
+pub(crate) const DEFINE_CHARACTER: u8 = 0x4e;
+pub(crate) const CONTRAST: u8 = 0x50;
+pub(crate) const BLINK_ON: u8 = 0x53;
+pub(crate) const BLINK_OFF: u8 = 0x54;
+pub(crate) const CLEAR: u8 = 0x58;
+pub(crate) const BRIGHTNESS: u8 = 0x99;
+
+const OUTPUT_OFF: u8 = 0x56;
+const OUTPUT_ON: u8 = 0x57;
+const CONTRAST_AND_SAVE: u8 = 0x91;
+const BRIGHTNESS_AND_SAVE: u8 = 0x98;
+
+const CHARACTERS: u8 = 8;
+const CHARACTER_ROWS: u8 = 8;
+const MAX_ARGUMENTS: usize = 1 + CHARACTER_ROWS as usize;
+
+const SECOND_ROW_ADDRESS: u8 = 0x40;
+
+/// Returns the number of argument bytes following the given command.
+fn argument_count(command: u8) -> usize {
+    match command {
+        SET_CURSOR => 2,
+        DEFINE_CHARACTER => MAX_ARGUMENTS,
+        BACKLIGHT_ON | CONTRAST | CONTRAST_AND_SAVE | BRIGHTNESS | BRIGHTNESS_AND_SAVE
+        | OUTPUT_OFF | OUTPUT_ON => 1,
+        _ => 0,
+    }
+}
+
+/// Enumeration of module settings, which are not handled by the display controller. They are
+/// returned by the [`MatrixOrbitalInterpreter`], so that the firmware can apply them, e.g. by
+/// PWM pins.
+///
+/// [`MatrixOrbitalInterpreter`]: struct.MatrixOrbitalInterpreter.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModuleSetting {
+    /// Switches the backlight on or off.
+    Backlight(bool),
+    /// Sets the brightness of the backlight.
+    Brightness(u8),
+    /// Sets the contrast.
+    Contrast(u8),
+    /// Switches the general purpose output with the given number on or off.
+    GeneralPurposeOutput { output: u8, enabled: bool },
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Text,
+    Command,
+    Arguments { command: u8, received: usize },
+}
+
+enum RamDisplay<P, U>
+where
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    DdRam(DdRamDisplay<P, U>),
+    /// A display defining a custom character, together with the DDRAM address to return to.
+    CgRam(Display<P, U, CgRam>, Address<DdRam>),
+}
+
+/// An interpreter of the Matrix Orbital command set, which executes a serial byte stream on a
+/// display.
+///
+/// It allows a microcontroller driving a HD44780 panel to pose as a serial LCD module for PC
+/// software like LCDproc or lcd4linux. Bytes are written as text, unless they are prefixed by
+/// `0xfe`, which starts a command.
+///
+/// Positioning, home, clear, custom characters, cursor movement, underline and blinking cursor
+/// are executed on the display. Backlight, brightness, contrast and general purpose output
+/// commands are returned as [`ModuleSetting`]. Any other command is skipped, which assumes that
+/// it has no arguments.
+///
+/// Rows three and four continue the first and the second line of the display data RAM, as usual
+/// for HD44780 panels.
+///
+/// [`ModuleSetting`]: enum.ModuleSetting.html
+pub struct MatrixOrbitalInterpreter<P, U>
+where
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    display: Option<RamDisplay<P, U>>,
+    columns: u8,
+    display_control: DisplayControlBuilder,
+    state: State,
+    arguments: [u8; MAX_ARGUMENTS],
+}
+
+impl<P, U> MatrixOrbitalInterpreter<P, U>
+where
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Creates a new interpreter for the given display, which has the given number of columns.
+    ///
+    /// The display is expected to be initialized and switched on with the cursor turned off.
+    pub fn new(display: DdRamDisplay<P, U>, columns: u8) -> Self {
+        MatrixOrbitalInterpreter {
+            display: Some(RamDisplay::DdRam(display)),
+            columns,
+            display_control: DisplayControlBuilder::default(),
+            state: State::Text,
+            arguments: [0; MAX_ARGUMENTS],
+        }
+    }
+
+    fn take_display(&mut self) -> RamDisplay<P, U> {
+        match self.display.take() {
+            Some(display) => display,
+            None => unreachable!("the display is only taken while switching RAM"),
+        }
+    }
+
+    /// Returns the DDRAM address of the given one-based column and row. Columns beyond the
+    /// display are clamped to the last column, as they are received from an untrusted stream.
+    fn address(&self, column: u8, row: u8) -> u8 {
+        let start = match row.saturating_sub(1) % 4 {
+            0 => 0,
+            1 => SECOND_ROW_ADDRESS,
+            2 => self.columns,
+            _ => SECOND_ROW_ADDRESS.wrapping_add(self.columns),
+        };
+        let column = column.saturating_sub(1).min(self.columns.saturating_sub(1));
+
+        start.wrapping_add(column)
+    }
+}
+
+impl<P, U> MatrixOrbitalInterpreter<P, U>
+where
+    P: Send + Wait,
+    U: Into<Address<DdRam>> + Into<Address<CgRam>> + Home,
+{
+    /// Interprets the next byte of the stream.
+    ///
+    /// Returns a module setting as soon as the command and all of its arguments have been
+    /// received.
+    pub fn feed(&mut self, byte: u8) -> Result<Option<ModuleSetting>, Error<P::Error>> {
+        match self.state {
+            State::Text if byte == PREFIX => {
+                self.state = State::Command;
+                Ok(None)
+            }
+            State::Text => {
+                self.ddram()?.write(byte)?;
+                Ok(None)
+            }
+            State::Command if argument_count(byte) == 0 => {
+                self.state = State::Text;
+                self.execute(byte)
+            }
+            State::Command => {
+                self.state = State::Arguments {
+                    command: byte,
+                    received: 0,
+                };
+                Ok(None)
+            }
+            State::Arguments { command, received } => {
+                self.arguments[received] = byte;
+
+                if received + 1 < argument_count(command) {
+                    self.state = State::Arguments {
+                        command,
+                        received: received + 1,
+                    };
+                    Ok(None)
+                } else {
+                    self.state = State::Text;
+                    self.execute(command)
+                }
+            }
+        }
+    }
+
+    /// Returns the display to access it directly, e.g. to apply a module setting.
+    pub fn display(&mut self) -> Result<&mut DdRamDisplay<P, U>, Error<P::Error>> {
+        self.ddram()
+    }
+
+    /// Returns the display. On failure the unchanged interpreter is returned together with the
+    /// error, so that the connection is not lost.
+    #[allow(clippy::type_complexity)]
+    pub fn into_display(mut self) -> Result<DdRamDisplay<P, U>, (Self, Error<P::Error>)> {
+        if let Err(e) = self.ddram() {
+            return Err((self, e));
+        }
+
+        match self.take_display() {
+            RamDisplay::DdRam(display) => Ok(display),
+            RamDisplay::CgRam(..) => unreachable!("the display has been switched to DDRAM"),
+        }
+    }
+
+    fn execute(&mut self, command: u8) -> Result<Option<ModuleSetting>, Error<P::Error>> {
+        let argument = self.arguments[0];
+
+        match command {
+            CLEAR => self.ddram()?.clear()?,
+            HOME => self.ddram()?.seek(SeekFrom::Home(0))?,
+            SET_CURSOR => {
+                let address = self.address(self.arguments[0], self.arguments[1]);
+                self.ddram()?.seek(SeekFrom::Home(address))?;
+            }
+            CURSOR_LEFT => self.ddram()?.shift_cursor(ShiftTo::Left(1))?,
+            CURSOR_RIGHT => self.ddram()?.shift_cursor(ShiftTo::Right(1))?,
+            UNDERLINE_ON | UNDERLINE_OFF => {
+                let cursor = if command == UNDERLINE_ON {
+                    CursorState::On
+                } else {
+                    CursorState::Off
+                };
+                self.display_control.set_cursor(cursor);
+                self.update_display_control()?;
+            }
+            BLINK_ON | BLINK_OFF => {
+                let blinking = if command == BLINK_ON {
+                    CursorBlinking::On
+                } else {
+                    CursorBlinking::Off
+                };
+                self.display_control.set_cursor_blinking(blinking);
+                self.update_display_control()?;
+            }
+            DEFINE_CHARACTER => self.define_character()?,
+            BACKLIGHT_ON => return Ok(Some(ModuleSetting::Backlight(true))),
+            BACKLIGHT_OFF => return Ok(Some(ModuleSetting::Backlight(false))),
+            BRIGHTNESS | BRIGHTNESS_AND_SAVE => {
+                return Ok(Some(ModuleSetting::Brightness(argument)))
+            }
+            CONTRAST | CONTRAST_AND_SAVE => return Ok(Some(ModuleSetting::Contrast(argument))),
+            OUTPUT_OFF | OUTPUT_ON => {
+                return Ok(Some(ModuleSetting::GeneralPurposeOutput {
+                    output: argument,
+                    enabled: command == OUTPUT_ON,
+                }))
+            }
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    fn update_display_control(&mut self) -> Result<(), Error<P::Error>> {
+        let display_control = self.display_control;
+        self.ddram()?.set_display_control(&display_control)
+    }
+
+    /// Writes the received pattern to the character generator RAM and returns to the previous
+    /// position in display data RAM.
+    fn define_character(&mut self) -> Result<(), Error<P::Error>> {
+        let address = (self.arguments[0] % CHARACTERS) * CHARACTER_ROWS;
+        self.ddram()?;
+
+        let display = match self.take_display() {
+            RamDisplay::DdRam(display) => display,
+            RamDisplay::CgRam(..) => unreachable!("the display has been switched to DDRAM"),
+        };
+        let ddram_address = display.cursor_address();
+
+        match display.set_cgram_address(address) {
+            Ok(display) => self.display = Some(RamDisplay::CgRam(display, ddram_address)),
+            Err((display, e)) => {
+                self.display = Some(RamDisplay::DdRam(display));
+                return Err(e);
+            }
+        }
+
+        if let Some(RamDisplay::CgRam(ref mut display, _)) = self.display {
+            for &row in &self.arguments[1..] {
+                display.write(row)?;
+            }
+        }
+
+        self.ddram().map(|_| ())
+    }
+
+    /// Returns the display in display data RAM mode, after switching back from the character
+    /// generator RAM if needed.
+    fn ddram(&mut self) -> Result<&mut DdRamDisplay<P, U>, Error<P::Error>> {
+        let display = match self.take_display() {
+            RamDisplay::DdRam(display) => display,
+            RamDisplay::CgRam(display, address) => {
+                let offset = u8::from(address).wrapping_sub(U::FIRST_LINE_ADDRESS);
+
+                match display.set_ddram_address(SetFrom::Home(offset)) {
+                    Ok(display) => display,
+                    Err((display, e)) => {
+                        self.display = Some(RamDisplay::CgRam(display, address));
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        self.display = Some(RamDisplay::DdRam(display));
+
+        match self.display {
+            Some(RamDisplay::DdRam(ref mut display)) => Ok(display),
+            _ => unreachable!("the display has been switched to DDRAM"),
+        }
+    }
+}
@@ -0,0 +1,578 @@
+use core::cell::Cell;
+
+use core::fmt::Debug;
+
+use crate::backlight::Backlight;
+use crate::error::{check_pins, PinError};
+use crate::hal::{first_error, Delay, ErrorType, Grounded, Init, Nibble, ReadMode, Receive, Send,
+                 Timing, Wait, WriteMode};
+
+/// The `Mcp230xxBus` trait is intended to be implemented by the library user as a thin wrapper
+/// around the hardware specific I2C or SPI bus an MCP230xx I/O expander is attached to.
+///
+/// The implementation is responsible for addressing the expander, e.g. by its I2C address or SPI
+/// opcode.
+pub trait Mcp230xxBus {
+    /// The error reported by the bus, e.g. if the expander does not respond.
+    type Error: Debug;
+
+    /// Writes the given value to a register of the expander.
+    fn write_register(&self, register: u8, value: u8) -> Result<(), Self::Error>;
+
+    /// Reads the value of a register of the expander.
+    fn read_register(&self, register: u8) -> Result<u8, Self::Error>;
+}
+
+/// Enumeration of supported MCP230xx expanders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mcp230xx {
+    /// 8-bit expander with a single port (GP0-GP7).
+    Mcp23008,
+    /// 16-bit expander with two ports (GPA0-GPA7, GPB0-GPB7), used with the default register
+    /// mapping (`IOCON.BANK = 0`).
+    Mcp23017,
+}
+
+#[derive(Clone, Copy)]
+enum Register {
+    IoDir,
+    Gpio,
+    OLat,
+}
+
+enum Port {
+    A,
+    B,
+}
+
+impl Mcp230xx {
+    fn address(&self, register: Register, port: Port) -> u8 {
+        match (*self, register, port) {
+            (Mcp230xx::Mcp23008, Register::IoDir, _) => 0x00,
+            (Mcp230xx::Mcp23008, Register::Gpio, _) => 0x09,
+            (Mcp230xx::Mcp23008, Register::OLat, _) => 0x0a,
+            (Mcp230xx::Mcp23017, Register::IoDir, Port::A) => 0x00,
+            (Mcp230xx::Mcp23017, Register::IoDir, Port::B) => 0x01,
+            (Mcp230xx::Mcp23017, Register::Gpio, Port::A) => 0x12,
+            (Mcp230xx::Mcp23017, Register::Gpio, Port::B) => 0x13,
+            (Mcp230xx::Mcp23017, Register::OLat, Port::A) => 0x14,
+            (Mcp230xx::Mcp23017, Register::OLat, Port::B) => 0x15,
+        }
+    }
+
+    fn has_port_b(&self) -> bool {
+        *self == Mcp230xx::Mcp23017
+    }
+
+    fn pin_count(&self) -> u8 {
+        if self.has_port_b() {
+            16
+        } else {
+            8
+        }
+    }
+}
+
+/// Enumeration of possible data line setups of an MCP230xx wired display.
+///
+/// Pins `0` to `7` refer to GP0-GP7 (GPA0-GPA7) and `8` to `15` to GPB0-GPB7.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum McpDataPins {
+    /// Pins connected to D4-D7.
+    FourBit([u8; 4]),
+    /// Pins connected to D0-D7, only available on the MCP23017.
+    EightBit([u8; 8]),
+}
+
+impl McpDataPins {
+    fn as_slice(&self) -> &[u8] {
+        match *self {
+            McpDataPins::FourBit(ref pins) => pins,
+            McpDataPins::EightBit(ref pins) => pins,
+        }
+    }
+
+    fn mask(&self) -> u16 {
+        self.as_slice().iter().fold(0, |mask, pin| mask | bit(*pin))
+    }
+
+    fn port_bits(&self, value: u8) -> u16 {
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| value & (1 << i) != 0)
+            .fold(0, |port, (_, pin)| port | bit(*pin))
+    }
+
+    fn port_value(&self, port: u16) -> u8 {
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .filter(|&(_, pin)| port & bit(*pin) != 0)
+            .fold(0, |value, (i, _)| value | (1 << i))
+    }
+}
+
+/// This trait is implemented for the possible wirings of the R/W signal to an MCP230xx: a pin
+/// (`u8`) or [`Grounded`], which makes the display write-only.
+///
+/// [`Grounded`]: struct.Grounded.html
+pub trait McpReadPin: Copy {
+    /// Returns the pin R/W is wired to, if any.
+    fn pin(&self) -> Option<u8>;
+}
+
+impl McpReadPin for u8 {
+    fn pin(&self) -> Option<u8> {
+        Some(*self)
+    }
+}
+
+impl McpReadPin for Grounded {
+    fn pin(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// This struct describes which pin of an MCP230xx I/O expander is wired to which signal of the
+/// LCD.
+///
+/// Displays wired write-only use [`Grounded`] as `read` pin.
+///
+/// Presets for common backpacks are available as associated constants.
+///
+/// [`Grounded`]: struct.Grounded.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct McpPins<R = u8> {
+    pub register_select: u8,
+    pub read: R,
+    pub enable: u8,
+    /// `None` if the backlight is not controlled by the expander.
+    pub backlight: Option<u8>,
+    pub data: McpDataPins,
+}
+
+impl McpPins<Grounded> {
+    /// The wiring of the Adafruit I2C/SPI character LCD backpack (MCP23008).
+    ///
+    ///  - **GP1:** RS
+    ///  - **GP2:** E
+    ///  - **GP3-GP6:** D4-D7
+    ///  - **GP7:** backlight
+    ///
+    /// R/W is tied to ground.
+    pub const ADAFRUIT_BACKPACK: McpPins<Grounded> = McpPins {
+        register_select: 1,
+        read: Grounded,
+        enable: 2,
+        backlight: Some(7),
+        data: McpDataPins::FourBit([3, 4, 5, 6]),
+    };
+}
+
+impl McpPins {
+
+    /// The wiring of the Adafruit RGB LCD shield (MCP23017).
+    ///
+    ///  - **GPB7:** RS
+    ///  - **GPB6:** R/W
+    ///  - **GPB5:** E
+    ///  - **GPB4-GPB1:** D4-D7
+    ///
+    /// The RGB backlight is not covered by this preset.
+    pub const ADAFRUIT_RGB_SHIELD: McpPins = McpPins {
+        register_select: 15,
+        read: 14,
+        enable: 13,
+        backlight: None,
+        data: McpDataPins::FourBit([12, 11, 10, 9]),
+    };
+}
+
+impl<R: McpReadPin> McpPins<R> {
+    /// Checks that all signals are wired to distinct pins of the given expander.
+    fn check(&self, expander: Mcp230xx) -> Result<(), PinError> {
+        let signals = [
+            Some(self.register_select),
+            self.read.pin(),
+            Some(self.enable),
+            self.backlight,
+        ];
+
+        let mut pins = [0; 12];
+        let mut count = 0;
+
+        for &pin in signals.iter().flatten().chain(self.data.as_slice()) {
+            pins[count] = pin;
+            count += 1;
+        }
+
+        check_pins(&pins[..count], expander.pin_count())
+    }
+
+    fn output_mask(&self) -> u16 {
+        let mut mask = bit(self.register_select) | bit(self.enable) | self.data.mask();
+
+        if let Some(read) = self.read.pin() {
+            mask |= bit(read);
+        }
+
+        if let Some(backlight) = self.backlight {
+            mask |= bit(backlight);
+        }
+
+        mask
+    }
+}
+
+fn bit(pin: u8) -> u16 {
+    1 << pin
+}
+
+/// A connection to a display which is wired to an MCP23008 or MCP23017 I/O expander.
+///
+/// The display is driven in 4-bit or 8-bit mode depending on the given [`McpDataPins`], so it has
+/// to be initialized with the matching `DataLength`.
+///
+/// [`McpDataPins`]: enum.McpDataPins.html
+pub struct Mcp230xxConnection<B, T, R = u8> {
+    bus: B,
+    expander: Mcp230xx,
+    pins: McpPins<R>,
+    iodir: Cell<u16>,
+    olat: Cell<u16>,
+    delay: T,
+    timing: Timing,
+}
+
+impl<B, T, R> Mcp230xxConnection<B, T, R>
+where
+    R: McpReadPin,
+{
+    /// Creates a new connection to the given expander.
+    ///
+    /// The backlight is switched on by default.
+    ///
+    /// Fails if a signal is wired to a pin the expander does not have (e.g. eight data lines or a
+    /// pin beyond `7` on the MCP23008) or two signals share a pin.
+    pub fn new(
+        bus: B,
+        expander: Mcp230xx,
+        pins: McpPins<R>,
+        delay: T,
+    ) -> Result<Self, PinError> {
+        pins.check(expander)?;
+
+        let olat = match pins.backlight {
+            Some(backlight) => bit(backlight),
+            None => 0,
+        };
+
+        Ok(Mcp230xxConnection {
+            bus,
+            expander,
+            pins,
+            iodir: Cell::new(0xffff),
+            olat: Cell::new(olat),
+            delay,
+            timing: Timing::default(),
+        })
+    }
+}
+
+impl<B, T, R> Mcp230xxConnection<B, T, R> {
+    /// Sets the timing of the bus, which defaults to the timing for the lower supply voltage
+    /// ([`Timing::VCC_3V`]).
+    ///
+    /// [`Timing::VCC_3V`]: struct.Timing.html#associatedconstant.VCC_3V
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Returns the underlying bus.
+    pub fn into_bus(self) -> B {
+        self.bus
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn delay(&self) -> &T {
+        &self.delay
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn timing(&self) -> &Timing {
+        &self.timing
+    }
+}
+
+impl<B, T, R> Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    R: McpReadPin,
+{
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    ///
+    /// Does nothing if the backlight is not wired to the expander.
+    pub fn set_backlight(&self, enabled: bool) -> Result<(), B::Error> {
+        if let Some(backlight) = self.pins.backlight {
+            let olat = if enabled {
+                self.olat.get() | bit(backlight)
+            } else {
+                self.olat.get() & !bit(backlight)
+            };
+
+            self.write_olat(olat)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_registers(
+        &self,
+        register: Register,
+        value: u16,
+        changed: u16,
+    ) -> Result<(), B::Error> {
+        let (low, high) = split(value);
+
+        if changed & 0x00ff != 0 {
+            self.bus
+                .write_register(self.expander.address(register, Port::A), low)?;
+        }
+
+        if self.expander.has_port_b() && changed & 0xff00 != 0 {
+            self.bus
+                .write_register(self.expander.address(register, Port::B), high)?;
+        }
+
+        Ok(())
+    }
+
+    // the cached register values are only updated after a successful write, so that a failed
+    // write is repeated by the next one
+
+    fn write_olat(&self, olat: u16) -> Result<(), B::Error> {
+        self.write_registers(Register::OLat, olat, olat ^ self.olat.get())?;
+        self.olat.set(olat);
+
+        Ok(())
+    }
+
+    fn write_iodir(&self, iodir: u16) -> Result<(), B::Error> {
+        self.write_registers(Register::IoDir, iodir, iodir ^ self.iodir.get())?;
+        self.iodir.set(iodir);
+
+        Ok(())
+    }
+
+    fn read_gpio(&self) -> Result<u16, B::Error> {
+        let mask = self.pins.data.mask();
+        let mut value = 0;
+
+        if mask & 0x00ff != 0 {
+            value |= u16::from(
+                self.bus
+                    .read_register(self.expander.address(Register::Gpio, Port::A))?,
+            );
+        }
+
+        if self.expander.has_port_b() && mask & 0xff00 != 0 {
+            value |= u16::from(
+                self.bus
+                    .read_register(self.expander.address(Register::Gpio, Port::B))?,
+            ) << 8;
+        }
+
+        Ok(value)
+    }
+
+    fn control_bits(&self, register_select: bool, read: bool) -> u16 {
+        let mut olat = self.olat.get();
+
+        olat &= !(bit(self.pins.register_select) | bit(self.pins.enable));
+
+        if register_select {
+            olat |= bit(self.pins.register_select);
+        }
+
+        if let Some(read_pin) = self.pins.read.pin() {
+            if read {
+                olat |= bit(read_pin);
+            } else {
+                olat &= !bit(read_pin);
+            }
+        }
+
+        olat
+    }
+}
+
+impl<B, T, R> Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    T: Delay,
+    R: McpReadPin,
+{
+    fn write_value(&self, control: u16, value: u8) -> Result<(), B::Error> {
+        let olat = (control & !self.pins.data.mask()) | self.pins.data.port_bits(value);
+        let enable = bit(self.pins.enable);
+
+        self.write_olat(olat)?;
+        self.delay.delay_ns(self.timing.address_setup_time);
+
+        self.write_olat(olat | enable)?;
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+
+        self.write_olat(olat)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(())
+    }
+
+    fn read_value(&self, control: u16) -> Result<u8, B::Error> {
+        let enable = bit(self.pins.enable);
+
+        self.write_olat(control)?;
+        self.delay.delay_ns(self.timing.address_setup_time);
+
+        self.write_olat(control | enable)?;
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+
+        let mut value = 0;
+        let read = self.read_gpio().map(|gpio| value = gpio);
+
+        // E is pulled low again even if the read failed
+        first_error([read, self.write_olat(control)])?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(self.pins.data.port_value(value))
+    }
+
+    fn read_data(&self, control: u16) -> Result<u8, B::Error> {
+        match self.pins.data {
+            McpDataPins::FourBit(_) => {
+                let upper = self.read_value(control)?;
+                let lower = self.read_value(control)?;
+
+                Ok((upper << 4) | (lower & 0x0f))
+            }
+            McpDataPins::EightBit(_) => self.read_value(control),
+        }
+    }
+}
+
+impl<B, T, R> ErrorType for Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+{
+    type Error = B::Error;
+}
+
+impl<B, T, R> Backlight for Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    R: McpReadPin,
+{
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+        Mcp230xxConnection::set_backlight(self, enabled)
+    }
+}
+
+impl<B, T, R> Init for Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    R: McpReadPin,
+{
+    fn init(&self) -> Result<(), Self::Error> {
+        // the output latches are set up before the pins are switched to outputs, so that the
+        // control lines do not glitch
+        self.write_registers(Register::OLat, self.olat.get(), 0xffff)?;
+
+        let iodir = !self.pins.output_mask();
+        self.write_registers(Register::IoDir, iodir, 0xffff)?;
+        self.iodir.set(iodir);
+
+        Ok(())
+    }
+}
+
+impl<B, T, R> Send for Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    T: Delay,
+    R: McpReadPin,
+{
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        let (register_select, value) = match mode {
+            WriteMode::Command(value) => (false, value),
+            WriteMode::Data(value) => (true, value),
+        };
+
+        let control = self.control_bits(register_select, false);
+
+        match self.pins.data {
+            McpDataPins::FourBit(_) => {
+                self.write_value(control, Nibble::Upper(value).into())?;
+                self.write_value(control, Nibble::Lower(value).into())
+            }
+            McpDataPins::EightBit(_) => self.write_value(control, value),
+        }
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        let control = self.control_bits(false, false);
+
+        match self.pins.data {
+            McpDataPins::FourBit(_) => self.write_value(control, Nibble::Upper(cmd).into()),
+            McpDataPins::EightBit(_) => self.write_value(control, cmd),
+        }
+    }
+}
+
+/// Receiving is only possible if R/W is wired to the expander.
+impl<B, T> Receive for Mcp230xxConnection<B, T, u8>
+where
+    B: Mcp230xxBus,
+    T: Delay,
+{
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+        let register_select = match mode {
+            ReadMode::Data => true,
+            ReadMode::BusyFlag => false,
+        };
+
+        let control = self.control_bits(register_select, true);
+
+        let iodir = self.iodir.get();
+        self.write_iodir(iodir | self.pins.data.mask())?;
+
+        let mut result = 0;
+        let read = self.read_data(control).map(|value| result = value);
+
+        // the data pins are switched back to outputs even if the read failed
+        first_error([read, self.write_iodir(iodir)])?;
+
+        Ok(result)
+    }
+}
+
+fn split(value: u16) -> (u8, u8) {
+    (value as u8, (value >> 8) as u8)
+}
+
+impl<B, T, R> Wait for Mcp230xxConnection<B, T, R>
+where
+    B: Mcp230xxBus,
+    T: Delay,
+{
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.delay.delay_us(self.timing.execution_time(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+}
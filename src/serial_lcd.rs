@@ -0,0 +1,358 @@
+use core::cell::{Cell, RefCell};
+use core::fmt::Debug;
+
+use crate::backlight::{Backlight, Brightness};
+use crate::contrast::Contrast;
+use crate::hal::{Delay, ErrorType, Init, Send, Wait, WriteMode};
+use crate::matrix_orbital;
+
+const CGRAM_SIZE: usize = 64;
+const CHARACTER_ROWS: u8 = 8;
+
+const SERLCD_INSTRUCTION: u8 = 0xfe;
+const SERLCD_SETTING: u8 = 0x7c;
+const SERLCD_BACKLIGHT_OFF: u8 = 128;
+const SERLCD_BACKLIGHT_LEVELS: u8 = 29;
+const SERLCD_CONTRAST: u8 = 0x18;
+
+/// The character written instead of data bytes, which would be taken as command prefix.
+const SUBSTITUTE: u8 = b' ';
+
+/// The `SerialWrite` trait is intended to be implemented by the library user as a thin wrapper
+/// around the hardware specific UART or any other byte stream.
+pub trait SerialWrite {
+    /// The error reported by the serial port.
+    type Error: Debug;
+
+    /// Writes the given bytes.
+    fn write(&self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Enumeration of command sets spoken by serial LCD backpacks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SerialProtocol {
+    /// The command set of Matrix Orbital LCD and LK modules, which is understood by many
+    /// compatible backpacks as well.
+    MatrixOrbital,
+    /// The command set of SparkFun SerLCD backpacks and their successor OpenLCD, which pass
+    /// instructions through to the HD44780 controller.
+    SerLcd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Ram {
+    DdRam,
+    CgRam(u8),
+}
+
+/// A write-only connection to a display with a serial LCD backpack, which translates the
+/// instructions of the display into the command set of the backpack.
+///
+/// The backpack initializes the display itself, so function set instructions are dropped. Data
+/// bytes clashing with a command prefix of the protocol (`0xfe`, and `0x7c` for SerLCD) are
+/// written as space.
+///
+/// Matrix Orbital modules do neither know display shifts nor entry modes, so these instructions
+/// are dropped. Custom characters are sent, as soon as all rows of a character have been written
+/// or another instruction follows.
+///
+/// Matrix Orbital modules can not hide their text either, so switching the display off clears
+/// it and returns the cursor home. The text has to be written again after switching the display
+/// back on.
+pub struct SerialLcdConnection<W, T> {
+    port: W,
+    protocol: SerialProtocol,
+    columns: u8,
+    delay: T,
+    backlight: Cell<bool>,
+    brightness: Cell<u8>,
+    ram: Cell<Ram>,
+    cgram: RefCell<[u8; CGRAM_SIZE]>,
+    pending_character: Cell<Option<u8>>,
+}
+
+impl<W, T> SerialLcdConnection<W, T> {
+    /// Creates a new connection to a backpack speaking the given protocol, which drives a
+    /// display with the given number of columns.
+    ///
+    /// The backlight is assumed to be switched on at full brightness.
+    pub fn new(port: W, protocol: SerialProtocol, columns: u8, delay: T) -> Self {
+        SerialLcdConnection {
+            port,
+            protocol,
+            columns,
+            delay,
+            backlight: Cell::new(true),
+            brightness: Cell::new(u8::MAX),
+            ram: Cell::new(Ram::DdRam),
+            cgram: RefCell::new([0; CGRAM_SIZE]),
+            pending_character: Cell::new(None),
+        }
+    }
+
+    /// Returns the underlying serial port.
+    pub fn into_port(self) -> W {
+        self.port
+    }
+
+    /// Returns the one-based column and row of the given DDRAM address, as used by Matrix
+    /// Orbital modules. Displays with four lines continue the first and second line at the third
+    /// and fourth.
+    fn position(&self, address: u8) -> (u8, u8) {
+        let (mut column, mut row) = if address >= 0x40 {
+            (address - 0x40, 1)
+        } else {
+            (address, 0)
+        };
+
+        if self.columns > 0 && column >= self.columns {
+            column -= self.columns;
+            row += 2;
+        }
+
+        (column + 1, row + 1)
+    }
+}
+
+impl<W, T> SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    fn send_matrix_orbital(&self, cmd: u8) -> Result<(), W::Error> {
+        self.flush_character()?;
+
+        if cmd & 0b1000_0000 != 0 {
+            self.ram.set(Ram::DdRam);
+
+            let (column, row) = self.position(cmd & 0b0111_1111);
+            self.port
+                .write(&[matrix_orbital::PREFIX, matrix_orbital::SET_CURSOR, column, row])
+        } else if cmd & 0b0100_0000 != 0 {
+            self.ram.set(Ram::CgRam(cmd & 0b0011_1111));
+            Ok(())
+        } else if cmd & 0b0010_0000 != 0 {
+            // function set
+            Ok(())
+        } else if cmd & 0b0001_0000 != 0 {
+            match (cmd & 0b0000_1000 != 0, cmd & 0b0000_0100 != 0) {
+                (true, _) => Ok(()),
+                (false, true) => self.write_matrix_orbital(matrix_orbital::CURSOR_RIGHT),
+                (false, false) => self.write_matrix_orbital(matrix_orbital::CURSOR_LEFT),
+            }
+        } else if cmd & 0b0000_1000 != 0 {
+            if cmd & 0b0000_0100 == 0 {
+                // display off
+                self.write_matrix_orbital(matrix_orbital::CLEAR)?;
+            }
+
+            let underline = if cmd & 0b0000_0010 != 0 {
+                matrix_orbital::UNDERLINE_ON
+            } else {
+                matrix_orbital::UNDERLINE_OFF
+            };
+            let blink = if cmd & 0b0000_0001 != 0 {
+                matrix_orbital::BLINK_ON
+            } else {
+                matrix_orbital::BLINK_OFF
+            };
+
+            self.write_matrix_orbital(underline)?;
+            self.write_matrix_orbital(blink)
+        } else if cmd & 0b0000_0100 != 0 {
+            // entry mode set
+            Ok(())
+        } else if cmd & 0b0000_0010 != 0 {
+            self.ram.set(Ram::DdRam);
+            self.write_matrix_orbital(matrix_orbital::HOME)
+        } else if cmd & 0b0000_0001 != 0 {
+            self.ram.set(Ram::DdRam);
+            self.write_matrix_orbital(matrix_orbital::CLEAR)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_matrix_orbital(&self, command: u8) -> Result<(), W::Error> {
+        self.port.write(&[matrix_orbital::PREFIX, command])
+    }
+
+    /// Stores a pattern row of a custom character, which is sent once the character is complete.
+    fn write_character_row(&self, address: u8, row: u8) -> Result<(), W::Error> {
+        self.cgram.borrow_mut()[usize::from(address)] = row;
+        self.pending_character.set(Some(address / CHARACTER_ROWS));
+
+        let next = (address + 1) % CGRAM_SIZE as u8;
+        self.ram.set(Ram::CgRam(next));
+
+        if next / CHARACTER_ROWS != address / CHARACTER_ROWS {
+            self.flush_character()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush_character(&self) -> Result<(), W::Error> {
+        let character = match self.pending_character.take() {
+            Some(character) => character,
+            None => return Ok(()),
+        };
+
+        let start = usize::from(character * CHARACTER_ROWS);
+        let cgram = self.cgram.borrow();
+
+        let mut bytes = [0; 3 + CHARACTER_ROWS as usize];
+        bytes[0] = matrix_orbital::PREFIX;
+        bytes[1] = matrix_orbital::DEFINE_CHARACTER;
+        bytes[2] = character;
+        bytes[3..].copy_from_slice(&cgram[start..start + usize::from(CHARACTER_ROWS)]);
+
+        self.port.write(&bytes)
+    }
+
+    fn write_data(&self, value: u8) -> Result<(), W::Error> {
+        let clashes = match self.protocol {
+            SerialProtocol::MatrixOrbital => value == matrix_orbital::PREFIX,
+            SerialProtocol::SerLcd => value == SERLCD_INSTRUCTION || value == SERLCD_SETTING,
+        };
+
+        if clashes {
+            self.port.write(&[SUBSTITUTE])
+        } else {
+            self.port.write(&[value])
+        }
+    }
+
+    /// Returns the SerLCD setting of the given brightness.
+    fn serlcd_brightness(brightness: u8) -> u8 {
+        let level = u16::from(brightness) * u16::from(SERLCD_BACKLIGHT_LEVELS) / 255;
+        SERLCD_BACKLIGHT_OFF + level as u8
+    }
+}
+
+impl<W, T> ErrorType for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    type Error = W::Error;
+}
+
+impl<W, T> Init for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    /// Does nothing, the backpack initializes the display on its own.
+    fn init(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<W, T> Send for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        match (self.protocol, mode) {
+            (SerialProtocol::MatrixOrbital, WriteMode::Command(cmd)) => {
+                self.send_matrix_orbital(cmd)
+            }
+            (SerialProtocol::MatrixOrbital, WriteMode::Data(value)) => match self.ram.get() {
+                Ram::CgRam(address) => self.write_character_row(address, value),
+                Ram::DdRam => self.write_data(value),
+            },
+            (SerialProtocol::SerLcd, WriteMode::Command(cmd)) => {
+                if cmd & 0b1110_0000 == 0b0010_0000 {
+                    // the backpack owns the interface, so function set instructions are dropped
+                    Ok(())
+                } else {
+                    self.port.write(&[SERLCD_INSTRUCTION, cmd])
+                }
+            }
+            (SerialProtocol::SerLcd, WriteMode::Data(value)) => self.write_data(value),
+        }
+    }
+}
+
+impl<W, T> Wait for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+    T: Delay,
+{
+    /// Does nothing, the backpack waits until the display has executed an instruction.
+    fn wait(&self, _: WriteMode) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+}
+
+impl<W, T> Backlight for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+        self.backlight.set(enabled);
+
+        match (self.protocol, enabled) {
+            (SerialProtocol::MatrixOrbital, true) => {
+                // the backlight stays on for an unlimited number of minutes
+                self.port
+                    .write(&[matrix_orbital::PREFIX, matrix_orbital::BACKLIGHT_ON, 0])
+            }
+            (SerialProtocol::MatrixOrbital, false) => {
+                self.write_matrix_orbital(matrix_orbital::BACKLIGHT_OFF)
+            }
+            (SerialProtocol::SerLcd, true) => {
+                let setting = Self::serlcd_brightness(self.brightness.get());
+                self.port.write(&[SERLCD_SETTING, setting])
+            }
+            (SerialProtocol::SerLcd, false) => {
+                self.port.write(&[SERLCD_SETTING, SERLCD_BACKLIGHT_OFF])
+            }
+        }
+    }
+}
+
+impl<W, T> Brightness for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    fn brightness(&self) -> u8 {
+        self.brightness.get()
+    }
+
+    fn set_brightness(&self, brightness: u8) -> Result<(), Self::Error> {
+        self.brightness.set(brightness);
+
+        match self.protocol {
+            SerialProtocol::MatrixOrbital => {
+                self.port
+                    .write(&[matrix_orbital::PREFIX, matrix_orbital::BRIGHTNESS, brightness])
+            }
+            SerialProtocol::SerLcd if self.backlight.get() => {
+                let setting = Self::serlcd_brightness(brightness);
+                self.port.write(&[SERLCD_SETTING, setting])
+            }
+            SerialProtocol::SerLcd => Ok(()),
+        }
+    }
+}
+
+impl<W, T> Contrast for SerialLcdConnection<W, T>
+where
+    W: SerialWrite,
+{
+    /// Sets the contrast, which is supported by Matrix Orbital modules and OpenLCD, but not by
+    /// the older SerLCD firmware.
+    fn set_contrast(&self, level: u8) -> Result<(), Self::Error> {
+        match self.protocol {
+            SerialProtocol::MatrixOrbital => {
+                self.port
+                    .write(&[matrix_orbital::PREFIX, matrix_orbital::CONTRAST, level])
+            }
+            SerialProtocol::SerLcd => self.port.write(&[SERLCD_SETTING, SERLCD_CONTRAST, level]),
+        }
+    }
+}
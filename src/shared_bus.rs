@@ -0,0 +1,234 @@
+use core::cell::Cell;
+
+use crate::dual_controller::{Controller, SelectController};
+use crate::hal::{DataPins4Lines, DataPins8Lines, Direction, DisplayHardwareLayer, ErrorType, Init,
+                 ParallelConnection, Pins, ReadMode, Receive, Release, Send, Wait, WriteMode};
+
+/// This trait is used to lend the data pins of a [`SharedBus`] to its connections.
+///
+/// [`SharedBus`]: struct.SharedBus.html
+pub trait ShareData<'a> {
+    /// The borrowed data pins.
+    type Shared;
+
+    /// Borrows the data pins.
+    fn share(&'a self) -> Self::Shared;
+}
+
+impl<'a, P4, P5, P6, P7> ShareData<'a> for DataPins4Lines<P4, P5, P6, P7>
+where
+    P4: DisplayHardwareLayer + 'a,
+    P5: DisplayHardwareLayer + 'a,
+    P6: DisplayHardwareLayer + 'a,
+    P7: DisplayHardwareLayer + 'a,
+{
+    type Shared = DataPins4Lines<&'a P4, &'a P5, &'a P6, &'a P7>;
+
+    fn share(&'a self) -> Self::Shared {
+        DataPins4Lines {
+            data4: &self.data4,
+            data5: &self.data5,
+            data6: &self.data6,
+            data7: &self.data7,
+        }
+    }
+}
+
+impl<'a, P0, P1, P2, P3, P4, P5, P6, P7> ShareData<'a>
+    for DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: DisplayHardwareLayer + 'a,
+    P1: DisplayHardwareLayer + 'a,
+    P2: DisplayHardwareLayer + 'a,
+    P3: DisplayHardwareLayer + 'a,
+    P4: DisplayHardwareLayer + 'a,
+    P5: DisplayHardwareLayer + 'a,
+    P6: DisplayHardwareLayer + 'a,
+    P7: DisplayHardwareLayer + 'a,
+{
+    type Shared = DataPins8Lines<&'a P0, &'a P1, &'a P2, &'a P3, &'a P4, &'a P5, &'a P6, &'a P7>;
+
+    fn share(&'a self) -> Self::Shared {
+        DataPins8Lines {
+            data0: &self.data0,
+            data1: &self.data1,
+            data2: &self.data2,
+            data3: &self.data3,
+            data4: &self.data4,
+            data5: &self.data5,
+            data6: &self.data6,
+            data7: &self.data7,
+        }
+    }
+}
+
+/// The register select (RS), read/write (R/W) and data pins shared by several displays, which
+/// only have their own enable signal (E).
+///
+/// Every display gets its own connection, which borrows the shared pins. The bus can not be
+/// shared between threads, so a transfer is always finished before the next one starts, even
+/// if the displays are driven by an async executor. To drive the displays from several threads,
+/// they have to be put behind a common lock.
+pub struct SharedBus<RS, R, D> {
+    register_select: RS,
+    read: R,
+    data: D,
+    initialized: Cell<bool>,
+}
+
+impl<RS, R, D> SharedBus<RS, R, D> {
+    /// Creates a bus of the given shared pins. Displays wired write-only use [`Grounded`] as
+    /// `read` pin.
+    ///
+    /// [`Grounded`]: struct.Grounded.html
+    pub fn new(register_select: RS, read: R, data: D) -> Self {
+        SharedBus {
+            register_select,
+            read,
+            data,
+            initialized: Cell::new(false),
+        }
+    }
+
+    /// Returns a connection to the display with the given enable pin. The displays may share a
+    /// delay by reference.
+    pub fn connection<'a, E, T>(
+        &'a self,
+        enable: E,
+        delay: T,
+    ) -> SharedBusConnection<'a, RS, R, E, D, T>
+    where
+        D: ShareData<'a>,
+    {
+        let pins = Pins {
+            register_select: &self.register_select,
+            read: &self.read,
+            enable,
+            data: self.data.share(),
+        };
+
+        SharedBusConnection {
+            initialized: &self.initialized,
+            connection: pins.into_connection(delay),
+        }
+    }
+
+    /// Returns the shared pins.
+    pub fn into_inner(self) -> (RS, R, D) {
+        (self.register_select, self.read, self.data)
+    }
+}
+
+type BusConnection<'a, RS, R, E, D, T> =
+    ParallelConnection<&'a RS, &'a R, E, <D as ShareData<'a>>::Shared, T>;
+
+/// A connection to one of the displays on a [`SharedBus`].
+///
+/// [`SharedBus`]: struct.SharedBus.html
+pub struct SharedBusConnection<'a, RS, R, E, D: ShareData<'a>, T> {
+    initialized: &'a Cell<bool>,
+    connection: BusConnection<'a, RS, R, E, D, T>,
+}
+
+impl<'a, RS, R, E, D, T> ErrorType for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    D: ShareData<'a>,
+{
+    type Error = RS::Error;
+}
+
+impl<'a, RS, R, E, D, T> Init for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    D: ShareData<'a>,
+    BusConnection<'a, RS, R, E, D, T>: Init<Error = RS::Error>,
+{
+    /// Initializes the shared pins by the first initialized connection and only the enable pin
+    /// by all others.
+    fn init(&self) -> Result<(), Self::Error> {
+        if self.initialized.get() {
+            let enable = self.connection.enable();
+            enable.init()?;
+            return enable.set_direction(Direction::Out);
+        }
+
+        self.connection.init()?;
+        self.initialized.set(true);
+
+        Ok(())
+    }
+}
+
+/// Releasing cleans up and hands back only the enable pin, the shared pins are still used by the
+/// other displays. They are returned by [`SharedBus::into_inner()`].
+///
+/// [`SharedBus::into_inner()`]: struct.SharedBus.html#method.into_inner
+impl<'a, RS, R, E, D, T> Release for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    E: DisplayHardwareLayer<Error = RS::Error>,
+    D: ShareData<'a>,
+{
+    type Pins = E;
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        self.connection.enable().cleanup()
+    }
+
+    fn into_pins(self) -> Self::Pins {
+        self.connection.into_enable()
+    }
+}
+
+impl<'a, RS, R, E, D, T> Send for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    D: ShareData<'a>,
+    BusConnection<'a, RS, R, E, D, T>: Send<Error = RS::Error>,
+{
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.connection.send(mode)
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        self.connection.send_upper_nibble(cmd)
+    }
+}
+
+impl<'a, RS, R, E, D, T> Receive for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    D: ShareData<'a>,
+    BusConnection<'a, RS, R, E, D, T>: Receive<Error = RS::Error>,
+{
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+        self.connection.receive(mode)
+    }
+}
+
+impl<'a, RS, R, E, D, T> Wait for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    RS: DisplayHardwareLayer,
+    D: ShareData<'a>,
+    BusConnection<'a, RS, R, E, D, T>: Wait<Error = RS::Error>,
+{
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.connection.wait(mode)
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.connection.wait_us(us)
+    }
+}
+
+impl<'a, RS, R, E, D, T> SelectController for SharedBusConnection<'a, RS, R, E, D, T>
+where
+    D: ShareData<'a>,
+    BusConnection<'a, RS, R, E, D, T>: SelectController,
+{
+    fn select_controller(&self, controller: Controller) {
+        self.connection.select_controller(controller);
+    }
+}
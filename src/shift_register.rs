@@ -0,0 +1,358 @@
+use core::cell::Cell;
+
+use crate::backlight::Backlight;
+use crate::error::{check_pins, PinError};
+use crate::hal::{Delay, Direction, DisplayHardwareLayer, ErrorType, Init, Level, Nibble, Send,
+                 Timing, Wait, WriteMode};
+
+/// Enumeration of possible data line setups of a shift register wired display.
+///
+/// Bits `0` to `7` refer to the outputs Q0-Q7 of the first shift register and `8` to `15` to the
+/// outputs of a second, daisy-chained one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShiftRegisterDataBits {
+    /// Bits connected to D4-D7.
+    FourBit([u8; 4]),
+    /// Bits connected to D0-D7.
+    EightBit([u8; 8]),
+}
+
+impl ShiftRegisterDataBits {
+    fn as_slice(&self) -> &[u8] {
+        match *self {
+            ShiftRegisterDataBits::FourBit(ref bits) => bits,
+            ShiftRegisterDataBits::EightBit(ref bits) => bits,
+        }
+    }
+
+    fn word_bits(&self, value: u8) -> u16 {
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| value & (1 << i) != 0)
+            .fold(0, |word, (_, bit)| word | mask(*bit))
+    }
+}
+
+/// This struct describes which output of a (daisy-chained) 74HC595 shift register is wired to
+/// which signal of the LCD.
+///
+/// Presets for common layouts are available as associated constants.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShiftRegisterLayout {
+    pub register_select: u8,
+    pub enable: u8,
+    /// `None` if the backlight is not controlled by the shift register.
+    pub backlight: Option<u8>,
+    pub data: ShiftRegisterDataBits,
+}
+
+impl ShiftRegisterLayout {
+    /// The layout of the Adafruit I2C/SPI character LCD backpack in SPI mode.
+    ///
+    ///  - **Q1:** RS
+    ///  - **Q2:** E
+    ///  - **Q3-Q6:** D4-D7
+    ///  - **Q7:** backlight
+    pub const ADAFRUIT_BACKPACK: ShiftRegisterLayout = ShiftRegisterLayout {
+        register_select: 1,
+        enable: 2,
+        backlight: Some(7),
+        data: ShiftRegisterDataBits::FourBit([3, 4, 5, 6]),
+    };
+
+    /// An 8-bit layout using two daisy-chained shift registers.
+    ///
+    ///  - **first Q0-Q7:** D0-D7
+    ///  - **second Q0:** RS
+    ///  - **second Q1:** E
+    ///  - **second Q2:** backlight
+    pub const TWO_CHIPS_8BIT: ShiftRegisterLayout = ShiftRegisterLayout {
+        register_select: 8,
+        enable: 9,
+        backlight: Some(10),
+        data: ShiftRegisterDataBits::EightBit([0, 1, 2, 3, 4, 5, 6, 7]),
+    };
+
+    /// Checks that all signals are wired to distinct outputs of at most two shift registers.
+    fn check(&self) -> Result<(), PinError> {
+        let signals = [Some(self.register_select), Some(self.enable), self.backlight];
+
+        let mut bits = [0; 11];
+        let mut count = 0;
+
+        for &bit in signals.iter().flatten().chain(self.data.as_slice()) {
+            bits[count] = bit;
+            count += 1;
+        }
+
+        check_pins(&bits[..count], 16)
+    }
+
+    fn width(&self) -> u8 {
+        let mut highest = self.register_select.max(self.enable);
+
+        if let Some(backlight) = self.backlight {
+            highest = highest.max(backlight);
+        }
+
+        highest = self.data.as_slice().iter().fold(highest, |a, b| a.max(*b));
+
+        if highest < 8 {
+            8
+        } else {
+            16
+        }
+    }
+}
+
+fn mask(bit: u8) -> u16 {
+    1 << bit
+}
+
+/// This struct is used for easily setting up [`ShiftRegisterConnection`]s.
+///
+/// [`ShiftRegisterConnection`]: struct.ShiftRegisterConnection.html
+pub struct ShiftRegisterPins<DS, SH, ST> {
+    /// Serial data input (DS).
+    pub data: DS,
+    /// Shift register clock (SH_CP).
+    pub clock: SH,
+    /// Storage register clock (ST_CP), also known as latch.
+    pub latch: ST,
+}
+
+impl<DS, SH, ST> ShiftRegisterPins<DS, SH, ST> {
+    /// Converts the pin setup into a [`ShiftRegisterConnection`] using the given layout and
+    /// delay.
+    ///
+    /// Fails if a signal is wired to a bit beyond `15` or two signals share a bit.
+    ///
+    /// [`ShiftRegisterConnection`]: struct.ShiftRegisterConnection.html
+    pub fn into_connection<T>(
+        self,
+        layout: ShiftRegisterLayout,
+        delay: T,
+    ) -> Result<ShiftRegisterConnection<DS, SH, ST, T>, PinError> {
+        layout.check()?;
+
+        Ok(ShiftRegisterConnection {
+            data: self.data,
+            clock: self.clock,
+            latch: self.latch,
+            layout,
+            backlight: Cell::new(true),
+            delay,
+            timing: Timing::default(),
+        })
+    }
+}
+
+/// A write-only connection to a display which is wired to one or two daisy-chained 74HC595 shift
+/// registers, using only three I/O pins.
+///
+/// The display is driven in 4-bit or 8-bit mode depending on the given layout, so it has to be
+/// initialized with the matching `DataLength`.
+///
+/// All pins have to report the same error type, which is used as error type of the connection.
+pub struct ShiftRegisterConnection<DS, SH, ST, T> {
+    data: DS,
+    clock: SH,
+    latch: ST,
+    layout: ShiftRegisterLayout,
+    backlight: Cell<bool>,
+    delay: T,
+    timing: Timing,
+}
+
+impl<DS, SH, ST, T> ShiftRegisterConnection<DS, SH, ST, T> {
+    /// Sets the timing of the bus, which defaults to the timing for the lower supply voltage
+    /// ([`Timing::VCC_3V`]).
+    ///
+    /// [`Timing::VCC_3V`]: struct.Timing.html#associatedconstant.VCC_3V
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn delay(&self) -> &T {
+        &self.delay
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn timing(&self) -> &Timing {
+        &self.timing
+    }
+}
+
+impl<DS, SH, ST, T> ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    SH: DisplayHardwareLayer<Error = DS::Error>,
+    ST: DisplayHardwareLayer<Error = DS::Error>,
+{
+    /// Switches the backlight `On` (`true`) or `Off` (`false`).
+    ///
+    /// Does nothing if the backlight is not wired to the shift register.
+    pub fn set_backlight(&self, enabled: bool) -> Result<(), DS::Error> {
+        if self.layout.backlight.is_some() {
+            self.backlight.set(enabled);
+
+            let word = self.control_bits(false);
+            self.shift_out(word)?;
+        }
+
+        Ok(())
+    }
+
+    fn control_bits(&self, register_select: bool) -> u16 {
+        let mut word = 0;
+
+        if register_select {
+            word |= mask(self.layout.register_select);
+        }
+
+        if let Some(backlight) = self.layout.backlight {
+            if self.backlight.get() {
+                word |= mask(backlight);
+            }
+        }
+
+        word
+    }
+
+    fn shift_out(&self, word: u16) -> Result<(), DS::Error> {
+        for i in (0..self.layout.width()).rev() {
+            if word & mask(i) != 0 {
+                self.data.set_level(Level::High)?;
+            } else {
+                self.data.set_level(Level::Low)?;
+            }
+
+            self.clock.set_level(Level::High)?;
+            self.clock.set_level(Level::Low)?;
+        }
+
+        self.latch.set_level(Level::High)?;
+        self.latch.set_level(Level::Low)
+    }
+}
+
+impl<DS, SH, ST, T> ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    SH: DisplayHardwareLayer<Error = DS::Error>,
+    ST: DisplayHardwareLayer<Error = DS::Error>,
+    T: Delay,
+{
+    fn write_value(&self, control: u16, value: u8) -> Result<(), DS::Error> {
+        let word = control | self.layout.data.word_bits(value);
+        let enable = mask(self.layout.enable);
+
+        self.shift_out(word)?;
+        self.delay.delay_ns(self.timing.address_setup_time);
+
+        self.shift_out(word | enable)?;
+        self.delay.delay_ns(self.timing.enable_pulse_width);
+
+        self.shift_out(word)?;
+        self.delay.delay_ns(self.timing.data_hold_time);
+
+        Ok(())
+    }
+}
+
+impl<DS, SH, ST, T> ErrorType for ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+{
+    type Error = DS::Error;
+}
+
+impl<DS, SH, ST, T> Backlight for ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    SH: DisplayHardwareLayer<Error = DS::Error>,
+    ST: DisplayHardwareLayer<Error = DS::Error>,
+{
+    fn set_backlight(&self, enabled: bool) -> Result<(), Self::Error> {
+        ShiftRegisterConnection::set_backlight(self, enabled)
+    }
+}
+
+impl<DS, SH, ST, T> Init for ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    SH: DisplayHardwareLayer<Error = DS::Error>,
+    ST: DisplayHardwareLayer<Error = DS::Error>,
+{
+    fn init(&self) -> Result<(), Self::Error> {
+        self.data.init()?;
+        self.data.set_direction(Direction::Out)?;
+        self.data.set_level(Level::Low)?;
+
+        self.clock.init()?;
+        self.clock.set_direction(Direction::Out)?;
+        self.clock.set_level(Level::Low)?;
+
+        self.latch.init()?;
+        self.latch.set_direction(Direction::Out)?;
+        self.latch.set_level(Level::Low)?;
+
+        let word = self.control_bits(false);
+        self.shift_out(word)
+    }
+}
+
+impl<DS, SH, ST, T> Send for ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    SH: DisplayHardwareLayer<Error = DS::Error>,
+    ST: DisplayHardwareLayer<Error = DS::Error>,
+    T: Delay,
+{
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        let (register_select, value) = match mode {
+            WriteMode::Command(value) => (false, value),
+            WriteMode::Data(value) => (true, value),
+        };
+
+        let control = self.control_bits(register_select);
+
+        match self.layout.data {
+            ShiftRegisterDataBits::FourBit(_) => {
+                self.write_value(control, Nibble::Upper(value).into())?;
+                self.write_value(control, Nibble::Lower(value).into())
+            }
+            ShiftRegisterDataBits::EightBit(_) => self.write_value(control, value),
+        }
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        let control = self.control_bits(false);
+
+        match self.layout.data {
+            ShiftRegisterDataBits::FourBit(_) => {
+                self.write_value(control, Nibble::Upper(cmd).into())
+            }
+            ShiftRegisterDataBits::EightBit(_) => self.write_value(control, cmd),
+        }
+    }
+}
+
+impl<DS, SH, ST, T> Wait for ShiftRegisterConnection<DS, SH, ST, T>
+where
+    DS: DisplayHardwareLayer,
+    T: Delay,
+{
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.delay.delay_us(self.timing.execution_time(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+}
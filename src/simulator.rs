@@ -0,0 +1,476 @@
+//! A simulated HD44780 bus, which allows to exercise the whole pin-level stack of a
+//! [`ParallelConnection`] without any hardware.
+//!
+//! [`ParallelConnection`]: struct.ParallelConnection.html
+
+use core::cell::{Cell, RefCell};
+use core::convert::Infallible;
+
+use crate::function_set::{DataLength, LineNumber};
+use crate::hal::{DataPins4Lines, DataPins8Lines, Direction, DisplayHardwareLayer, Level, Pins};
+
+const DDRAM_SIZE: usize = 0x80;
+const CGRAM_SIZE: usize = 0x40;
+
+const ONE_LINE_LAST_ADDRESS: u8 = 0x4f;
+const FIRST_LINE_LAST_ADDRESS: u8 = 0x27;
+const SECOND_LINE_FIRST_ADDRESS: u8 = 0x40;
+const SECOND_LINE_LAST_ADDRESS: u8 = 0x67;
+
+const DISPLAY_WIDTH: u8 = 40;
+
+/// Enumeration of the bus lines of an HD44780 compliant controller.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BusLine {
+    RegisterSelect,
+    ReadWrite,
+    Enable,
+    /// The data lines D0 to D7.
+    Data(u8),
+}
+
+impl BusLine {
+    /// Returns the bit of the line in the bus levels. Data lines beyond D7 do not exist, so
+    /// they are never driven.
+    pub(crate) fn mask(self) -> u16 {
+        match self {
+            BusLine::Data(n) => {
+                debug_assert!(n < 8, "there is no data line D{}", n);
+
+                if n < 8 {
+                    1 << n
+                } else {
+                    0
+                }
+            }
+            BusLine::RegisterSelect => 1 << 8,
+            BusLine::ReadWrite => 1 << 9,
+            BusLine::Enable => 1 << 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Ram {
+    DdRam,
+    CgRam,
+}
+
+/// The progress of a transfer on a 4-bit interface.
+#[derive(Clone, Copy)]
+enum Phase {
+    Idle,
+    /// The upper nibble has been written.
+    Written(u8),
+    /// The upper nibble of the given byte has been read.
+    Read(u8),
+}
+
+/// A virtual HD44780 compliant controller with its bus lines, which latches written nibbles or
+/// bytes on the falling edge of the enable signal and drives the data lines on reads.
+///
+/// The controller always reports itself as ready, the timing of the bus is not checked.
+pub struct BusSimulator {
+    levels: Cell<u16>,
+    output: Cell<u8>,
+    phase: Cell<Phase>,
+    data_length: Cell<DataLength>,
+    line_number: Cell<LineNumber>,
+    ram: Cell<Ram>,
+    address: Cell<u8>,
+    increment: Cell<bool>,
+    entry_shift: Cell<bool>,
+    display_control: Cell<u8>,
+    display_shift: Cell<u8>,
+    ddram: RefCell<[u8; DDRAM_SIZE]>,
+    cgram: RefCell<[u8; CGRAM_SIZE]>,
+}
+
+impl Default for BusSimulator {
+    fn default() -> Self {
+        BusSimulator::new()
+    }
+}
+
+impl BusSimulator {
+    /// Creates a controller in its power on state, which uses an 8-bit interface.
+    pub fn new() -> Self {
+        BusSimulator {
+            levels: Cell::new(0),
+            output: Cell::new(0),
+            phase: Cell::new(Phase::Idle),
+            data_length: Cell::new(DataLength::EightBit),
+            line_number: Cell::new(LineNumber::One),
+            ram: Cell::new(Ram::DdRam),
+            address: Cell::new(0),
+            increment: Cell::new(true),
+            entry_shift: Cell::new(false),
+            display_control: Cell::new(0),
+            display_shift: Cell::new(0),
+            ddram: RefCell::new([b' '; DDRAM_SIZE]),
+            cgram: RefCell::new([0; CGRAM_SIZE]),
+        }
+    }
+
+    /// Returns a pin connected to the given bus line.
+    pub fn pin(&self, line: BusLine) -> SimulatedPin<'_> {
+        SimulatedPin {
+            bus: self,
+            line,
+            direction: Cell::new(Direction::In),
+        }
+    }
+
+    /// Returns the pins of a display wired with four data lines (D4-D7).
+    pub fn pins_4lines(&self) -> SimulatedPins4Lines<'_> {
+        Pins {
+            register_select: self.pin(BusLine::RegisterSelect),
+            read: self.pin(BusLine::ReadWrite),
+            enable: self.pin(BusLine::Enable),
+            data: DataPins4Lines {
+                data4: self.pin(BusLine::Data(4)),
+                data5: self.pin(BusLine::Data(5)),
+                data6: self.pin(BusLine::Data(6)),
+                data7: self.pin(BusLine::Data(7)),
+            },
+        }
+    }
+
+    /// Returns the pins of a display wired with eight data lines (D0-D7).
+    pub fn pins_8lines(&self) -> SimulatedPins8Lines<'_> {
+        Pins {
+            register_select: self.pin(BusLine::RegisterSelect),
+            read: self.pin(BusLine::ReadWrite),
+            enable: self.pin(BusLine::Enable),
+            data: DataPins8Lines {
+                data0: self.pin(BusLine::Data(0)),
+                data1: self.pin(BusLine::Data(1)),
+                data2: self.pin(BusLine::Data(2)),
+                data3: self.pin(BusLine::Data(3)),
+                data4: self.pin(BusLine::Data(4)),
+                data5: self.pin(BusLine::Data(5)),
+                data6: self.pin(BusLine::Data(6)),
+                data7: self.pin(BusLine::Data(7)),
+            },
+        }
+    }
+
+    /// Returns the interface data length the controller is set to.
+    pub fn data_length(&self) -> DataLength {
+        self.data_length.get()
+    }
+
+    /// Returns the number of display lines the controller is set to.
+    pub fn line_number(&self) -> LineNumber {
+        self.line_number.get()
+    }
+
+    /// Returns the address counter.
+    pub fn address_counter(&self) -> u8 {
+        self.address.get()
+    }
+
+    /// Returns the display, cursor and blinking bits of the last display control instruction.
+    pub fn display_control(&self) -> u8 {
+        self.display_control.get()
+    }
+
+    /// Returns the number of characters the display has been shifted to the left.
+    pub fn display_shift(&self) -> u8 {
+        self.display_shift.get()
+    }
+
+    /// Returns the character stored at the given DDRAM address.
+    pub fn ddram(&self, address: u8) -> u8 {
+        self.ddram.borrow()[usize::from(address) % DDRAM_SIZE]
+    }
+
+    /// Returns the pattern row stored at the given CGRAM address.
+    pub fn cgram(&self, address: u8) -> u8 {
+        self.cgram.borrow()[usize::from(address) % CGRAM_SIZE]
+    }
+
+    /// Returns `true` while the controller drives the data lines, that is while R/W and E are
+    /// high.
+    pub fn drives_data(&self) -> bool {
+        self.is_set(BusLine::ReadWrite) && self.is_set(BusLine::Enable)
+    }
+
+    fn is_set(&self, line: BusLine) -> bool {
+        self.levels.get() & line.mask() != 0
+    }
+
+    fn data_lines(&self) -> u8 {
+        self.levels.get() as u8
+    }
+
+    fn set_level(&self, line: BusLine, level: Level) {
+        let enable_was_set = self.is_set(BusLine::Enable);
+
+        let levels = match level {
+            Level::High => self.levels.get() | line.mask(),
+            Level::Low => self.levels.get() & !line.mask(),
+        };
+        self.levels.set(levels);
+
+        if line == BusLine::Enable {
+            match (enable_was_set, level) {
+                (false, Level::High) => self.enable_rising(),
+                (true, Level::Low) => self.enable_falling(),
+                _ => {}
+            }
+        }
+    }
+
+    fn get_value(&self, line: BusLine) -> u8 {
+        let levels = if self.drives_data() {
+            u16::from(self.output.get())
+        } else {
+            self.levels.get()
+        };
+
+        (levels & line.mask() != 0) as u8
+    }
+
+    fn enable_rising(&self) {
+        if !self.is_set(BusLine::ReadWrite) {
+            return;
+        }
+
+        let output = match (self.data_length.get(), self.phase.get()) {
+            (DataLength::FourBit, Phase::Read(byte)) => byte << 4,
+            _ => self.read_register(),
+        };
+        self.output.set(output);
+    }
+
+    fn enable_falling(&self) {
+        let reading = self.is_set(BusLine::ReadWrite);
+
+        let completed = match (self.data_length.get(), self.phase.get()) {
+            (DataLength::EightBit, _) => Some(self.data_lines()),
+            (DataLength::FourBit, Phase::Idle) => {
+                let nibble = self.data_lines() >> 4;
+                if reading {
+                    self.phase.set(Phase::Read(self.output.get()));
+                } else {
+                    self.phase.set(Phase::Written(nibble));
+                }
+                None
+            }
+            (DataLength::FourBit, Phase::Written(upper)) => {
+                self.phase.set(Phase::Idle);
+                Some(upper << 4 | self.data_lines() >> 4)
+            }
+            (DataLength::FourBit, Phase::Read(_)) => {
+                self.phase.set(Phase::Idle);
+                Some(0)
+            }
+        };
+
+        let value = match completed {
+            Some(value) => value,
+            None => return,
+        };
+
+        let register_select = self.is_set(BusLine::RegisterSelect);
+        match (reading, register_select) {
+            (false, false) => self.execute(value),
+            (false, true) => self.write_data(value),
+            (true, true) => self.move_address(self.increment.get()),
+            (true, false) => {}
+        }
+    }
+
+    fn read_register(&self) -> u8 {
+        if self.is_set(BusLine::RegisterSelect) {
+            self.read_ram()
+        } else {
+            // the busy flag is never set
+            self.address.get() & 0x7f
+        }
+    }
+
+    fn read_ram(&self) -> u8 {
+        let address = usize::from(self.address.get());
+
+        match self.ram.get() {
+            Ram::DdRam => self.ddram.borrow()[address % DDRAM_SIZE],
+            Ram::CgRam => self.cgram.borrow()[address % CGRAM_SIZE],
+        }
+    }
+
+    fn write_data(&self, value: u8) {
+        let address = usize::from(self.address.get());
+
+        match self.ram.get() {
+            Ram::DdRam => self.ddram.borrow_mut()[address % DDRAM_SIZE] = value,
+            Ram::CgRam => self.cgram.borrow_mut()[address % CGRAM_SIZE] = value,
+        }
+
+        self.move_address(self.increment.get());
+
+        // the display is shifted in the opposite direction of the cursor
+        if self.entry_shift.get() && self.ram.get() == Ram::DdRam {
+            self.shift_display(!self.increment.get());
+        }
+    }
+
+    fn execute(&self, cmd: u8) {
+        match cmd.leading_zeros() {
+            0 => {
+                self.ram.set(Ram::DdRam);
+                self.address.set(cmd & 0x7f);
+            }
+            1 => {
+                self.ram.set(Ram::CgRam);
+                self.address.set(cmd & 0x3f);
+            }
+            2 => {
+                let data_length = if cmd & 0b0001_0000 != 0 {
+                    DataLength::EightBit
+                } else {
+                    DataLength::FourBit
+                };
+                let line_number = if cmd & 0b0000_1000 != 0 {
+                    LineNumber::Two
+                } else {
+                    LineNumber::One
+                };
+
+                self.data_length.set(data_length);
+                self.line_number.set(line_number);
+            }
+            3 => {
+                let right = cmd & 0b0000_0100 != 0;
+
+                if cmd & 0b0000_1000 != 0 {
+                    self.shift_display(right);
+                } else {
+                    self.move_address(right);
+                }
+            }
+            4 => self.display_control.set(cmd & 0b0000_0111),
+            5 => {
+                self.increment.set(cmd & 0b0000_0010 != 0);
+                self.entry_shift.set(cmd & 0b0000_0001 != 0);
+            }
+            6 => self.return_home(),
+            7 => {
+                *self.ddram.borrow_mut() = [b' '; DDRAM_SIZE];
+                self.increment.set(true);
+                self.return_home();
+            }
+            _ => {}
+        }
+    }
+
+    fn return_home(&self) {
+        self.ram.set(Ram::DdRam);
+        self.address.set(0);
+        self.display_shift.set(0);
+    }
+
+    fn move_address(&self, increment: bool) {
+        let address = self.address.get();
+
+        let address = match (self.ram.get(), self.line_number.get(), increment) {
+            (Ram::CgRam, _, true) => address.wrapping_add(1) & 0x3f,
+            (Ram::CgRam, _, false) => address.wrapping_sub(1) & 0x3f,
+            (Ram::DdRam, LineNumber::One, true) if address >= ONE_LINE_LAST_ADDRESS => 0,
+            (Ram::DdRam, LineNumber::One, false) if address == 0 => ONE_LINE_LAST_ADDRESS,
+            (Ram::DdRam, LineNumber::Two, true) if address == FIRST_LINE_LAST_ADDRESS => {
+                SECOND_LINE_FIRST_ADDRESS
+            }
+            (Ram::DdRam, LineNumber::Two, true) if address >= SECOND_LINE_LAST_ADDRESS => 0,
+            (Ram::DdRam, LineNumber::Two, false) if address == SECOND_LINE_FIRST_ADDRESS => {
+                FIRST_LINE_LAST_ADDRESS
+            }
+            (Ram::DdRam, LineNumber::Two, false) if address == 0 => SECOND_LINE_LAST_ADDRESS,
+            (Ram::DdRam, _, true) => address + 1,
+            (Ram::DdRam, _, false) => address - 1,
+        };
+
+        self.address.set(address);
+    }
+
+    fn shift_display(&self, right: bool) {
+        let shift = self.display_shift.get();
+
+        let shift = if right {
+            (shift + DISPLAY_WIDTH - 1) % DISPLAY_WIDTH
+        } else {
+            (shift + 1) % DISPLAY_WIDTH
+        };
+
+        self.display_shift.set(shift);
+    }
+}
+
+/// A pin connected to a line of a [`BusSimulator`].
+///
+/// [`BusSimulator`]: struct.BusSimulator.html
+pub struct SimulatedPin<'a> {
+    bus: &'a BusSimulator,
+    line: BusLine,
+    direction: Cell<Direction>,
+}
+
+impl<'a> SimulatedPin<'a> {
+    /// Returns the bus line the pin is connected to.
+    pub fn line(&self) -> BusLine {
+        self.line
+    }
+}
+
+impl<'a> DisplayHardwareLayer for SimulatedPin<'a> {
+    type Error = Infallible;
+
+    fn set_direction(&self, direction: Direction) -> Result<(), Infallible> {
+        self.direction.set(direction);
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), Infallible> {
+        self.bus.set_level(self.line, level);
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, Infallible> {
+        let value = match self.direction.get() {
+            Direction::In => self.bus.get_value(self.line),
+            Direction::Out => (self.bus.levels.get() & self.line.mask() != 0) as u8,
+        };
+
+        Ok(value)
+    }
+}
+
+/// The pins of a display wired to a [`BusSimulator`] with four data lines.
+///
+/// [`BusSimulator`]: struct.BusSimulator.html
+pub type SimulatedPins4Lines<'a> = Pins<
+    SimulatedPin<'a>,
+    SimulatedPin<'a>,
+    SimulatedPin<'a>,
+    DataPins4Lines<SimulatedPin<'a>, SimulatedPin<'a>, SimulatedPin<'a>, SimulatedPin<'a>>,
+>;
+
+/// The pins of a display wired to a [`BusSimulator`] with eight data lines.
+///
+/// [`BusSimulator`]: struct.BusSimulator.html
+pub type SimulatedPins8Lines<'a> = Pins<
+    SimulatedPin<'a>,
+    SimulatedPin<'a>,
+    SimulatedPin<'a>,
+    DataPins8Lines<
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+        SimulatedPin<'a>,
+    >,
+>;
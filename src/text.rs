@@ -0,0 +1,116 @@
+const SECOND_LINE_ADDRESS: u8 = 0x40;
+const MAX_LINES: u8 = 4;
+
+/// Enumeration of ways to continue text written by `write!`, which reaches the end of a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineOverflow {
+    /// Continues at the start of the next line, the last line is followed by the first one.
+    Wrap,
+    /// Drops all characters until the next newline.
+    Truncate,
+}
+
+/// Enumeration of ways to write characters, which are missing in the character set of the
+/// display.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unencodable {
+    /// Writes the given character code instead.
+    Substitute(u8),
+    /// Skips the character.
+    Skip,
+    /// Fails with `fmt::Error`.
+    Fail,
+}
+
+/// Returns the code of the given character in the character set of the display. The character
+/// set is the ROM code A00, which is the most common one.
+pub(crate) fn encode(c: char) -> Option<u8> {
+    let code = match c {
+        // the custom characters
+        '\u{0}'..='\u{7}' => c as u8,
+        // ASCII, except for backslash and tilde, whose codes show a yen sign and an arrow
+        ' '..='[' | ']'..='}' => c as u8,
+        '¥' => 0x5c,
+        '→' => 0x7e,
+        '←' => 0x7f,
+        '°' => 0xdf,
+        'α' => 0xe0,
+        'ä' => 0xe1,
+        'ß' | 'β' => 0xe2,
+        'ε' => 0xe3,
+        'µ' | 'μ' => 0xe4,
+        'σ' => 0xe5,
+        'ρ' => 0xe6,
+        '√' => 0xe8,
+        '¢' => 0xec,
+        'ñ' => 0xee,
+        'ö' => 0xef,
+        'θ' => 0xf2,
+        '∞' => 0xf3,
+        'Ω' => 0xf4,
+        'ü' => 0xf5,
+        'Σ' => 0xf6,
+        'π' => 0xf7,
+        '÷' => 0xfd,
+        '█' => 0xff,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// The geometry of a display and the policies for writing text on it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TextLayout {
+    pub(crate) columns: u8,
+    pub(crate) lines: u8,
+    pub(crate) overflow: LineOverflow,
+    pub(crate) unencodable: Unencodable,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        TextLayout {
+            columns: 16,
+            lines: 2,
+            overflow: LineOverflow::Truncate,
+            unencodable: Unencodable::Substitute(b'?'),
+        }
+    }
+}
+
+impl TextLayout {
+    /// Returns the address of the given line relative to home. Displays with four lines continue
+    /// the first and second line at the third and fourth.
+    pub(crate) fn line_address(&self, line: u8) -> u8 {
+        match line % MAX_LINES {
+            0 => 0,
+            1 => SECOND_LINE_ADDRESS,
+            2 => self.columns,
+            _ => SECOND_LINE_ADDRESS + self.columns,
+        }
+    }
+
+    /// Returns the number of lines, which can be addressed.
+    pub(crate) fn visible_lines(&self) -> u8 {
+        self.lines.min(MAX_LINES)
+    }
+
+    pub(crate) fn next_line(&self, line: u8) -> u8 {
+        (line + 1) % self.visible_lines().max(1)
+    }
+
+    /// Returns the line and column of the given address relative to home, if it is visible.
+    pub(crate) fn position(&self, address: u8) -> Option<(u8, u8)> {
+        (0..self.visible_lines()).find_map(|line| {
+            let start = self.line_address(line);
+            let column = address.checked_sub(start)?;
+
+            if column < self.columns {
+                Some((line, column))
+            } else {
+                None
+            }
+        })
+    }
+}
@@ -0,0 +1,615 @@
+//! Recording of pin transitions against virtual time and a checker, which verifies them against
+//! the timing constraints of the HD44780 datasheet.
+
+use core::cell::{Cell, RefCell};
+
+use std::time::Instant;
+use std::vec::Vec;
+
+use crate::hal::{DataPins4Lines, DataPins8Lines, Delay, Direction, DisplayHardwareLayer, Init,
+                 Level, Pins, ReadMode, Receive, Send, Wait, WriteMode};
+use crate::simulator::BusLine;
+
+const CLEAR_DISPLAY: u8 = 0b0000_0001;
+const RETURN_HOME: u8 = 0b0000_0010;
+const FUNCTION_SET: u8 = 0b0010_0000;
+const INTERFACE_DATA_LENGTH_8BIT: u8 = 0b0001_0000;
+
+/// A `Delay`, which returns immediately and advances its own virtual clock instead. The clock
+/// starts at `0`.
+#[derive(Debug, Default)]
+pub struct VirtualDelay {
+    now: Cell<u64>,
+}
+
+impl VirtualDelay {
+    /// Creates a delay, whose virtual clock starts at `0`.
+    pub fn new() -> Self {
+        VirtualDelay::default()
+    }
+
+    /// Returns the current virtual time (ns).
+    pub fn now(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn advance(&self, ns: u64) {
+        self.now.set(self.now.get().saturating_add(ns));
+    }
+}
+
+impl Delay for VirtualDelay {
+    fn delay_ns(&self, ns: u32) {
+        self.advance(u64::from(ns));
+    }
+
+    fn delay_us(&self, us: u32) {
+        self.advance(u64::from(us) * 1000);
+    }
+
+    fn delay_ms(&self, ms: u32) {
+        self.advance(u64::from(ms) * 1_000_000);
+    }
+}
+
+/// A level change of a bus line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// The time (ns) of the change.
+    pub time: u64,
+    pub line: BusLine,
+    pub level: Level,
+}
+
+/// Enumeration of connection operations, which cause pin transitions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Init,
+    Send(WriteMode),
+    SendUpperNibble(u8),
+    Receive(ReadMode),
+    Wait(WriteMode),
+    WaitUs(u32),
+}
+
+/// A connection operation and the time (ns) it has been started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TracedOperation {
+    pub time: u64,
+    pub operation: Operation,
+}
+
+type Pins8Lines<RS, R, E, P0, P1, P2, P3, P4, P5, P6, P7> =
+    Pins<RS, R, E, DataPins8Lines<P0, P1, P2, P3, P4, P5, P6, P7>>;
+
+/// A recorder for the transitions of all pins wrapped by it and the operations of all
+/// connections wrapped by it.
+///
+/// All lines are assumed to be `Low` when the recording starts.
+#[derive(Default)]
+pub struct TraceRecorder {
+    start: Option<Instant>,
+    delay: VirtualDelay,
+    wired: Cell<u16>,
+    levels: Cell<u16>,
+    transitions: RefCell<Vec<Transition>>,
+    operations: RefCell<Vec<TracedOperation>>,
+}
+
+impl TraceRecorder {
+    /// Creates a recorder, which timestamps against the virtual clock of its [`delay`].
+    ///
+    /// [`delay`]: #method.delay
+    pub fn new() -> Self {
+        TraceRecorder::default()
+    }
+
+    /// Creates a recorder, which timestamps against the time elapsed since its creation, e.g. to
+    /// trace a display connected to real hardware.
+    pub fn with_real_time() -> Self {
+        TraceRecorder {
+            start: Some(Instant::now()),
+            ..TraceRecorder::default()
+        }
+    }
+
+    /// Returns the delay, which advances the virtual time of the recorder. It is passed to the
+    /// connection of the recorded pins.
+    pub fn delay(&self) -> &VirtualDelay {
+        &self.delay
+    }
+
+    /// Wraps the given connection to record its operations.
+    pub fn connection<P>(&self, connection: P) -> TracingConnection<'_, P> {
+        TracingConnection {
+            recorder: self,
+            connection,
+        }
+    }
+
+    /// Wraps the given pin, which is connected to the given bus line.
+    pub fn pin<P>(&self, line: BusLine, pin: P) -> RecordingPin<'_, P> {
+        self.wired.set(self.wired.get() | line.mask());
+
+        RecordingPin {
+            recorder: self,
+            line,
+            pin,
+        }
+    }
+
+    /// Wraps all pins of a display wired with four data lines.
+    pub fn pins_4lines<RS, R, E, P4, P5, P6, P7>(
+        &self,
+        pins: Pins<RS, R, E, DataPins4Lines<P4, P5, P6, P7>>,
+    ) -> RecordingPins4Lines<'_, RS, R, E, P4, P5, P6, P7>
+    where
+        P4: DisplayHardwareLayer,
+        P5: DisplayHardwareLayer,
+        P6: DisplayHardwareLayer,
+        P7: DisplayHardwareLayer,
+    {
+        let data = pins.data;
+
+        Pins {
+            register_select: self.pin(BusLine::RegisterSelect, pins.register_select),
+            read: self.pin(BusLine::ReadWrite, pins.read),
+            enable: self.pin(BusLine::Enable, pins.enable),
+            data: DataPins4Lines {
+                data4: self.pin(BusLine::Data(4), data.data4),
+                data5: self.pin(BusLine::Data(5), data.data5),
+                data6: self.pin(BusLine::Data(6), data.data6),
+                data7: self.pin(BusLine::Data(7), data.data7),
+            },
+        }
+    }
+
+    /// Wraps all pins of a display wired with eight data lines.
+    pub fn pins_8lines<RS, R, E, P0, P1, P2, P3, P4, P5, P6, P7>(
+        &self,
+        pins: Pins8Lines<RS, R, E, P0, P1, P2, P3, P4, P5, P6, P7>,
+    ) -> RecordingPins8Lines<'_, RS, R, E, P0, P1, P2, P3, P4, P5, P6, P7>
+    where
+        P0: DisplayHardwareLayer,
+        P1: DisplayHardwareLayer,
+        P2: DisplayHardwareLayer,
+        P3: DisplayHardwareLayer,
+        P4: DisplayHardwareLayer,
+        P5: DisplayHardwareLayer,
+        P6: DisplayHardwareLayer,
+        P7: DisplayHardwareLayer,
+    {
+        let data = pins.data;
+
+        Pins {
+            register_select: self.pin(BusLine::RegisterSelect, pins.register_select),
+            read: self.pin(BusLine::ReadWrite, pins.read),
+            enable: self.pin(BusLine::Enable, pins.enable),
+            data: DataPins8Lines {
+                data0: self.pin(BusLine::Data(0), data.data0),
+                data1: self.pin(BusLine::Data(1), data.data1),
+                data2: self.pin(BusLine::Data(2), data.data2),
+                data3: self.pin(BusLine::Data(3), data.data3),
+                data4: self.pin(BusLine::Data(4), data.data4),
+                data5: self.pin(BusLine::Data(5), data.data5),
+                data6: self.pin(BusLine::Data(6), data.data6),
+                data7: self.pin(BusLine::Data(7), data.data7),
+            },
+        }
+    }
+
+    /// Returns the transitions recorded so far.
+    pub fn transitions(&self) -> Vec<Transition> {
+        self.transitions.borrow().clone()
+    }
+
+    /// Returns the connection operations recorded so far.
+    pub fn operations(&self) -> Vec<TracedOperation> {
+        self.operations.borrow().clone()
+    }
+
+    /// Checks the transitions recorded so far against the given constraints.
+    pub fn check(&self, constraints: &TimingConstraints) -> Vec<Violation> {
+        check_timing(&self.transitions.borrow(), constraints)
+    }
+
+    pub(crate) fn is_wired(&self, line: BusLine) -> bool {
+        self.wired.get() & line.mask() != 0
+    }
+
+    fn now(&self) -> u64 {
+        match self.start {
+            Some(start) => start.elapsed().as_nanos() as u64,
+            None => self.delay.now(),
+        }
+    }
+
+    fn record_operation(&self, operation: Operation) {
+        self.operations.borrow_mut().push(TracedOperation {
+            time: self.now(),
+            operation,
+        });
+    }
+
+    fn record(&self, line: BusLine, level: Level) {
+        let mask = line.mask();
+        let levels = self.levels.get();
+
+        let changed = match level {
+            Level::High => levels | mask,
+            Level::Low => levels & !mask,
+        };
+
+        if changed != levels {
+            self.levels.set(changed);
+            self.transitions.borrow_mut().push(Transition {
+                time: self.now(),
+                line,
+                level,
+            });
+        }
+    }
+}
+
+/// A pin wrapper, which records the level changes of the wrapped pin in a [`TraceRecorder`].
+///
+/// [`TraceRecorder`]: struct.TraceRecorder.html
+pub struct RecordingPin<'a, P> {
+    recorder: &'a TraceRecorder,
+    line: BusLine,
+    pin: P,
+}
+
+impl<'a, P> RecordingPin<'a, P> {
+    /// Returns the wrapped pin.
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+impl<'a, P: DisplayHardwareLayer> DisplayHardwareLayer for RecordingPin<'a, P> {
+    type Error = P::Error;
+
+    fn init(&self) -> Result<(), P::Error> {
+        self.pin.init()
+    }
+
+    fn cleanup(&self) -> Result<(), P::Error> {
+        self.pin.cleanup()
+    }
+
+    fn set_direction(&self, direction: Direction) -> Result<(), P::Error> {
+        self.pin.set_direction(direction)
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), P::Error> {
+        self.recorder.record(self.line, level);
+        self.pin.set_level(level)
+    }
+
+    fn get_value(&self) -> Result<u8, P::Error> {
+        self.pin.get_value()
+    }
+}
+
+/// A connection wrapper, which records the operations of the wrapped connection in a
+/// [`TraceRecorder`].
+///
+/// [`TraceRecorder`]: struct.TraceRecorder.html
+pub struct TracingConnection<'a, P> {
+    recorder: &'a TraceRecorder,
+    connection: P,
+}
+
+impl<'a, P> TracingConnection<'a, P> {
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> P {
+        self.connection
+    }
+}
+
+forward_connection!(impl<'a, P> TracingConnection<'a, P> => P:
+    ErrorType, Release, SelectController, Backlight, Brightness, Contrast);
+
+impl<'a, P: Init> Init for TracingConnection<'a, P> {
+    fn init(&self) -> Result<(), Self::Error> {
+        self.recorder.record_operation(Operation::Init);
+        self.connection.init()
+    }
+}
+
+impl<'a, P: Send> Send for TracingConnection<'a, P> {
+    fn send(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.recorder.record_operation(Operation::Send(mode));
+        self.connection.send(mode)
+    }
+
+    fn send_upper_nibble(&self, cmd: u8) -> Result<(), Self::Error> {
+        self.recorder.record_operation(Operation::SendUpperNibble(cmd));
+        self.connection.send_upper_nibble(cmd)
+    }
+}
+
+impl<'a, P: Receive> Receive for TracingConnection<'a, P> {
+    fn receive(&self, mode: ReadMode) -> Result<u8, Self::Error> {
+        self.recorder.record_operation(Operation::Receive(mode));
+        self.connection.receive(mode)
+    }
+}
+
+impl<'a, P: Wait> Wait for TracingConnection<'a, P> {
+    fn wait(&self, mode: WriteMode) -> Result<(), Self::Error> {
+        self.recorder.record_operation(Operation::Wait(mode));
+        self.connection.wait(mode)
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), Self::Error> {
+        self.recorder.record_operation(Operation::WaitUs(us));
+        self.connection.wait_us(us)
+    }
+}
+
+/// The pins of a display wired with four data lines, wrapped by a [`TraceRecorder`].
+///
+/// [`TraceRecorder`]: struct.TraceRecorder.html
+pub type RecordingPins4Lines<'a, RS, R, E, P4, P5, P6, P7> = Pins<
+    RecordingPin<'a, RS>,
+    RecordingPin<'a, R>,
+    RecordingPin<'a, E>,
+    DataPins4Lines<
+        RecordingPin<'a, P4>,
+        RecordingPin<'a, P5>,
+        RecordingPin<'a, P6>,
+        RecordingPin<'a, P7>,
+    >,
+>;
+
+/// The pins of a display wired with eight data lines, wrapped by a [`TraceRecorder`].
+///
+/// [`TraceRecorder`]: struct.TraceRecorder.html
+pub type RecordingPins8Lines<'a, RS, R, E, P0, P1, P2, P3, P4, P5, P6, P7> = Pins<
+    RecordingPin<'a, RS>,
+    RecordingPin<'a, R>,
+    RecordingPin<'a, E>,
+    DataPins8Lines<
+        RecordingPin<'a, P0>,
+        RecordingPin<'a, P1>,
+        RecordingPin<'a, P2>,
+        RecordingPin<'a, P3>,
+        RecordingPin<'a, P4>,
+        RecordingPin<'a, P5>,
+        RecordingPin<'a, P6>,
+        RecordingPin<'a, P7>,
+    >,
+>;
+
+/// The minimum timings of the bus given in the datasheet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimingConstraints {
+    /// The time (ns) RS and R/W have to be stable before E rises (tAS).
+    pub address_setup_time: u64,
+    /// The time (ns) RS and R/W have to be stable after E falls (tAH).
+    pub address_hold_time: u64,
+    /// The duration (ns) E has to be `High` (PWEH).
+    pub enable_pulse_width: u64,
+    /// The time (ns) the data has to be stable before E falls (tDSW).
+    pub data_setup_time: u64,
+    /// The time (ns) the data has to be stable after E falls (tH).
+    pub data_hold_time: u64,
+    /// The execution time (µs) of instructions and data writes.
+    pub command_execution_time: u64,
+    /// The execution time (µs) of the clear display and return home instructions.
+    pub clear_display_execution_time: u64,
+}
+
+impl TimingConstraints {
+    /// The constraints for a supply voltage of 4.5 V to 5.5 V.
+    pub const VCC_5V: TimingConstraints = TimingConstraints {
+        address_setup_time: 40,
+        address_hold_time: 10,
+        enable_pulse_width: 230,
+        data_setup_time: 80,
+        data_hold_time: 10,
+        command_execution_time: 37,
+        clear_display_execution_time: 1520,
+    };
+
+    /// The constraints for a supply voltage of 2.7 V to 4.5 V.
+    pub const VCC_3V: TimingConstraints = TimingConstraints {
+        address_setup_time: 60,
+        address_hold_time: 20,
+        enable_pulse_width: 450,
+        data_setup_time: 195,
+        data_hold_time: 10,
+        command_execution_time: 37,
+        clear_display_execution_time: 1520,
+    };
+}
+
+impl Default for TimingConstraints {
+    /// Returns the constraints for the lower supply voltage, which are met at any voltage.
+    fn default() -> Self {
+        TimingConstraints::VCC_3V
+    }
+}
+
+/// Enumeration of violated timing constraints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViolationKind {
+    AddressSetup,
+    AddressHold,
+    EnablePulseWidth,
+    DataSetup,
+    DataHold,
+    /// The next transfer started before the previous instruction has been executed. Busy flag
+    /// reads are allowed at any time.
+    ExecutionTime,
+}
+
+/// A violated timing constraint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// The virtual time (ns) of the transition, which violated the constraint.
+    pub time: u64,
+    /// The measured time (ns).
+    pub actual: u64,
+    /// The required time (ns).
+    pub required: u64,
+}
+
+/// The state of the bus and the controller while walking through a trace.
+struct TraceState {
+    levels: u16,
+    address_changed: u64,
+    data_changed: u64,
+    enable_rose: u64,
+    enable_fell: Option<u64>,
+    eight_bit: bool,
+    upper_nibble: Option<u8>,
+    /// The completion time and the execution time (ns) of the last instruction.
+    executing: Option<(u64, u64)>,
+}
+
+impl TraceState {
+    fn is_set(&self, line: BusLine) -> bool {
+        self.levels & line.mask() != 0
+    }
+
+    /// Returns the transferred byte, once it is complete.
+    fn transfer(&mut self) -> Option<u8> {
+        let data = self.levels as u8;
+
+        if self.eight_bit {
+            return Some(data);
+        }
+
+        match self.upper_nibble.take() {
+            None => {
+                self.upper_nibble = Some(data >> 4);
+                None
+            }
+            Some(upper) => Some(upper << 4 | data >> 4),
+        }
+    }
+}
+
+/// Checks the given transitions against the given constraints and returns all violations in
+/// the order of their occurrence.
+///
+/// The controller is assumed to be in its power on state, i.e. it uses an 8-bit interface,
+/// until the trace contains a function set instruction. A transition, which is older than the
+/// one before it, is treated as simultaneous with it.
+pub fn check_timing(transitions: &[Transition], constraints: &TimingConstraints) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut state = TraceState {
+        levels: 0,
+        address_changed: 0,
+        data_changed: 0,
+        enable_rose: 0,
+        enable_fell: None,
+        eight_bit: true,
+        upper_nibble: None,
+        executing: None,
+    };
+
+    let mut violated = |kind, time, actual, required| {
+        if actual < required {
+            violations.push(Violation {
+                kind,
+                time,
+                actual,
+                required,
+            });
+        }
+    };
+
+    for transition in transitions {
+        let time = transition.time;
+        let mask = transition.line.mask();
+
+        state.levels = match transition.level {
+            Level::High => state.levels | mask,
+            Level::Low => state.levels & !mask,
+        };
+
+        let enabled = state.is_set(BusLine::Enable);
+        let since_fall = state.enable_fell.map(|fell| time.saturating_sub(fell));
+
+        match (transition.line, transition.level) {
+            (BusLine::RegisterSelect, _) | (BusLine::ReadWrite, _) => {
+                if enabled {
+                    violated(ViolationKind::AddressHold, time, 0, constraints.address_hold_time);
+                } else if let Some(since_fall) = since_fall {
+                    let required = constraints.address_hold_time;
+                    violated(ViolationKind::AddressHold, time, since_fall, required);
+                }
+
+                state.address_changed = time;
+            }
+            (BusLine::Data(_), _) => {
+                if !enabled {
+                    if let Some(since_fall) = since_fall {
+                        let required = constraints.data_hold_time;
+                        violated(ViolationKind::DataHold, time, since_fall, required);
+                    }
+                }
+
+                state.data_changed = time;
+            }
+            (BusLine::Enable, Level::High) => {
+                let since_address = time.saturating_sub(state.address_changed);
+                let required = constraints.address_setup_time;
+                violated(ViolationKind::AddressSetup, time, since_address, required);
+
+                let reads_busy_flag = state.is_set(BusLine::ReadWrite)
+                    && !state.is_set(BusLine::RegisterSelect);
+
+                if let Some((completed, execution_time)) = state.executing.take() {
+                    if !reads_busy_flag {
+                        let kind = ViolationKind::ExecutionTime;
+                        violated(kind, time, time.saturating_sub(completed), execution_time);
+                    }
+                }
+
+                state.enable_rose = time;
+            }
+            (BusLine::Enable, Level::Low) => {
+                let pulse_width = time.saturating_sub(state.enable_rose);
+                let required = constraints.enable_pulse_width;
+                violated(ViolationKind::EnablePulseWidth, time, pulse_width, required);
+
+                let writing = !state.is_set(BusLine::ReadWrite);
+                if writing {
+                    let since_data = time.saturating_sub(state.data_changed);
+                    let required = constraints.data_setup_time;
+                    violated(ViolationKind::DataSetup, time, since_data, required);
+                }
+
+                if let Some(value) = state.transfer() {
+                    if writing {
+                        let is_command = !state.is_set(BusLine::RegisterSelect);
+                        let execution_time = match value {
+                            _ if !is_command => constraints.command_execution_time,
+                            CLEAR_DISPLAY => constraints.clear_display_execution_time,
+                            cmd if cmd & !0b0000_0001 == RETURN_HOME => {
+                                constraints.clear_display_execution_time
+                            }
+                            _ => constraints.command_execution_time,
+                        };
+
+                        if is_command && value & 0b1110_0000 == FUNCTION_SET {
+                            state.eight_bit = value & INTERFACE_DATA_LENGTH_8BIT != 0;
+                        }
+
+                        state.executing = Some((time, execution_time * 1000));
+                    }
+                }
+
+                state.enable_fell = Some(time);
+            }
+        }
+    }
+
+    violations
+}
@@ -0,0 +1,137 @@
+//! Export of recorded traces as Value Change Dump (VCD), which can be viewed by waveform viewers
+//! like GTKWave.
+
+use std::format;
+use std::io::{self, Write};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::hal::{Level, ReadMode, WriteMode};
+use crate::simulator::BusLine;
+use crate::timing::{Operation, TraceRecorder};
+
+const LINES: [BusLine; 11] = [
+    BusLine::RegisterSelect,
+    BusLine::ReadWrite,
+    BusLine::Enable,
+    BusLine::Data(0),
+    BusLine::Data(1),
+    BusLine::Data(2),
+    BusLine::Data(3),
+    BusLine::Data(4),
+    BusLine::Data(5),
+    BusLine::Data(6),
+    BusLine::Data(7),
+];
+
+/// The VCD identifier of the operation variable, following the ones of the bus lines.
+const OPERATION_ID: char = ',';
+
+enum Event {
+    Change(BusLine, Level),
+    Operation(Operation),
+}
+
+fn line_name(line: BusLine) -> String {
+    match line {
+        BusLine::RegisterSelect => String::from("RS"),
+        BusLine::ReadWrite => String::from("RW"),
+        BusLine::Enable => String::from("E"),
+        BusLine::Data(n) => format!("D{}", n),
+    }
+}
+
+fn line_id(line: BusLine) -> char {
+    let index = LINES.iter().position(|&l| l == line).unwrap_or(0);
+    (b'!' + index as u8) as char
+}
+
+/// Returns a label of the operation, which must not contain whitespace.
+fn operation_label(operation: Operation) -> String {
+    match operation {
+        Operation::Init => String::from("init"),
+        Operation::Send(WriteMode::Command(cmd)) => format!("command_0x{:02x}", cmd),
+        Operation::Send(WriteMode::Data(value)) => format!("data_0x{:02x}", value),
+        Operation::SendUpperNibble(cmd) => format!("upper_nibble_0x{:x}", cmd >> 4),
+        Operation::Receive(ReadMode::Data) => String::from("read_data"),
+        Operation::Receive(ReadMode::BusyFlag) => String::from("read_busy_flag"),
+        Operation::Wait(_) => String::from("wait_execution"),
+        Operation::WaitUs(us) => format!("wait_{}us", us),
+    }
+}
+
+fn level_value(level: Level) -> char {
+    match level {
+        Level::Low => '0',
+        Level::High => '1',
+    }
+}
+
+impl TraceRecorder {
+    /// Writes the recorded transitions as VCD with a resolution of one nanosecond. The
+    /// recorded connection operations are written as additional string variable `operation`.
+    ///
+    /// Only the data lines with a wrapped pin are written, besides RS, R/W and E.
+    pub fn write_vcd<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let transitions = self.transitions();
+        let operations = self.operations();
+
+        let lines: Vec<BusLine> = LINES
+            .iter()
+            .cloned()
+            .filter(|&line| match line {
+                BusLine::Data(_) => self.is_wired(line),
+                _ => true,
+            })
+            .collect();
+
+        writeln!(writer, "$version clerk {} $end", env!("CARGO_PKG_VERSION"))?;
+        writeln!(writer, "$timescale 1ns $end")?;
+        writeln!(writer, "$scope module lcd $end")?;
+        for &line in &lines {
+            writeln!(writer, "$var wire 1 {} {} $end", line_id(line), line_name(line))?;
+        }
+        writeln!(writer, "$var string 1 {} operation $end", OPERATION_ID)?;
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        writeln!(writer, "#0")?;
+        writeln!(writer, "$dumpvars")?;
+        for &line in &lines {
+            writeln!(writer, "0{}", line_id(line))?;
+        }
+        writeln!(writer, "$end")?;
+
+        // operations are recorded before the transitions they cause, so they go first on equal
+        // timestamps
+        let mut events: Vec<(u64, Event)> = operations
+            .iter()
+            .map(|o| (o.time, Event::Operation(o.operation)))
+            .chain(
+                transitions
+                    .iter()
+                    .map(|t| (t.time, Event::Change(t.line, t.level))),
+            )
+            .collect();
+        events.sort_by_key(|&(time, _)| time);
+
+        let mut current_time = 0;
+        for (time, event) in events {
+            if time != current_time {
+                writeln!(writer, "#{}", time)?;
+                current_time = time;
+            }
+
+            match event {
+                Event::Change(line, level) => {
+                    writeln!(writer, "{}{}", level_value(level), line_id(line))?
+                }
+                Event::Operation(operation) => {
+                    writeln!(writer, "s{} {}", operation_label(operation), OPERATION_ID)?
+                }
+            }
+        }
+
+        writer.flush()
+    }
+}
@@ -0,0 +1,334 @@
+#![cfg(feature = "async")]
+
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use clerk::{AsyncDelay, AsyncDisplay, AsyncInit, AsyncReceive, AsyncSend, AsyncWait,
+            DataPins4Lines, DefaultLines, Delay, Direction, DisplayHardwareLayer, ErrorType,
+            FunctionSetBuilder, Grounded, Level, Pins, ReadMode, SeekCgRamFrom, SeekFrom,
+            SetFrom, WriteMode};
+
+/// A future, which is pending on its first poll, like a timer of an async executor.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Polls the given future to completion and returns its output together with the number of
+/// times it has been pending, i.e. other tasks had the chance to run.
+fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut pending = 0;
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, pending),
+            Poll::Pending => pending += 1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    Init,
+    Send(WriteMode),
+    ReceiveData,
+    ReceiveBusyFlag,
+    Wait(WriteMode),
+    WaitUs(u32),
+}
+
+#[derive(Default)]
+struct ConnectionMock {
+    calls: RefCell<Vec<Call>>,
+    lost: Cell<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+struct DeviceLost;
+
+impl ErrorType for ConnectionMock {
+    type Error = DeviceLost;
+}
+
+impl AsyncInit for ConnectionMock {
+    async fn init(&self) -> Result<(), DeviceLost> {
+        self.calls.borrow_mut().push(Call::Init);
+        Ok(())
+    }
+}
+
+impl AsyncSend for ConnectionMock {
+    async fn send(&self, mode: WriteMode) -> Result<(), DeviceLost> {
+        if self.lost.get() {
+            return Err(DeviceLost);
+        }
+
+        self.calls.borrow_mut().push(Call::Send(mode));
+        Ok(())
+    }
+}
+
+impl AsyncReceive for ConnectionMock {
+    async fn receive(&self, mode: ReadMode) -> Result<u8, DeviceLost> {
+        let call = match mode {
+            ReadMode::Data => Call::ReceiveData,
+            ReadMode::BusyFlag => Call::ReceiveBusyFlag,
+        };
+
+        self.calls.borrow_mut().push(call);
+        Ok(0b1000_0011)
+    }
+}
+
+impl AsyncWait for ConnectionMock {
+    async fn wait(&self, mode: WriteMode) -> Result<(), DeviceLost> {
+        YieldOnce(false).await;
+        self.calls.borrow_mut().push(Call::Wait(mode));
+        Ok(())
+    }
+
+    async fn wait_us(&self, us: u32) -> Result<(), DeviceLost> {
+        YieldOnce(false).await;
+        self.calls.borrow_mut().push(Call::WaitUs(us));
+        Ok(())
+    }
+}
+
+#[test]
+fn init() {
+    let lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+
+    let (result, pending) = block_on(lcd.init(&FunctionSetBuilder::default()));
+    assert_eq!(result, Ok(()));
+    assert_eq!(pending, 7);
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Init,
+            Call::WaitUs(40_000),
+            Call::Send(WriteMode::Command(0x30)),
+            Call::WaitUs(4_100),
+            Call::Send(WriteMode::Command(0x30)),
+            Call::WaitUs(100),
+            Call::Send(WriteMode::Command(0x30)),
+            Call::WaitUs(100),
+            Call::Send(WriteMode::Command(0x20)),
+            Call::WaitUs(100),
+            Call::Send(WriteMode::Command(0b0010_0000)),
+            Call::Wait(WriteMode::Command(0b0010_0000)),
+            Call::Send(WriteMode::Command(0x01)),
+            Call::Wait(WriteMode::Command(0x01)),
+        ]
+    );
+}
+
+#[test]
+fn write_and_seek() {
+    let mut lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+
+    block_on(lcd.write_message("ab")).0.unwrap();
+    block_on(lcd.seek(SeekFrom::Current(2))).0.unwrap();
+    block_on(lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 1,
+    })).0
+        .unwrap();
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Send(WriteMode::Data(b'a')),
+            Call::Wait(WriteMode::Data(b'a')),
+            Call::Send(WriteMode::Data(b'b')),
+            Call::Wait(WriteMode::Data(b'b')),
+            Call::Send(WriteMode::Command(0b1000_0100)),
+            Call::Wait(WriteMode::Command(0b1000_0100)),
+            Call::Send(WriteMode::Command(0b1100_0001)),
+            Call::Wait(WriteMode::Command(0b1100_0001)),
+        ]
+    );
+}
+
+#[test]
+fn clear_resets_cursor() {
+    let mut lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+
+    block_on(lcd.write_message("ab")).0.unwrap();
+    block_on(lcd.clear()).0.unwrap();
+    block_on(lcd.seek(SeekFrom::Current(1))).0.unwrap();
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(calls[6], Call::Send(WriteMode::Command(0b1000_0001)));
+}
+
+#[test]
+fn write_message_respects_geometry() {
+    let mut lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+    lcd.set_geometry(20, 4);
+
+    block_on(lcd.write_message("0123456789abcdefghijXYZ")).0.unwrap();
+
+    let calls = lcd.get_connection().calls.into_inner();
+    let written: Vec<u8> = calls
+        .iter()
+        .filter_map(|call| match *call {
+            Call::Send(WriteMode::Data(c)) => Some(c),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(written, b"0123456789abcdefghij");
+}
+
+#[test]
+fn switch_ram() {
+    let lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+
+    let mut lcd = block_on(lcd.set_cgram_address(3)).0.ok().unwrap();
+    block_on(lcd.write(0b1_0101)).0.unwrap();
+    block_on(lcd.seek(SeekCgRamFrom::Current(1))).0.unwrap();
+
+    let lcd = block_on(lcd.set_ddram_address(SetFrom::Home(0))).0.ok().unwrap();
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Send(WriteMode::Command(0b0100_0011)),
+            Call::Wait(WriteMode::Command(0b0100_0011)),
+            Call::Send(WriteMode::Data(0b1_0101)),
+            Call::Wait(WriteMode::Data(0b1_0101)),
+            Call::Send(WriteMode::Command(0b0100_0101)),
+            Call::Wait(WriteMode::Command(0b0100_0101)),
+            Call::Send(WriteMode::Command(0b1000_0000)),
+            Call::Wait(WriteMode::Command(0b1000_0000)),
+        ]
+    );
+}
+
+#[test]
+fn failed_set_cgram_address_returns_display() {
+    let mut lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+    block_on(lcd.seek(SeekFrom::Home(5))).0.unwrap();
+
+    let connection = lcd.get_connection();
+    connection.lost.set(true);
+    let lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(connection);
+
+    let (lcd, e) = match block_on(lcd.set_cgram_address(3)).0 {
+        Ok(_) => panic!("switching RAM must fail"),
+        Err(err) => err,
+    };
+    assert_eq!(e, clerk::Error::Connection(DeviceLost));
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Send(WriteMode::Command(0b1000_0101)),
+            Call::Wait(WriteMode::Command(0b1000_0101)),
+        ]
+    );
+}
+
+#[test]
+fn read() {
+    let mut lcd: AsyncDisplay<_, DefaultLines> = AsyncDisplay::new(ConnectionMock::default());
+
+    assert_eq!(block_on(lcd.read_byte()).0, Ok(0b1000_0011));
+    assert_eq!(block_on(lcd.read_busy_flag()).0, Ok((true, 3)));
+
+    let calls = lcd.get_connection().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::ReceiveData,
+            Call::Wait(WriteMode::Data(0b1000_0011)),
+            Call::ReceiveBusyFlag,
+        ]
+    );
+}
+
+struct PinMock;
+
+impl DisplayHardwareLayer for PinMock {
+    type Error = ();
+
+    fn set_direction(&self, _: Direction) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn set_level(&self, _: Level) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, ()> {
+        Ok(0)
+    }
+}
+
+thread_local! {
+    static DELAYS: RefCell<Vec<u32>> = const { RefCell::new(vec![]) };
+}
+
+struct AsyncDelayMock;
+
+impl Delay for AsyncDelayMock {
+    fn delay_ns(&self, _: u32) {}
+
+    fn delay_us(&self, _: u32) {
+        panic!("the execution time must not be waited for blocking");
+    }
+}
+
+impl AsyncDelay for AsyncDelayMock {
+    async fn delay_us(&self, us: u32) {
+        YieldOnce(false).await;
+        DELAYS.with(|delays| delays.borrow_mut().push(us));
+    }
+}
+
+#[test]
+fn parallel_connection_awaits_execution_time() {
+    let pins = Pins {
+        register_select: PinMock,
+        read: Grounded,
+        enable: PinMock,
+        data: DataPins4Lines {
+            data4: PinMock,
+            data5: PinMock,
+            data6: PinMock,
+            data7: PinMock,
+        },
+    };
+
+    let mut lcd: AsyncDisplay<_, DefaultLines> =
+        AsyncDisplay::new(pins.into_connection(AsyncDelayMock));
+
+    let (result, pending) = block_on(lcd.clear());
+    assert_eq!(result, Ok(()));
+    assert_eq!(pending, 1);
+
+    block_on(lcd.write(b'a')).0.unwrap();
+
+    DELAYS.with(|delays| assert_eq!(*delays.borrow(), vec![1520, 37]));
+}
@@ -0,0 +1,163 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+
+use clerk::{BacklightConnection, DefaultLines, Direction, Display, DisplayHardwareLayer,
+            ErrorType, Init, Level, PwmBacklight, PwmPin, Send, Wait, WriteMode};
+
+#[derive(Default)]
+struct ConnectionMock {
+    init_calls: Cell<u8>,
+    waits: RefCell<Vec<u32>>,
+}
+
+impl ErrorType for ConnectionMock {
+    type Error = ();
+}
+
+impl Init for ConnectionMock {
+    fn init(&self) -> Result<(), ()> {
+        self.init_calls.set(self.init_calls.get() + 1);
+        Ok(())
+    }
+}
+
+impl Send for ConnectionMock {
+    fn send(&self, _: WriteMode) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+impl Wait for ConnectionMock {
+    fn wait(&self, _: WriteMode) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), ()> {
+        self.waits.borrow_mut().push(us);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct PinMock {
+    direction: Cell<Option<Direction>>,
+    levels: RefCell<Vec<Level>>,
+}
+
+impl DisplayHardwareLayer for PinMock {
+    type Error = ();
+
+    fn set_direction(&self, direction: Direction) -> Result<(), ()> {
+        self.direction.set(Some(direction));
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), ()> {
+        self.levels.borrow_mut().push(level);
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, ()> {
+        Ok(0)
+    }
+}
+
+#[derive(Default)]
+struct PwmMock {
+    duty_cycles: RefCell<Vec<u16>>,
+}
+
+impl PwmPin for PwmMock {
+    type Error = ();
+
+    fn max_duty_cycle(&self) -> u16 {
+        1000
+    }
+
+    fn set_duty_cycle(&self, duty: u16) -> Result<(), ()> {
+        self.duty_cycles.borrow_mut().push(duty);
+        Ok(())
+    }
+}
+
+type PwmDisplay = Display<BacklightConnection<ConnectionMock, PwmBacklight<PwmMock>>, DefaultLines>;
+
+fn setup_pwm_display() -> PwmDisplay {
+    let backlight = PwmBacklight::new(PwmMock::default());
+
+    Display::new(BacklightConnection::new(ConnectionMock::default(), backlight))
+}
+
+#[test]
+fn init_configures_backlight_pin() {
+    let connection = BacklightConnection::new(ConnectionMock::default(), PinMock::default());
+
+    connection.init().unwrap();
+
+    let (connection, pin) = connection.into_inner();
+    assert_eq!(connection.init_calls.get(), 1);
+    assert_eq!(pin.direction.get(), Some(Direction::Out));
+}
+
+#[test]
+fn set_backlight_by_pin() {
+    let connection = BacklightConnection::new(ConnectionMock::default(), PinMock::default());
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+
+    lcd.set_backlight(true).unwrap();
+    lcd.set_backlight(false).unwrap();
+
+    let (_, pin) = lcd.get_connection().into_inner();
+    assert_eq!(pin.levels.into_inner(), vec![Level::High, Level::Low]);
+}
+
+#[test]
+fn set_brightness_scales_duty_cycle() {
+    let lcd = setup_pwm_display();
+
+    lcd.set_brightness(128).unwrap();
+    assert_eq!(lcd.brightness(), 128);
+
+    lcd.set_backlight(false).unwrap();
+    lcd.set_backlight(true).unwrap();
+    assert_eq!(lcd.brightness(), 255);
+
+    let (_, backlight) = lcd.get_connection().into_inner();
+    let duty_cycles = backlight.into_inner().duty_cycles.into_inner();
+    assert_eq!(duty_cycles, vec![501, 0, 1000]);
+}
+
+#[test]
+fn fade_in() {
+    let lcd = setup_pwm_display();
+
+    lcd.fade_in(255).unwrap();
+    assert_eq!(lcd.brightness(), 255);
+
+    let (connection, backlight) = lcd.get_connection().into_inner();
+
+    let waits = connection.waits.into_inner();
+    assert_eq!(waits.len(), 255);
+    assert!(waits.iter().all(|&us| us == 1000));
+
+    let duty_cycles = backlight.into_inner().duty_cycles.into_inner();
+    assert_eq!(duty_cycles.len(), 255);
+    assert_eq!(duty_cycles[0], 3);
+    assert_eq!(duty_cycles[254], 1000);
+}
+
+#[test]
+fn fade_out_with_long_steps() {
+    let lcd = setup_pwm_display();
+    lcd.set_brightness(2).unwrap();
+
+    lcd.fade_out(200).unwrap();
+    assert_eq!(lcd.brightness(), 0);
+
+    let (connection, backlight) = lcd.get_connection().into_inner();
+    assert_eq!(connection.waits.into_inner(), vec![100_000, 100_000]);
+
+    let duty_cycles = backlight.into_inner().duty_cycles.into_inner();
+    assert_eq!(duty_cycles, vec![7, 3, 0]);
+}
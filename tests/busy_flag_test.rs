@@ -0,0 +1,161 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use clerk::{BusyFlagPolling, DefaultLines, Display, ErrorType, Init, ReadMode, Receive, Send,
+            Wait, WriteMode};
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    Send(WriteMode),
+    Receive,
+    Wait(WriteMode),
+    WaitUs(u32),
+}
+
+#[derive(Default)]
+struct ConnectionMock {
+    init_calls: Cell<u8>,
+    calls: RefCell<Vec<Call>>,
+    busy_flags: RefCell<VecDeque<u8>>,
+}
+
+impl ConnectionMock {
+    fn set_busy_flags(&self, flags: &[u8]) {
+        self.busy_flags.borrow_mut().extend(flags);
+    }
+}
+
+/// Reported if no more busy flags are available.
+#[derive(Debug, PartialEq)]
+struct NoResponse;
+
+impl ErrorType for ConnectionMock {
+    type Error = NoResponse;
+}
+
+impl Init for ConnectionMock {
+    fn init(&self) -> Result<(), NoResponse> {
+        self.init_calls.set(self.init_calls.get() + 1);
+        Ok(())
+    }
+}
+
+impl Send for ConnectionMock {
+    fn send(&self, mode: WriteMode) -> Result<(), NoResponse> {
+        self.calls.borrow_mut().push(Call::Send(mode));
+        Ok(())
+    }
+}
+
+impl Receive for ConnectionMock {
+    fn receive(&self, mode: ReadMode) -> Result<u8, NoResponse> {
+        match mode {
+            ReadMode::BusyFlag => {
+                self.calls.borrow_mut().push(Call::Receive);
+                self.busy_flags.borrow_mut().pop_front().ok_or(NoResponse)
+            }
+            ReadMode::Data => unreachable!("busy flag polling only reads the busy flag"),
+        }
+    }
+}
+
+impl Wait for ConnectionMock {
+    fn wait(&self, mode: WriteMode) -> Result<(), NoResponse> {
+        self.calls.borrow_mut().push(Call::Wait(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), NoResponse> {
+        self.calls.borrow_mut().push(Call::WaitUs(us));
+        Ok(())
+    }
+}
+
+#[test]
+fn init_is_forwarded() {
+    let connection = BusyFlagPolling::new(ConnectionMock::default());
+
+    connection.init().unwrap();
+
+    assert_eq!(connection.into_inner().init_calls.get(), 1);
+}
+
+#[test]
+fn wait_returns_when_not_busy() {
+    let mock = ConnectionMock::default();
+    mock.set_busy_flags(&[0b1000_0101, 0b1000_0101, 0b0000_0110]);
+
+    let connection = BusyFlagPolling::new(mock);
+    connection.wait(WriteMode::Data(b'a')).unwrap();
+
+    let calls = connection.into_inner().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Receive,
+            Call::WaitUs(10),
+            Call::Receive,
+            Call::WaitUs(10),
+            Call::Receive,
+        ]
+    );
+}
+
+#[test]
+fn wait_falls_back_to_execution_time() {
+    let mock = ConnectionMock::default();
+    mock.set_busy_flags(&[0b1000_0000, 0b1000_0000]);
+
+    let connection = BusyFlagPolling::with_max_polls(mock, 2);
+    connection.wait(WriteMode::Command(0x01)).unwrap();
+
+    let calls = connection.into_inner().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Receive,
+            Call::WaitUs(10),
+            Call::Receive,
+            Call::Wait(WriteMode::Command(0x01)),
+        ]
+    );
+}
+
+#[test]
+fn wait_reports_receive_error() {
+    let mock = ConnectionMock::default();
+    mock.set_busy_flags(&[0b1000_0000]);
+
+    let connection = BusyFlagPolling::new(mock);
+
+    assert_eq!(connection.wait(WriteMode::Data(b'a')), Err(NoResponse));
+
+    let calls = connection.into_inner().calls.into_inner();
+    assert_eq!(calls, vec![Call::Receive, Call::WaitUs(10), Call::Receive]);
+}
+
+#[test]
+fn display_polls_after_every_instruction() {
+    let mock = ConnectionMock::default();
+    mock.set_busy_flags(&[0b1000_0000, 0b0000_0000, 0b0000_0001]);
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(BusyFlagPolling::new(mock));
+
+    lcd.clear().unwrap();
+    lcd.write(b'x').unwrap();
+
+    let calls = lcd.get_connection().into_inner().calls.into_inner();
+    assert_eq!(
+        calls,
+        vec![
+            Call::Send(WriteMode::Command(0x01)),
+            Call::Receive,
+            Call::WaitUs(10),
+            Call::Receive,
+            Call::Send(WriteMode::Data(b'x')),
+            Call::Receive,
+        ]
+    );
+}
@@ -0,0 +1,173 @@
+extern crate clerk;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use clerk::{DacContrast, DacPin, DataPins4Lines, DefaultLines, Delay, Direction, Display,
+            DisplayHardwareLayer, ErrorType, FunctionSetBuilder, Grounded, Level, Pins,
+            PwmContrast, PwmPin, Send, St7032Contrast, Wait, WriteMode};
+
+struct PinMock;
+
+impl DisplayHardwareLayer for PinMock {
+    type Error = ();
+
+    fn set_direction(&self, _: Direction) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn set_level(&self, _: Level) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, ()> {
+        Ok(0)
+    }
+}
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+struct OutputMock {
+    values: Rc<RefCell<Vec<u16>>>,
+}
+
+impl PwmPin for OutputMock {
+    type Error = ();
+
+    fn max_duty_cycle(&self) -> u16 {
+        1000
+    }
+
+    fn set_duty_cycle(&self, duty: u16) -> Result<(), ()> {
+        self.values.borrow_mut().push(duty);
+        Ok(())
+    }
+}
+
+impl DacPin for OutputMock {
+    type Error = ();
+
+    fn max_value(&self) -> u16 {
+        4095
+    }
+
+    fn set_value(&self, value: u16) -> Result<(), ()> {
+        self.values.borrow_mut().push(value);
+        Ok(())
+    }
+}
+
+fn pins() -> Pins<PinMock, Grounded, PinMock, DataPins4Lines<PinMock, PinMock, PinMock, PinMock>> {
+    Pins {
+        register_select: PinMock,
+        read: Grounded,
+        enable: PinMock,
+        data: DataPins4Lines {
+            data4: PinMock,
+            data5: PinMock,
+            data6: PinMock,
+            data7: PinMock,
+        },
+    }
+}
+
+#[test]
+fn pwm_contrast() {
+    let values = Rc::new(RefCell::new(vec![]));
+    let output = OutputMock {
+        values: values.clone(),
+    };
+
+    let connection = pins()
+        .into_connection(DelayMock)
+        .with_contrast(PwmContrast::new(output));
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+
+    lcd.set_contrast(255).unwrap();
+    lcd.set_contrast(128).unwrap();
+    lcd.set_contrast(0).unwrap();
+
+    // the contrast rises with falling contrast voltage
+    assert_eq!(*values.borrow(), vec![0, 498, 1000]);
+}
+
+#[test]
+fn dac_contrast() {
+    let values = Rc::new(RefCell::new(vec![]));
+    let output = OutputMock {
+        values: values.clone(),
+    };
+
+    let connection = pins()
+        .into_connection(DelayMock)
+        .with_contrast(DacContrast::new(output));
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+
+    lcd.set_contrast(0).unwrap();
+    lcd.set_contrast(51).unwrap();
+
+    assert_eq!(*values.borrow(), vec![4095, 3276]);
+}
+
+#[derive(Default)]
+struct ConnectionMock {
+    send_bytes: RefCell<Vec<WriteMode>>,
+}
+
+impl ErrorType for ConnectionMock {
+    type Error = ();
+}
+
+impl Send for ConnectionMock {
+    fn send(&self, mode: WriteMode) -> Result<(), ()> {
+        self.send_bytes.borrow_mut().push(mode);
+        Ok(())
+    }
+}
+
+impl Wait for ConnectionMock {
+    fn wait(&self, _: WriteMode) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn wait_us(&self, _: u32) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn st7032_contrast_by_instruction() {
+    let function_set = FunctionSetBuilder::default();
+    let connection = St7032Contrast::new(ConnectionMock::default(), &function_set, true);
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+
+    lcd.set_contrast(128).unwrap();
+
+    let send_bytes = lcd.get_connection().into_inner().send_bytes.into_inner();
+    assert_eq!(
+        send_bytes,
+        vec![
+            WriteMode::Command(0b0010_0001),
+            WriteMode::Command(0b0101_0101),
+            WriteMode::Command(0b0111_1111),
+            WriteMode::Command(0b0010_0000),
+        ]
+    );
+}
+
+#[test]
+fn st7032_contrast_without_booster() {
+    let function_set = FunctionSetBuilder::default();
+    let connection = St7032Contrast::new(ConnectionMock::default(), &function_set, false);
+
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+    lcd.set_contrast(255).unwrap();
+
+    let send_bytes = lcd.get_connection().into_inner().send_bytes.into_inner();
+    assert_eq!(send_bytes[1], WriteMode::Command(0b0101_0011));
+    assert_eq!(send_bytes[2], WriteMode::Command(0b0111_1111));
+}
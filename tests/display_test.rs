@@ -1,16 +1,28 @@
 extern crate clerk;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
+use std::rc::Rc;
 
-use clerk::{DefaultLines, Delay, Display, DisplayControlBuilder, EntryModeBuilder,
-            FunctionSetBuilder, Init, ReadMode, Receive, SeekCgRamFrom, SeekFrom, Send, ShiftTo,
-            WriteMode};
+use clerk::{DataLength, DefaultLines, Delay, Display, DisplayControlBuilder, EntryModeBuilder,
+            Error, ErrorType, FunctionSetBuilder, Init, LineNumber, ReadMode, Receive,
+            SeekCgRamFrom, SeekFrom, Send, ShiftTo, Wait, WriteMode};
 
 struct ConnectionMock {
     init_calls: RefCell<u8>,
     send_bytes: RefCell<Vec<WriteMode>>,
     receivable_bytes: RefCell<VecDeque<u8>>,
+    waits: RefCell<Vec<Waited>>,
+    lost: Rc<Cell<bool>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct DeviceLost;
+
+#[derive(Debug, PartialEq)]
+enum Waited {
+    Execution(WriteMode),
+    Microseconds(u32),
 }
 
 impl Default for ConnectionMock {
@@ -19,6 +31,8 @@ impl Default for ConnectionMock {
             init_calls: RefCell::new(0),
             send_bytes: RefCell::new(vec![]),
             receivable_bytes: RefCell::new(VecDeque::new()),
+            waits: RefCell::new(vec![]),
+            lost: Rc::new(Cell::new(false)),
         }
     }
 }
@@ -29,32 +43,60 @@ impl ConnectionMock {
     }
 }
 
+impl ErrorType for ConnectionMock {
+    type Error = DeviceLost;
+}
+
 impl Init for ConnectionMock {
-    fn init(&self) {
+    fn init(&self) -> Result<(), DeviceLost> {
         let mut init_calls = self.init_calls.borrow_mut();
 
         *init_calls += 1;
+
+        Ok(())
     }
 }
 
 impl Send for ConnectionMock {
-    fn send(&self, mode: WriteMode) {
+    fn send(&self, mode: WriteMode) -> Result<(), DeviceLost> {
+        if self.lost.get() {
+            return Err(DeviceLost);
+        }
+
         let mut send_bytes = self.send_bytes.borrow_mut();
 
         send_bytes.push(mode);
+
+        Ok(())
     }
 }
 
 impl Receive for ConnectionMock {
-    fn receive(&self, _: ReadMode) -> u8 {
-        self.receivable_bytes.borrow_mut().pop_front().unwrap()
+    fn receive(&self, _: ReadMode) -> Result<u8, DeviceLost> {
+        if self.lost.get() {
+            return Err(DeviceLost);
+        }
+
+        Ok(self.receivable_bytes.borrow_mut().pop_front().unwrap())
+    }
+}
+
+impl Wait for ConnectionMock {
+    fn wait(&self, mode: WriteMode) -> Result<(), DeviceLost> {
+        self.waits.borrow_mut().push(Waited::Execution(mode));
+        Ok(())
+    }
+
+    fn wait_us(&self, us: u32) -> Result<(), DeviceLost> {
+        self.waits.borrow_mut().push(Waited::Microseconds(us));
+        Ok(())
     }
 }
 
 pub struct CustomDelayMock;
 
 impl Delay for CustomDelayMock {
-    fn delay_ns(_: u16) {
+    fn delay_ns(&self, _: u32) {
         // mhh
     }
 }
@@ -67,25 +109,78 @@ fn setup_display() -> Display<ConnectionMock, DefaultLines> {
 fn init() {
     let lcd = setup_display();
 
-    lcd.init(&FunctionSetBuilder::default());
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
 
     let connection = lcd.get_connection();
 
     let init_calls = connection.init_calls.borrow_mut();
     assert_eq!(*init_calls, 1);
 
+    // the mock sends the upper nibbles as whole commands
     let send_bytes = connection.send_bytes.borrow_mut();
-    assert_eq!(send_bytes[0], WriteMode::Command(0x33));
-    assert_eq!(send_bytes[1], WriteMode::Command(0x32));
-    assert_eq!(send_bytes[2], WriteMode::Command(0x20));
-    assert_eq!(send_bytes[3], WriteMode::Command(0x01));
+    assert_eq!(
+        *send_bytes,
+        vec![
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x20),
+            WriteMode::Command(0x20),
+            WriteMode::Command(0x01),
+        ]
+    );
+}
+
+#[test]
+fn init_8bit() {
+    let lcd = setup_display();
+
+    let mut builder = FunctionSetBuilder::default();
+    builder
+        .set_data_length(DataLength::EightBit)
+        .set_line_number(LineNumber::Two);
+    lcd.init(&builder).unwrap();
+
+    let send_bytes = lcd.get_connection().send_bytes.into_inner();
+    assert_eq!(
+        send_bytes,
+        vec![
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x30),
+            WriteMode::Command(0x38),
+            WriteMode::Command(0x01),
+        ]
+    );
+}
+
+#[test]
+fn init_waits() {
+    let lcd = setup_display();
+
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    let connection = lcd.get_connection();
+    let waits = connection.waits.borrow_mut();
+    assert_eq!(
+        *waits,
+        vec![
+            Waited::Microseconds(40_000),
+            Waited::Microseconds(4_100),
+            Waited::Microseconds(100),
+            Waited::Microseconds(100),
+            Waited::Microseconds(100),
+            Waited::Execution(WriteMode::Command(0x20)),
+            Waited::Execution(WriteMode::Command(0x01)),
+        ]
+    );
 }
 
 #[test]
 fn set_entry_mode() {
     let lcd = setup_display();
 
-    lcd.set_entry_mode(&EntryModeBuilder::default());
+    lcd.set_entry_mode(&EntryModeBuilder::default()).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -96,7 +191,7 @@ fn set_entry_mode() {
 fn test_set_display_control() {
     let lcd = setup_display();
 
-    lcd.set_display_control(&DisplayControlBuilder::default());
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -107,7 +202,7 @@ fn test_set_display_control() {
 fn test_shift_cursor_left() {
     let mut lcd = setup_display();
 
-    lcd.shift_cursor(ShiftTo::Left(1));
+    lcd.shift_cursor(ShiftTo::Left(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -118,7 +213,7 @@ fn test_shift_cursor_left() {
 fn test_shift_cursor_left_with_zero_offset() {
     let mut lcd = setup_display();
 
-    lcd.shift_cursor(ShiftTo::Left(0));
+    lcd.shift_cursor(ShiftTo::Left(0)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -129,7 +224,7 @@ fn test_shift_cursor_left_with_zero_offset() {
 fn test_shift_cursor_right() {
     let mut lcd = setup_display();
 
-    lcd.shift_cursor(ShiftTo::Right(1));
+    lcd.shift_cursor(ShiftTo::Right(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -140,7 +235,7 @@ fn test_shift_cursor_right() {
 fn test_shift_cursor_right_multiple() {
     let mut lcd = setup_display();
 
-    lcd.shift_cursor(ShiftTo::Right(2));
+    lcd.shift_cursor(ShiftTo::Right(2)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -152,7 +247,7 @@ fn test_shift_cursor_right_multiple() {
 fn test_shift_cursor_right_with_zero_offset() {
     let mut lcd = setup_display();
 
-    lcd.shift_cursor(ShiftTo::Right(0));
+    lcd.shift_cursor(ShiftTo::Right(0)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -163,7 +258,7 @@ fn test_shift_cursor_right_with_zero_offset() {
 fn test_shift_left() {
     let lcd = setup_display();
 
-    lcd.shift(ShiftTo::Left(1));
+    lcd.shift(ShiftTo::Left(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -174,7 +269,7 @@ fn test_shift_left() {
 fn test_shift_right() {
     let lcd = setup_display();
 
-    lcd.shift(ShiftTo::Right(1));
+    lcd.shift(ShiftTo::Right(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -185,18 +280,29 @@ fn test_shift_right() {
 fn test_clear() {
     let lcd = setup_display();
 
-    lcd.clear();
+    lcd.clear().unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
     assert_eq!(send_bytes[0], WriteMode::Command(0x01));
 }
 
+#[test]
+fn test_clear_waits_for_execution() {
+    let lcd = setup_display();
+
+    lcd.clear().unwrap();
+
+    let connection = lcd.get_connection();
+    let waits = connection.waits.borrow_mut();
+    assert_eq!(*waits, vec![Waited::Execution(WriteMode::Command(0x01))]);
+}
+
 #[test]
 fn test_seek_from_home() {
     let mut lcd = setup_display();
 
-    lcd.seek(SeekFrom::Home(3));
+    lcd.seek(SeekFrom::Home(3)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -207,8 +313,8 @@ fn test_seek_from_home() {
 fn test_seek_from_current() {
     let mut lcd = setup_display();
 
-    lcd.seek(SeekFrom::Home(2));
-    lcd.seek(SeekFrom::Current(1));
+    lcd.seek(SeekFrom::Home(2)).unwrap();
+    lcd.seek(SeekFrom::Current(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -223,7 +329,7 @@ fn test_seek_from_line() {
     lcd.seek(SeekFrom::Line {
         line: DefaultLines::Two,
         offset: 3,
-    });
+    }).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -234,7 +340,7 @@ fn test_seek_from_line() {
 fn test_set_cgram_address_from_home() {
     let lcd = setup_display();
 
-    let lcd = lcd.set_cgram_address(3);
+    let lcd = lcd.set_cgram_address(3).ok().unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -245,8 +351,8 @@ fn test_set_cgram_address_from_home() {
 fn test_seek_cgram_from_current() {
     let lcd = setup_display();
 
-    let mut lcd = lcd.set_cgram_address(2);
-    lcd.seek(SeekCgRamFrom::Current(1));
+    let mut lcd = lcd.set_cgram_address(2).ok().unwrap();
+    lcd.seek(SeekCgRamFrom::Current(1)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -258,7 +364,7 @@ fn test_seek_cgram_from_current() {
 fn test_write() {
     let mut lcd = setup_display();
 
-    lcd.write(123);
+    lcd.write(123).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -269,10 +375,10 @@ fn test_write() {
 fn test_write_updates_address_counter() {
     let mut lcd = setup_display();
 
-    lcd.seek(SeekFrom::Home(0));
-    lcd.write(12);
-    lcd.write(34);
-    lcd.seek(SeekFrom::Current(0));
+    lcd.seek(SeekFrom::Home(0)).unwrap();
+    lcd.write(12).unwrap();
+    lcd.write(34).unwrap();
+    lcd.seek(SeekFrom::Current(0)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -283,7 +389,7 @@ fn test_write_updates_address_counter() {
 fn test_write_message() {
     let mut lcd = setup_display();
 
-    lcd.write_message("Hi");
+    lcd.write_message("Hi").unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -295,8 +401,8 @@ fn test_write_message() {
 fn test_write_message_increments_address_counter() {
     let mut lcd = setup_display();
 
-    lcd.write_message("Hi");
-    lcd.seek(SeekFrom::Current(0));
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Current(0)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
@@ -312,7 +418,7 @@ fn test_read() {
 
     let mut lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
     let input = lcd.read_byte();
-    assert_eq!(input, expected);
+    assert_eq!(input, Ok(expected));
 }
 
 #[test]
@@ -324,14 +430,83 @@ fn test_read_increments_address_counter() {
 
     let mut lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
 
-    lcd.read_byte();
-    lcd.seek(SeekFrom::Current(0));
+    lcd.read_byte().unwrap();
+    lcd.seek(SeekFrom::Current(0)).unwrap();
 
-    lcd.read_byte();
-    lcd.seek(SeekFrom::Current(0));
+    lcd.read_byte().unwrap();
+    lcd.seek(SeekFrom::Current(0)).unwrap();
 
     let connection = lcd.get_connection();
     let send_bytes = connection.send_bytes.borrow_mut();
     assert_eq!(send_bytes[0], WriteMode::Command(0b1000_0001));
     assert_eq!(send_bytes[1], WriteMode::Command(0b1000_0010));
 }
+
+#[test]
+fn test_read_line_waits_after_every_read() {
+    let connection = ConnectionMock::default();
+    for &byte in b"Hello" {
+        connection.set_read_value(byte);
+    }
+
+    let mut lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
+    lcd.set_geometry(5, 2);
+
+    let mut line = [0; 5];
+    assert_eq!(lcd.read_line(1, &mut line), Ok(5));
+    assert_eq!(&line, b"Hello");
+
+    let connection = lcd.get_connection();
+    let data_waits = connection
+        .waits
+        .borrow()
+        .iter()
+        .filter(|waited| matches!(waited, Waited::Execution(WriteMode::Data(_))))
+        .count();
+    assert_eq!(data_waits, 5);
+}
+
+#[test]
+fn test_write_reports_lost_connection() {
+    let connection = ConnectionMock::default();
+    connection.lost.set(true);
+
+    let mut lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
+
+    assert_eq!(lcd.write(b'a'), Err(Error::Connection(DeviceLost)));
+    assert_eq!(lcd.read_byte(), Err(Error::Connection(DeviceLost)));
+}
+
+#[test]
+fn test_failed_write_keeps_address_counter() {
+    let connection = ConnectionMock::default();
+    let lost = Rc::clone(&connection.lost);
+
+    let mut lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
+
+    lost.set(true);
+    assert!(lcd.write(b'a').is_err());
+
+    lost.set(false);
+    lcd.seek(SeekFrom::Current(0)).unwrap();
+
+    let connection = lcd.get_connection();
+    let send_bytes = connection.send_bytes.borrow_mut();
+    assert_eq!(send_bytes[0], WriteMode::Command(0b1000_0000));
+}
+
+#[test]
+fn test_failed_set_cgram_address_returns_display() {
+    let connection = ConnectionMock::default();
+    connection.lost.set(true);
+
+    let lcd: Display<ConnectionMock, DefaultLines> = Display::new(connection);
+
+    match lcd.set_cgram_address(3) {
+        Ok(_) => panic!("the connection is lost"),
+        Err((lcd, e)) => {
+            assert_eq!(e, Error::Connection(DeviceLost));
+            assert!(lcd.get_connection().send_bytes.borrow().is_empty());
+        }
+    }
+}
@@ -0,0 +1,294 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use clerk::{BusyFlagPolling, Controller, CursorState, DataPins4Lines, Delay, Direction,
+            DisplayControlBuilder, DisplayHardwareLayer, DualControllerDisplay,
+            DualControllerLines, DualEnable, ErrorType, FunctionSetBuilder, Grounded, Init, Level,
+            LineNumber, Pins, ReadMode, Receive, SeekCgRamFrom, SeekFrom, SelectController, Send,
+            SetFrom, Wait, WriteMode};
+
+#[derive(Default)]
+struct ConnectionMock {
+    selected: Cell<Option<Controller>>,
+    init_calls: Cell<u8>,
+    send_bytes: RefCell<Vec<(Controller, WriteMode)>>,
+}
+
+impl ConnectionMock {
+    fn selected(&self) -> Controller {
+        self.selected.get().unwrap_or(Controller::First)
+    }
+}
+
+impl ErrorType for ConnectionMock {
+    type Error = ();
+}
+
+impl SelectController for ConnectionMock {
+    fn select_controller(&self, controller: Controller) {
+        self.selected.set(Some(controller));
+    }
+}
+
+impl Init for ConnectionMock {
+    fn init(&self) -> Result<(), ()> {
+        self.init_calls.set(self.init_calls.get() + 1);
+        Ok(())
+    }
+}
+
+impl Send for ConnectionMock {
+    fn send(&self, mode: WriteMode) -> Result<(), ()> {
+        self.send_bytes.borrow_mut().push((self.selected(), mode));
+        Ok(())
+    }
+}
+
+impl Receive for ConnectionMock {
+    fn receive(&self, _: ReadMode) -> Result<u8, ()> {
+        assert_ne!(self.selected(), Controller::Both);
+        Ok(0)
+    }
+}
+
+impl Wait for ConnectionMock {
+    fn wait(&self, _: WriteMode) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn wait_us(&self, _: u32) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+fn two_lines() -> FunctionSetBuilder {
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    builder
+}
+
+#[test]
+fn init_and_clear_go_to_both_controllers() {
+    let mut lcd = DualControllerDisplay::new(ConnectionMock::default());
+
+    lcd.init(&two_lines()).unwrap();
+
+    let connection = lcd.get_connection();
+    assert_eq!(connection.init_calls.get(), 1);
+    assert_eq!(connection.selected(), Controller::First);
+
+    let send_bytes = connection.send_bytes.into_inner();
+    assert!(send_bytes.iter().all(|&(c, _)| c == Controller::Both));
+    assert_eq!(
+        send_bytes.last(),
+        Some(&(Controller::Both, WriteMode::Command(0x01)))
+    );
+}
+
+#[test]
+fn seek_routes_lines_to_controllers() {
+    let mut lcd = DualControllerDisplay::new(ConnectionMock::default());
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Three,
+        offset: 2,
+    }).unwrap();
+    lcd.write(b'a').unwrap();
+    assert_eq!(lcd.active_controller(), Controller::Second);
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Two,
+        offset: 0,
+    }).unwrap();
+    lcd.write(b'b').unwrap();
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Four,
+        offset: 1,
+    }).unwrap();
+    lcd.seek(SeekFrom::Current(1)).unwrap();
+    lcd.write(b'c').unwrap();
+
+    let send_bytes = lcd.get_connection().send_bytes.into_inner();
+    assert_eq!(
+        send_bytes,
+        vec![
+            (Controller::Second, WriteMode::Command(0b1000_0010)),
+            (Controller::Second, WriteMode::Data(b'a')),
+            (Controller::First, WriteMode::Command(0b1100_0000)),
+            (Controller::First, WriteMode::Data(b'b')),
+            (Controller::Second, WriteMode::Command(0b1100_0001)),
+            (Controller::Second, WriteMode::Command(0b1100_0010)),
+            (Controller::Second, WriteMode::Data(b'c')),
+        ]
+    );
+}
+
+#[test]
+fn busy_flag_polling_selects_controller() {
+    let mut lcd = DualControllerDisplay::new(BusyFlagPolling::new(ConnectionMock::default()));
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Three,
+        offset: 0,
+    }).unwrap();
+    lcd.write(b'a').unwrap();
+
+    let send_bytes = lcd.get_connection().into_inner().send_bytes.into_inner();
+    assert_eq!(send_bytes.last(), Some(&(Controller::Second, WriteMode::Data(b'a'))));
+}
+
+#[test]
+fn cursor_is_only_visible_on_active_controller() {
+    let mut lcd = DualControllerDisplay::new(ConnectionMock::default());
+
+    let mut display_control = DisplayControlBuilder::default();
+    display_control.set_cursor(CursorState::On);
+    lcd.set_display_control(&display_control).unwrap();
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Three,
+        offset: 0,
+    }).unwrap();
+
+    lcd.clear().unwrap();
+    assert_eq!(lcd.active_controller(), Controller::First);
+
+    let send_bytes = lcd.get_connection().send_bytes.into_inner();
+    assert_eq!(
+        send_bytes,
+        vec![
+            (Controller::Second, WriteMode::Command(0b0000_1100)),
+            (Controller::First, WriteMode::Command(0b0000_1110)),
+            (Controller::First, WriteMode::Command(0b0000_1100)),
+            (Controller::Second, WriteMode::Command(0b0000_1110)),
+            (Controller::Second, WriteMode::Command(0b1000_0000)),
+            (Controller::Both, WriteMode::Command(0x01)),
+            (Controller::Second, WriteMode::Command(0b0000_1100)),
+            (Controller::First, WriteMode::Command(0b0000_1110)),
+        ]
+    );
+}
+
+#[test]
+fn cgram_is_written_to_both_controllers() {
+    let mut lcd = DualControllerDisplay::new(ConnectionMock::default());
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Four,
+        offset: 0,
+    }).unwrap();
+
+    let mut lcd = lcd.set_cgram_address(8).ok().unwrap();
+    lcd.write(0b1_1111).unwrap();
+    lcd.seek(SeekCgRamFrom::Current(2)).unwrap();
+
+    let mut lcd = lcd.set_ddram_address(SetFrom::Line {
+        line: DualControllerLines::Three,
+        offset: 4,
+    }).ok()
+        .unwrap();
+    lcd.write(0x01).unwrap();
+    assert_eq!(lcd.read_byte(), Ok(0));
+
+    let send_bytes = lcd.get_connection().send_bytes.into_inner();
+    assert_eq!(
+        send_bytes,
+        vec![
+            (Controller::Second, WriteMode::Command(0b1100_0000)),
+            (Controller::Both, WriteMode::Command(0b0100_1000)),
+            (Controller::Both, WriteMode::Data(0b1_1111)),
+            (Controller::Both, WriteMode::Command(0b0100_1011)),
+            (Controller::Both, WriteMode::Command(0b1000_0100)),
+            (Controller::Second, WriteMode::Data(0x01)),
+        ]
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pin {
+    Enable1,
+    Enable2,
+    Other,
+}
+
+struct PinMock {
+    pin: Pin,
+    pulses: Rc<RefCell<Vec<Pin>>>,
+}
+
+impl PinMock {
+    fn new(pin: Pin, pulses: &Rc<RefCell<Vec<Pin>>>) -> Self {
+        PinMock {
+            pin,
+            pulses: pulses.clone(),
+        }
+    }
+}
+
+impl DisplayHardwareLayer for PinMock {
+    type Error = ();
+
+    fn set_direction(&self, _: Direction) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), ()> {
+        if self.pin != Pin::Other && level == Level::High {
+            self.pulses.borrow_mut().push(self.pin);
+        }
+
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, ()> {
+        Ok(0)
+    }
+}
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+#[test]
+fn dual_enable_pulses_selected_controller() {
+    let pulses = Rc::new(RefCell::new(vec![]));
+
+    let pins = Pins {
+        register_select: PinMock::new(Pin::Other, &pulses),
+        read: Grounded,
+        enable: DualEnable::new(
+            PinMock::new(Pin::Enable1, &pulses),
+            PinMock::new(Pin::Enable2, &pulses),
+        ),
+        data: DataPins4Lines {
+            data4: PinMock::new(Pin::Other, &pulses),
+            data5: PinMock::new(Pin::Other, &pulses),
+            data6: PinMock::new(Pin::Other, &pulses),
+            data7: PinMock::new(Pin::Other, &pulses),
+        },
+    };
+    let mut lcd = DualControllerDisplay::new(pins.into_connection(DelayMock));
+
+    lcd.seek(SeekFrom::Line {
+        line: DualControllerLines::Three,
+        offset: 0,
+    }).unwrap();
+    lcd.clear().unwrap();
+
+    // every byte is sent as two nibbles
+    assert_eq!(
+        *pulses.borrow(),
+        vec![
+            Pin::Enable2,
+            Pin::Enable2,
+            Pin::Enable1,
+            Pin::Enable2,
+            Pin::Enable1,
+            Pin::Enable2,
+        ]
+    );
+}
@@ -0,0 +1,126 @@
+extern crate clerk;
+
+use core::fmt::Write;
+
+use clerk::{BusSimulator, DefaultLines, Delay, Display, DisplayControlBuilder,
+            FunctionSetBuilder, LineNumber, LineOverflow, SeekFrom, Send, Unencodable, Wait};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn setup_display(bus: &BusSimulator) -> Display<impl Send + Wait + '_, DefaultLines> {
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    lcd.init(&builder).unwrap();
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+
+    lcd
+}
+
+fn ddram(bus: &BusSimulator, address: u8, len: u8) -> Vec<u8> {
+    (address..address + len).map(|a| bus.ddram(a)).collect()
+}
+
+#[test]
+fn write_formatted_text() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+
+    write!(lcd, "T={:.1}°C", 21.456).unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 8), b"T=21.5\xdfC");
+}
+
+#[test]
+fn unencodable_characters() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+
+    write!(lcd, "a~b").unwrap();
+    lcd.set_unencodable(Unencodable::Substitute(0xff));
+    write!(lcd, "€").unwrap();
+    lcd.set_unencodable(Unencodable::Skip);
+    write!(lcd, "c\\d").unwrap();
+    lcd.set_unencodable(Unencodable::Fail);
+    assert!(write!(lcd, "e☃f").is_err());
+
+    assert_eq!(ddram(&bus, 0x00, 8), b"a?b\xffcde ");
+}
+
+#[test]
+fn truncate_at_end_of_line() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+
+    write!(lcd, "0123456789abcdefXYZ\nline 2").unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 17), b"0123456789abcdef ");
+    assert_eq!(ddram(&bus, 0x40, 7), b"line 2 ");
+}
+
+#[test]
+fn truncate_on_four_lines() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+    lcd.set_geometry(20, 4);
+
+    write!(lcd, "{:25}|\r>", "first").unwrap();
+    assert_eq!(ddram(&bus, 0x00, 6), b">irst ");
+    assert_eq!(ddram(&bus, 20, 6), b"      ");
+
+    lcd.seek(SeekFrom::Home(20)).unwrap();
+    write!(lcd, "third\nfourth\n1st").unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 6), b"1stst ");
+    assert_eq!(ddram(&bus, 20, 6), b"third ");
+    assert_eq!(ddram(&bus, 0x40 + 20, 7), b"fourth ");
+    assert_eq!(bus.ddram(0x40), b' ');
+}
+
+#[test]
+fn wrap_on_four_lines() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+    lcd.set_geometry(20, 4);
+    lcd.set_line_overflow(LineOverflow::Wrap);
+
+    write!(lcd, "{:020}wraps", 0).unwrap();
+    lcd.seek(SeekFrom::Home(0x40 + 20 + 18)).unwrap();
+    write!(lcd, "endstart").unwrap();
+
+    assert_eq!(ddram(&bus, 0x40, 6), b"wraps ");
+    assert_eq!(ddram(&bus, 20, 1), b" ");
+    assert_eq!(ddram(&bus, 0x40 + 38, 2), b"en");
+    assert_eq!(ddram(&bus, 0x00, 7), b"dstart0");
+}
+
+#[test]
+fn write_message_respects_geometry() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+    lcd.set_geometry(20, 4);
+
+    lcd.write_message("0123456789abcdefghijXYZ").unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 21), b"0123456789abcdefghij ");
+}
+
+#[test]
+fn clear_resets_cursor() {
+    let bus = BusSimulator::new();
+    let mut lcd = setup_display(&bus);
+
+    write!(lcd, "0123456789abcdefXYZ").unwrap();
+    lcd.clear().unwrap();
+    write!(lcd, "ok").unwrap();
+    lcd.seek(SeekFrom::Current(1)).unwrap();
+    lcd.write(b'!').unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 4), b"ok !");
+}
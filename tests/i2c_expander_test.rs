@@ -0,0 +1,336 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use clerk::{DefaultLines, Delay, Display, Error, ExpanderPins, FunctionSetBuilder, I2cBus,
+            I2cExpanderConnection, Init, PinError, ReadMode, Receive, SeekFrom, Send,
+            WriteMode};
+
+const ADDRESS: u8 = 0x27;
+
+/// A fake PCF8574 with an attached display, which latches nibbles on the falling edge of the
+/// enable signal.
+struct BusMock {
+    pins: ExpanderPins,
+    port: Cell<u8>,
+    written: RefCell<Vec<u8>>,
+    pending_nibble: Cell<Option<u8>>,
+    send_bytes: RefCell<Vec<WriteMode>>,
+    receivable_bytes: RefCell<VecDeque<u8>>,
+    receivable_nibbles: RefCell<VecDeque<u8>>,
+    disconnected: Cell<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Nack;
+
+impl BusMock {
+    fn new(pins: ExpanderPins) -> Self {
+        BusMock {
+            pins,
+            port: Cell::new(0),
+            written: RefCell::new(vec![]),
+            pending_nibble: Cell::new(None),
+            send_bytes: RefCell::new(vec![]),
+            receivable_bytes: RefCell::new(VecDeque::new()),
+            receivable_nibbles: RefCell::new(VecDeque::new()),
+            disconnected: Cell::new(false),
+        }
+    }
+
+    fn set_read_value(&self, value: u8) {
+        self.receivable_bytes.borrow_mut().push_back(value);
+    }
+
+    fn is_set(&self, port: u8, pin: u8) -> bool {
+        port & (1 << pin) != 0
+    }
+
+    fn nibble(&self, port: u8) -> u8 {
+        let p = &self.pins;
+
+        (self.is_set(port, p.data7) as u8) << 3 | (self.is_set(port, p.data6) as u8) << 2
+            | (self.is_set(port, p.data5) as u8) << 1 | (self.is_set(port, p.data4) as u8)
+    }
+
+    fn latch(&self, port: u8) {
+        let nibble = self.nibble(port);
+
+        match self.pending_nibble.take() {
+            None => self.pending_nibble.set(Some(nibble)),
+            Some(upper) => {
+                let value = upper << 4 | nibble;
+
+                let mode = if self.is_set(port, self.pins.register_select) {
+                    WriteMode::Data(value)
+                } else {
+                    WriteMode::Command(value)
+                };
+
+                self.send_bytes.borrow_mut().push(mode);
+            }
+        }
+    }
+
+    fn prepare_read(&self) {
+        let mut nibbles = self.receivable_nibbles.borrow_mut();
+
+        if nibbles.is_empty() {
+            let value = self.receivable_bytes.borrow_mut().pop_front().unwrap();
+
+            nibbles.push_back(value >> 4);
+            nibbles.push_back(value & 0x0f);
+        }
+    }
+}
+
+impl I2cBus for &BusMock {
+    type Error = Nack;
+
+    fn write_byte(&self, address: u8, byte: u8) -> Result<(), Nack> {
+        assert_eq!(address, ADDRESS);
+
+        if self.disconnected.get() {
+            return Err(Nack);
+        }
+
+        let previous = self.port.get();
+        let enable = self.pins.enable;
+        let read = self.pins.read;
+
+        let falling_edge = self.is_set(previous, enable) && !self.is_set(byte, enable);
+        let rising_edge = !self.is_set(previous, enable) && self.is_set(byte, enable);
+
+        if falling_edge && !self.is_set(byte, read) {
+            self.latch(byte);
+        }
+
+        if rising_edge && self.is_set(byte, read) {
+            self.prepare_read();
+        }
+
+        if falling_edge && self.is_set(byte, read) {
+            self.receivable_nibbles.borrow_mut().pop_front();
+        }
+
+        self.port.set(byte);
+        self.written.borrow_mut().push(byte);
+
+        Ok(())
+    }
+
+    fn read_byte(&self, address: u8) -> Result<u8, Nack> {
+        assert_eq!(address, ADDRESS);
+
+        if self.disconnected.get() {
+            return Err(Nack);
+        }
+
+        let port = self.port.get();
+        let nibble = *self.receivable_nibbles.borrow().front().unwrap();
+
+        let mut value = port & !(1 << self.pins.data4 | 1 << self.pins.data5
+            | 1 << self.pins.data6 | 1 << self.pins.data7);
+
+        for (i, pin) in [self.pins.data4, self.pins.data5, self.pins.data6, self.pins.data7]
+            .iter()
+            .enumerate()
+        {
+            if nibble & (1 << i) != 0 {
+                value |= 1 << pin;
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+pub struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn setup_connection(bus: &BusMock) -> I2cExpanderConnection<&BusMock, DelayMock> {
+    I2cExpanderConnection::new(bus, ADDRESS, bus.pins, DelayMock).unwrap()
+}
+
+#[test]
+fn init_switches_backlight_on() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b0000_1000);
+}
+
+#[test]
+fn send_command() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    let connection = setup_connection(&bus);
+
+    connection.send(WriteMode::Command(0x28)).unwrap();
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Command(0x28)]);
+
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b0010_1000);
+    assert_eq!(written[1], 0b0010_1100);
+    assert_eq!(written[2], 0b0010_1000);
+}
+
+#[test]
+fn send_data() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    let connection = setup_connection(&bus);
+
+    connection.send(WriteMode::Data(b'A')).unwrap();
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Data(b'A')]);
+}
+
+#[test]
+fn send_with_mjkdz_pins() {
+    let bus = BusMock::new(ExpanderPins::MJKDZ);
+    let connection = setup_connection(&bus);
+
+    connection.send(WriteMode::Data(0x5a)).unwrap();
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Data(0x5a)]);
+
+    // active low backlight is on, so P7 stays low
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b0100_0101);
+}
+
+#[test]
+fn set_backlight() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    let connection = setup_connection(&bus);
+
+    connection.set_backlight(false).unwrap();
+    connection.send(WriteMode::Command(0x01)).unwrap();
+
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b0000_0000);
+    assert!(written.iter().all(|port| port & 0b0000_1000 == 0));
+}
+
+#[test]
+fn receive_data() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    bus.set_read_value(0xa5);
+
+    let connection = setup_connection(&bus);
+
+    assert_eq!(connection.receive(ReadMode::Data), Ok(0xa5));
+
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b1111_1011);
+}
+
+#[test]
+fn receive_busy_flag() {
+    let bus = BusMock::new(ExpanderPins::MJKDZ);
+    bus.set_read_value(0x83);
+
+    let connection = setup_connection(&bus);
+
+    assert_eq!(connection.receive(ReadMode::BusyFlag), Ok(0x83));
+
+    let written = bus.written.borrow();
+    assert_eq!(written[0], 0b0010_1111);
+}
+
+#[test]
+fn display_init_sends_single_nibbles() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+
+    let lcd: Display<_, DefaultLines> = Display::new(setup_connection(&bus));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    // the mock pairs the init nibbles `3, 3, 3, 2` up to bytes
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(
+        *send_bytes,
+        vec![
+            WriteMode::Command(0x33),
+            WriteMode::Command(0x32),
+            WriteMode::Command(0x20),
+            WriteMode::Command(0x01),
+        ]
+    );
+    assert_eq!(bus.pending_nibble.get(), None);
+}
+
+#[test]
+fn display_write_message() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    bus.set_read_value(b'H');
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(setup_connection(&bus));
+
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Home(0)).unwrap();
+    let value = lcd.read_byte();
+
+    assert_eq!(value, Ok(b'H'));
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(send_bytes[0], WriteMode::Data(b'H'));
+    assert_eq!(send_bytes[1], WriteMode::Data(b'i'));
+    assert_eq!(send_bytes[2], WriteMode::Command(0b1000_0000));
+}
+
+#[test]
+fn display_reports_lost_expander() {
+    let bus = BusMock::new(ExpanderPins::GENERIC);
+    bus.disconnected.set(true);
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(setup_connection(&bus));
+
+    assert_eq!(lcd.write(b'H'), Err(Error::Connection(Nack)));
+    assert_eq!(lcd.read_byte(), Err(Error::Connection(Nack)));
+}
+
+#[test]
+fn display_set_backlight() {
+    let bus = BusMock::new(ExpanderPins::MJKDZ);
+
+    let lcd: Display<_, DefaultLines> = Display::new(setup_connection(&bus));
+
+    lcd.set_backlight(false).unwrap();
+    lcd.set_backlight(true).unwrap();
+
+    // the backlight of mjkdz backpacks is active low
+    let written = bus.written.borrow();
+    assert_eq!(*written, vec![0b1000_0000, 0b0000_0000]);
+}
+
+#[test]
+fn reject_pins_beyond_port() {
+    let pins = ExpanderPins {
+        backlight: 8,
+        ..ExpanderPins::GENERIC
+    };
+
+    let result = I2cExpanderConnection::new(BusMock::new(pins), ADDRESS, pins, DelayMock);
+    assert_eq!(result.err(), Some(PinError::OutOfRange(8)));
+}
+
+#[test]
+fn reject_duplicate_pins() {
+    let pins = ExpanderPins {
+        backlight: 4,
+        ..ExpanderPins::GENERIC
+    };
+
+    let result = I2cExpanderConnection::new(BusMock::new(pins), ADDRESS, pins, DelayMock);
+    assert_eq!(result.err(), Some(PinError::Duplicate(4)));
+}
@@ -0,0 +1,375 @@
+#![cfg(feature = "std")]
+
+extern crate clerk;
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+use clerk::{BusSimulator, DefaultLines, Delay, Display, DisplayControlBuilder,
+            FunctionSetBuilder, LcdProcServer, LineNumber, Send, Wait};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn setup_server(bus: &BusSimulator) -> LcdProcServer<impl Send + Wait + '_, DefaultLines> {
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    lcd.init(&builder).unwrap();
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+
+    LcdProcServer::bind("127.0.0.1:0", lcd, 16, 2).unwrap()
+}
+
+/// A LCDproc client, which drives the server while waiting for its replies.
+struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl Client {
+    fn connect<P: Send + Wait>(server: &mut LcdProcServer<P, DefaultLines>) -> Self {
+        let stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        stream.set_nonblocking(true).unwrap();
+
+        Client {
+            stream,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Connects to the server and sends `hello`.
+    fn greet<P: Send + Wait>(server: &mut LcdProcServer<P, DefaultLines>) -> Self {
+        let mut client = Client::connect(server);
+        assert!(client.request(server, "hello").starts_with("connect LCDproc"));
+
+        client
+    }
+
+    fn request<P: Send + Wait>(
+        &mut self,
+        server: &mut LcdProcServer<P, DefaultLines>,
+        command: &str,
+    ) -> String {
+        self.stream.write_all(format!("{}\n", command).as_bytes()).unwrap();
+
+        self.receive(server)
+    }
+
+    fn receive<P: Send + Wait>(&mut self, server: &mut LcdProcServer<P, DefaultLines>) -> String {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return String::from_utf8(line[..end].to_vec()).unwrap();
+            }
+
+            server.handle_clients().unwrap();
+
+            let mut chunk = [0; 256];
+            match self.stream.read(&mut chunk) {
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(ref e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+    }
+
+    fn send_all<P: Send + Wait>(
+        &mut self,
+        server: &mut LcdProcServer<P, DefaultLines>,
+        commands: &[&str],
+    ) {
+        for command in commands {
+            assert_eq!(self.request(server, command), "success", "{}", command);
+        }
+    }
+}
+
+fn line(bus: &BusSimulator, row: u8) -> Vec<u8> {
+    let start = if row == 0 { 0x00 } else { 0x40 };
+    (start..start + 16).map(|a| bus.ddram(a)).collect()
+}
+
+#[test]
+fn hello_and_errors() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::connect(&mut server);
+
+    assert_eq!(
+        client.request(&mut server, "screen_add s"),
+        "huh? Please send hello first"
+    );
+    assert_eq!(
+        client.request(&mut server, "hello"),
+        "connect LCDproc 0.5.9 protocol 0.3 lcd wid 16 hgt 2 cellwid 5 cellhgt 8"
+    );
+    assert_eq!(
+        client.request(&mut server, "client_set -name test"),
+        "success"
+    );
+    assert_eq!(
+        client.request(&mut server, "frobnicate"),
+        "huh? Invalid command \"frobnicate\""
+    );
+    assert_eq!(
+        client.request(&mut server, "widget_add s w string"),
+        "huh? Invalid screen id \"s\""
+    );
+    assert_eq!(client.request(&mut server, "screen_add s"), "success");
+    assert_eq!(
+        client.request(&mut server, "widget_add s w clock"),
+        "huh? Invalid widget type \"clock\""
+    );
+    assert_eq!(client.request(&mut server, "widget_add s w string"), "success");
+    assert_eq!(
+        client.request(&mut server, "widget_set s w 1 1"),
+        "huh? Wrong number of arguments"
+    );
+}
+
+#[test]
+fn render_string_and_title() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(
+        &mut server,
+        &[
+            "screen_add s",
+            "widget_add s t title",
+            "widget_set s t Load",
+            "widget_add s w string",
+            "widget_set s w 3 2 \"1.5 \\\"avg\\\"\"",
+        ],
+    );
+    server.tick().unwrap();
+
+    assert_eq!(client.receive(&mut server), "listen s");
+    assert_eq!(line(&bus, 0), b"## Load ########");
+    assert_eq!(line(&bus, 1), b"  1.5 \"avg\"     ");
+
+    client.send_all(&mut server, &["widget_set s w 3 2 {up 3 days}"]);
+    server.tick().unwrap();
+
+    assert_eq!(line(&bus, 1), b"  up 3 days     ");
+}
+
+#[test]
+fn render_bars_and_icons() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(
+        &mut server,
+        &[
+            "screen_add s",
+            "widget_add s h hbar",
+            "widget_set s h 1 1 12",
+            "widget_add s v vbar",
+            "widget_set s v 16 2 12",
+            "widget_add s i icon",
+            "widget_set s i 10 2 HEART_FILLED",
+        ],
+    );
+    server.tick().unwrap();
+
+    assert_eq!(&line(&bus, 0)[..4], &[0xff, 0xff, 0x01, b' ']);
+    assert_eq!(bus.ddram(0x40 + 9), 0x07);
+    assert_eq!(bus.ddram(0x40 + 15), 0xff);
+    assert_eq!(bus.ddram(0x0f), 0x05);
+
+    // two columns of an hbar cell, four rows of a vbar cell
+    assert_eq!(bus.cgram(0x08), 0b1_1000);
+    assert_eq!(bus.cgram(0x2b), 0b0_0000);
+    assert_eq!(bus.cgram(0x2c), 0b1_1111);
+}
+
+#[test]
+fn scroll_horizontally() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(
+        &mut server,
+        &[
+            "screen_add s",
+            "widget_add s w scroller",
+            "widget_set s w 1 1 4 1 h 2 abcdef",
+        ],
+    );
+
+    let mut windows = vec![];
+    for _ in 0..6 {
+        server.tick().unwrap();
+        windows.push(line(&bus, 0)[..4].to_vec());
+    }
+
+    assert_eq!(
+        windows,
+        vec![
+            b"abcd".to_vec(),
+            b"abcd".to_vec(),
+            b"bcde".to_vec(),
+            b"bcde".to_vec(),
+            b"cdef".to_vec(),
+            b"cdef".to_vec(),
+        ]
+    );
+}
+
+#[test]
+fn rotate_screens_by_priority() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(
+        &mut server,
+        &[
+            "screen_add a",
+            "screen_set a -duration 2",
+            "widget_add a w string",
+            "widget_set a w 1 1 first",
+            "screen_add b",
+            "screen_set b -duration 2",
+            "widget_add b w string",
+            "widget_set b w 1 1 second",
+            "screen_add c",
+            "screen_set c -priority background",
+            "widget_add c w string",
+            "widget_set c w 1 1 background",
+        ],
+    );
+
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..6], b"first ");
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..6], b"first ");
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..6], b"second");
+    server.tick().unwrap();
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..6], b"first ");
+
+    assert_eq!(client.receive(&mut server), "listen a");
+    assert_eq!(client.receive(&mut server), "ignore a");
+    assert_eq!(client.receive(&mut server), "listen b");
+    assert_eq!(client.receive(&mut server), "ignore b");
+    assert_eq!(client.receive(&mut server), "listen a");
+
+    client.send_all(
+        &mut server,
+        &["screen_set a -priority hidden", "screen_set b -priority hidden"],
+    );
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..10], b"background");
+    assert_eq!(client.receive(&mut server), "ignore a");
+    assert_eq!(client.receive(&mut server), "listen c");
+
+    client.send_all(&mut server, &["screen_set b -priority alert"]);
+    server.tick().unwrap();
+    assert_eq!(&line(&bus, 0)[..10], b"second    ");
+    assert_eq!(client.receive(&mut server), "ignore c");
+    assert_eq!(client.receive(&mut server), "listen b");
+}
+
+#[test]
+fn disconnect_removes_screens() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+
+    {
+        let mut client = Client::greet(&mut server);
+        client.send_all(
+            &mut server,
+            &["screen_add s", "widget_add s w string", "widget_set s w 1 1 hi"],
+        );
+        server.tick().unwrap();
+        assert_eq!(&line(&bus, 0)[..3], b"hi ");
+
+        client.stream.write_all(b"bye\n").unwrap();
+    }
+
+    server.handle_clients().unwrap();
+    server.tick().unwrap();
+
+    assert_eq!(line(&bus, 0), b"LCDproc Server  ");
+    assert_eq!(line(&bus, 1), b"Clients: 0      ");
+}
+
+#[test]
+fn clip_extreme_coordinates() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(
+        &mut server,
+        &[
+            "screen_add s",
+            "widget_add s a string",
+            "widget_set s a 2147483647 1 ab",
+            "widget_add s b string",
+            "widget_set s b -2147483648 -2147483648 ab",
+            "widget_add s h hbar",
+            "widget_set s h -2147483648 2 2147483647",
+            "widget_add s v vbar",
+            "widget_set s v 16 -2147483648 2147483647",
+            "widget_add s c scroller",
+            "widget_set s c -2147483648 -2147483648 2147483647 2147483647 v 1 abc",
+            "widget_add s w string",
+            "widget_set s w 15 1 ok",
+        ],
+    );
+    server.tick().unwrap();
+
+    assert_eq!(line(&bus, 0), b"              ok");
+    assert_eq!(line(&bus, 1), b"                ");
+}
+
+#[test]
+fn close_client_sending_overlong_line() {
+    let bus = BusSimulator::new();
+    let mut server = setup_server(&bus);
+    let mut client = Client::greet(&mut server);
+
+    client.send_all(&mut server, &["screen_add s"]);
+    client.stream.write_all(&[b'x'; 9000]).unwrap();
+
+    loop {
+        server.handle_clients().unwrap();
+
+        let mut chunk = [0; 256];
+        match client.stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(_) => panic!("unexpected reply"),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+    }
+
+    server.tick().unwrap();
+
+    assert_eq!(line(&bus, 1), b"Clients: 0      ");
+}
+
+#[test]
+fn reject_unsupported_sizes() {
+    for &(width, height) in &[(0, 2), (16, 0), (20, 5)] {
+        let bus = BusSimulator::new();
+        let lcd: Display<_, DefaultLines> =
+            Display::new(bus.pins_4lines().into_connection(DelayMock));
+
+        let result = LcdProcServer::bind("127.0.0.1:0", lcd, width, height);
+        assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidInput));
+    }
+}
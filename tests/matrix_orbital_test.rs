@@ -0,0 +1,202 @@
+extern crate clerk;
+
+use std::cell::RefCell;
+
+use clerk::{BusSimulator, DefaultLines, Delay, Display, DisplayControlBuilder,
+            FunctionSetBuilder, LineNumber, MatrixOrbitalInterpreter, ModuleSetting, SeekFrom,
+            Send, SerialLcdConnection, SerialProtocol, SerialWrite, SetFrom, Wait};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn setup_interpreter(
+    bus: &BusSimulator,
+    columns: u8,
+) -> MatrixOrbitalInterpreter<impl Send + Wait + '_, DefaultLines> {
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    lcd.init(&builder).unwrap();
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+
+    MatrixOrbitalInterpreter::new(lcd, columns)
+}
+
+fn feed<P>(
+    interpreter: &mut MatrixOrbitalInterpreter<P, DefaultLines>,
+    bytes: &[u8],
+) -> Vec<ModuleSetting>
+where
+    P: Send + Wait,
+{
+    bytes
+        .iter()
+        .filter_map(|&byte| interpreter.feed(byte).unwrap())
+        .collect()
+}
+
+fn ddram(bus: &BusSimulator, address: u8, len: u8) -> Vec<u8> {
+    (address..address + len).map(|a| bus.ddram(a)).collect()
+}
+
+#[test]
+fn write_text_at_positions() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    feed(&mut interpreter, b"Hello\xfe\x47\x03\x02World\xfe\x48!");
+
+    assert_eq!(ddram(&bus, 0x00, 5), b"!ello");
+    assert_eq!(ddram(&bus, 0x42, 5), b"World");
+}
+
+#[test]
+fn clamp_column_beyond_display() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    feed(&mut interpreter, b"\xfe\x47\xff\x02x\xfe\x47\xff\x04y");
+
+    assert_eq!(bus.ddram(0x4f), b'x');
+    assert_eq!(bus.ddram(0x40 + 16 + 15), b'y');
+}
+
+#[test]
+fn four_lines() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 20);
+
+    feed(&mut interpreter, b"\xfe\x47\x01\x03three\xfe\x47\x02\x04four");
+
+    assert_eq!(ddram(&bus, 20, 5), b"three");
+    assert_eq!(ddram(&bus, 0x40 + 21, 4), b"four");
+}
+
+#[test]
+fn clear() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    feed(&mut interpreter, b"Hello\xfe\x58a");
+
+    assert_eq!(ddram(&bus, 0x00, 2), b"a ");
+}
+
+#[test]
+fn define_custom_character() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+    let rows = [0x00, 0x0a, 0x0a, 0x00, 0x11, 0x0e, 0x00, 0x00];
+
+    feed(&mut interpreter, b"A\xfe\x4e\x01");
+    feed(&mut interpreter, &rows);
+    feed(&mut interpreter, b"\x01B");
+
+    assert_eq!(ddram(&bus, 0x00, 3), b"A\x01B");
+    for (i, &row) in rows.iter().enumerate() {
+        assert_eq!(bus.cgram(8 + i as u8), row);
+    }
+}
+
+#[test]
+fn cursor_commands() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    feed(&mut interpreter, b"\xfe\x4a\xfe\x53");
+    assert_eq!(bus.display_control() & 0b0000_0111, 0b0000_0111);
+
+    feed(&mut interpreter, b"\xfe\x4bab\xfe\x4c\xfe\x4c\xfe\x4dc");
+    assert_eq!(bus.display_control() & 0b0000_0111, 0b0000_0101);
+    assert_eq!(ddram(&bus, 0x00, 2), b"ac");
+}
+
+#[test]
+fn module_settings() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    let settings = feed(
+        &mut interpreter,
+        b"\xfe\x42\x00\xfe\x46\xfe\x99\x80\xfe\x50\xc8\xfe\x57\x02\xfe\x56\x01",
+    );
+
+    assert_eq!(
+        settings,
+        vec![
+            ModuleSetting::Backlight(true),
+            ModuleSetting::Backlight(false),
+            ModuleSetting::Brightness(0x80),
+            ModuleSetting::Contrast(0xc8),
+            ModuleSetting::GeneralPurposeOutput {
+                output: 2,
+                enabled: true,
+            },
+            ModuleSetting::GeneralPurposeOutput {
+                output: 1,
+                enabled: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn unknown_commands_are_skipped() {
+    let bus = BusSimulator::new();
+    let mut interpreter = setup_interpreter(&bus, 16);
+
+    // auto scroll off and line wrap off, as sent by LCDproc
+    feed(&mut interpreter, b"\xfe\x52\xfe\x44ok");
+
+    let mut lcd = interpreter.into_display().ok().unwrap();
+    lcd.write(b'!').unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 3), b"ok!");
+}
+
+/// A serial port, which feeds the written bytes into an interpreter.
+struct Loopback<P>(RefCell<MatrixOrbitalInterpreter<P, DefaultLines>>);
+
+impl<P> SerialWrite for Loopback<P>
+where
+    P: Send + Wait,
+{
+    type Error = ();
+
+    fn write(&self, bytes: &[u8]) -> Result<(), ()> {
+        feed(&mut self.0.borrow_mut(), bytes);
+        Ok(())
+    }
+}
+
+#[test]
+fn loopback_from_serial_connection() {
+    let bus = BusSimulator::new();
+    let port = Loopback(RefCell::new(setup_interpreter(&bus, 16)));
+    let connection = SerialLcdConnection::new(port, SerialProtocol::MatrixOrbital, 16, DelayMock);
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(connection);
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 3,
+    })
+    .unwrap();
+    lcd.write(b'x').unwrap();
+
+    let mut lcd = lcd.set_cgram_address(0).ok().unwrap();
+    lcd.write(0x1f).unwrap();
+    let mut lcd = lcd.set_ddram_address(SetFrom::Home(5)).ok().unwrap();
+    lcd.write(0x00).unwrap();
+
+    assert_eq!(ddram(&bus, 0x00, 2), b"Hi");
+    assert_eq!(bus.ddram(0x43), b'x');
+    assert_eq!(bus.ddram(0x05), 0x00);
+    assert_eq!(bus.cgram(0x00), 0x1f);
+}
@@ -0,0 +1,397 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use clerk::{DefaultLines, Delay, Display, Init, Mcp230xx, Mcp230xxBus, Mcp230xxConnection,
+            McpDataPins, McpPins, McpReadPin, PinError, ReadMode, Receive, SeekFrom, Send,
+            WriteMode};
+
+/// A fake MCP230xx with an attached display, which latches data on the falling edge of the enable
+/// signal.
+struct BusMock<R = u8> {
+    expander: Mcp230xx,
+    pins: McpPins<R>,
+    iodir: Cell<u16>,
+    olat: Cell<u16>,
+    register_writes: RefCell<Vec<(u8, u8)>>,
+    pending_nibble: Cell<Option<u8>>,
+    send_bytes: RefCell<Vec<WriteMode>>,
+    receivable_bytes: RefCell<VecDeque<u8>>,
+    receivable_values: RefCell<VecDeque<u8>>,
+    disconnected: Cell<bool>,
+    failing_reads: Cell<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Disconnected;
+
+impl<R: McpReadPin> BusMock<R> {
+    fn new(expander: Mcp230xx, pins: McpPins<R>) -> Self {
+        BusMock {
+            expander,
+            pins,
+            iodir: Cell::new(0xffff),
+            olat: Cell::new(0),
+            register_writes: RefCell::new(vec![]),
+            pending_nibble: Cell::new(None),
+            send_bytes: RefCell::new(vec![]),
+            receivable_bytes: RefCell::new(VecDeque::new()),
+            receivable_values: RefCell::new(VecDeque::new()),
+            disconnected: Cell::new(false),
+            failing_reads: Cell::new(false),
+        }
+    }
+
+    fn set_read_value(&self, value: u8) {
+        self.receivable_bytes.borrow_mut().push_back(value);
+    }
+
+    fn data_pins(&self) -> Vec<u8> {
+        match self.pins.data {
+            McpDataPins::FourBit(pins) => pins.to_vec(),
+            McpDataPins::EightBit(pins) => pins.to_vec(),
+        }
+    }
+
+    fn is_set(value: u16, pin: u8) -> bool {
+        value & (1 << pin) != 0
+    }
+
+    fn is_read(&self, olat: u16) -> bool {
+        match self.pins.read.pin() {
+            Some(pin) => Self::is_set(olat, pin),
+            None => false,
+        }
+    }
+
+    fn data_value(&self, olat: u16) -> u8 {
+        self.data_pins()
+            .iter()
+            .enumerate()
+            .filter(|&(_, pin)| Self::is_set(olat, *pin))
+            .fold(0, |value, (i, _)| value | (1 << i))
+    }
+
+    fn latch(&self, olat: u16) {
+        let value = self.data_value(olat);
+
+        let value = match self.pins.data {
+            McpDataPins::EightBit(_) => value,
+            McpDataPins::FourBit(_) => match self.pending_nibble.take() {
+                None => {
+                    self.pending_nibble.set(Some(value));
+                    return;
+                }
+                Some(upper) => upper << 4 | value,
+            },
+        };
+
+        let mode = if Self::is_set(olat, self.pins.register_select) {
+            WriteMode::Data(value)
+        } else {
+            WriteMode::Command(value)
+        };
+
+        self.send_bytes.borrow_mut().push(mode);
+    }
+
+    fn prepare_read(&self) {
+        let mut values = self.receivable_values.borrow_mut();
+
+        if values.is_empty() {
+            let value = self.receivable_bytes.borrow_mut().pop_front().unwrap();
+
+            match self.pins.data {
+                McpDataPins::EightBit(_) => values.push_back(value),
+                McpDataPins::FourBit(_) => {
+                    values.push_back(value >> 4);
+                    values.push_back(value & 0x0f);
+                }
+            }
+        }
+    }
+
+    fn set_olat(&self, olat: u16) {
+        let previous = self.olat.replace(olat);
+        let enable = self.pins.enable;
+
+        let rising_edge = !Self::is_set(previous, enable) && Self::is_set(olat, enable);
+        let falling_edge = Self::is_set(previous, enable) && !Self::is_set(olat, enable);
+
+        if falling_edge && !self.is_read(olat) {
+            assert_eq!(self.iodir.get() & self.data_mask(), 0);
+            self.latch(olat);
+        }
+
+        if rising_edge && self.is_read(olat) {
+            self.prepare_read();
+        }
+
+        if falling_edge && self.is_read(olat) {
+            self.receivable_values.borrow_mut().pop_front();
+        }
+    }
+
+    fn data_mask(&self) -> u16 {
+        self.data_pins().iter().fold(0, |mask, pin| mask | 1 << pin)
+    }
+
+    fn gpio(&self) -> u16 {
+        let olat = self.olat.get();
+        let data_mask = self.data_mask();
+
+        if !Self::is_set(olat, self.pins.enable) || !self.is_read(olat) {
+            return olat;
+        }
+
+        assert_eq!(self.iodir.get() & data_mask, data_mask);
+
+        let value = *self.receivable_values.borrow().front().unwrap();
+
+        let data = self.data_pins()
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| value & (1 << i) != 0)
+            .fold(0, |port, (_, pin)| port | 1 << pin);
+
+        (olat & !data_mask) | data
+    }
+}
+
+fn replace_low(value: u16, low: u8) -> u16 {
+    (value & 0xff00) | u16::from(low)
+}
+
+fn replace_high(value: u16, high: u8) -> u16 {
+    (value & 0x00ff) | u16::from(high) << 8
+}
+
+impl<R: McpReadPin> Mcp230xxBus for &BusMock<R> {
+    type Error = Disconnected;
+
+    fn write_register(&self, register: u8, value: u8) -> Result<(), Disconnected> {
+        if self.disconnected.get() {
+            return Err(Disconnected);
+        }
+
+        self.register_writes.borrow_mut().push((register, value));
+
+        match (self.expander, register) {
+            (Mcp230xx::Mcp23008, 0x00) | (Mcp230xx::Mcp23017, 0x00) => {
+                self.iodir.set(replace_low(self.iodir.get(), value))
+            }
+            (Mcp230xx::Mcp23017, 0x01) => self.iodir.set(replace_high(self.iodir.get(), value)),
+            (Mcp230xx::Mcp23008, 0x0a) | (Mcp230xx::Mcp23017, 0x14) => {
+                self.set_olat(replace_low(self.olat.get(), value))
+            }
+            (Mcp230xx::Mcp23017, 0x15) => self.set_olat(replace_high(self.olat.get(), value)),
+            _ => panic!("unexpected register write: {:#04x}", register),
+        }
+
+        Ok(())
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8, Disconnected> {
+        if self.disconnected.get() || self.failing_reads.get() {
+            return Err(Disconnected);
+        }
+
+        match (self.expander, register) {
+            (Mcp230xx::Mcp23008, 0x09) | (Mcp230xx::Mcp23017, 0x12) => Ok(self.gpio() as u8),
+            (Mcp230xx::Mcp23017, 0x13) => Ok((self.gpio() >> 8) as u8),
+            _ => panic!("unexpected register read: {:#04x}", register),
+        }
+    }
+}
+
+pub struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+const EIGHT_BIT_PINS: McpPins = McpPins {
+    register_select: 8,
+    read: 9,
+    enable: 10,
+    backlight: Some(11),
+    data: McpDataPins::EightBit([0, 1, 2, 3, 4, 5, 6, 7]),
+};
+
+fn setup_connection<R: McpReadPin>(
+    bus: &BusMock<R>,
+) -> Mcp230xxConnection<&BusMock<R>, DelayMock, R> {
+    Mcp230xxConnection::new(bus, bus.expander, bus.pins, DelayMock).unwrap()
+}
+
+#[test]
+fn init_sets_up_registers() {
+    let bus = BusMock::new(Mcp230xx::Mcp23008, McpPins::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    let register_writes = bus.register_writes.borrow();
+    assert_eq!(*register_writes, vec![(0x0a, 0b1000_0000), (0x00, 0b0000_0001)]);
+}
+
+#[test]
+fn init_sets_up_both_ports() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, McpPins::ADAFRUIT_RGB_SHIELD);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    let register_writes = bus.register_writes.borrow();
+    assert_eq!(
+        *register_writes,
+        vec![(0x14, 0x00), (0x15, 0x00), (0x00, 0xff), (0x01, 0b0000_0001)]
+    );
+}
+
+#[test]
+fn send_4bit() {
+    let bus = BusMock::new(Mcp230xx::Mcp23008, McpPins::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+    connection.send(WriteMode::Command(0x28)).unwrap();
+    connection.send(WriteMode::Data(b'x')).unwrap();
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(
+        *send_bytes,
+        vec![WriteMode::Command(0x28), WriteMode::Data(b'x')]
+    );
+}
+
+#[test]
+fn send_8bit() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, EIGHT_BIT_PINS);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+    connection.send(WriteMode::Command(0x38)).unwrap();
+    connection.send(WriteMode::Data(0xa5)).unwrap();
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(
+        *send_bytes,
+        vec![WriteMode::Command(0x38), WriteMode::Data(0xa5)]
+    );
+}
+
+#[test]
+fn set_backlight() {
+    let bus = BusMock::new(Mcp230xx::Mcp23008, McpPins::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+    connection.set_backlight(false).unwrap();
+
+    let register_writes = bus.register_writes.borrow();
+    assert_eq!(register_writes.last(), Some(&(0x0a, 0x00)));
+}
+
+#[test]
+fn failed_write_is_repeated() {
+    let bus = BusMock::new(Mcp230xx::Mcp23008, McpPins::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    bus.disconnected.set(true);
+    assert_eq!(connection.set_backlight(false), Err(Disconnected));
+
+    bus.disconnected.set(false);
+    connection.set_backlight(false).unwrap();
+
+    let register_writes = bus.register_writes.borrow();
+    assert_eq!(register_writes.last(), Some(&(0x0a, 0x00)));
+}
+
+#[test]
+fn receive_4bit() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, McpPins::ADAFRUIT_RGB_SHIELD);
+    bus.set_read_value(0x9c);
+
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    assert_eq!(connection.receive(ReadMode::BusyFlag), Ok(0x9c));
+    assert_eq!(bus.iodir.get(), 0x01ff);
+}
+
+#[test]
+fn failed_receive_restores_outputs() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, McpPins::ADAFRUIT_RGB_SHIELD);
+    bus.set_read_value(0x9c);
+
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+    bus.failing_reads.set(true);
+
+    assert_eq!(connection.receive(ReadMode::BusyFlag), Err(Disconnected));
+    assert_eq!(bus.iodir.get(), 0x01ff);
+    assert!(!BusMock::<u8>::is_set(bus.olat.get(), bus.pins.enable));
+}
+
+#[test]
+fn receive_8bit() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, EIGHT_BIT_PINS);
+    bus.set_read_value(0x3c);
+
+    let connection = setup_connection(&bus);
+
+    connection.init().unwrap();
+
+    assert_eq!(connection.receive(ReadMode::Data), Ok(0x3c));
+
+    let register_writes = bus.register_writes.borrow();
+    assert!(register_writes.contains(&(0x00, 0xff)));
+    assert_eq!(register_writes.last(), Some(&(0x00, 0x00)));
+}
+
+#[test]
+fn display_write_and_read() {
+    let bus = BusMock::new(Mcp230xx::Mcp23017, EIGHT_BIT_PINS);
+    bus.set_read_value(b'O');
+
+    let connection = setup_connection(&bus);
+    connection.init().unwrap();
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(connection);
+
+    lcd.write_message("OK").unwrap();
+    lcd.seek(SeekFrom::Home(0)).unwrap();
+
+    assert_eq!(lcd.read_byte(), Ok(b'O'));
+
+    let send_bytes = bus.send_bytes.borrow();
+    assert_eq!(send_bytes[0], WriteMode::Data(b'O'));
+    assert_eq!(send_bytes[1], WriteMode::Data(b'K'));
+    assert_eq!(send_bytes[2], WriteMode::Command(0b1000_0000));
+}
+
+#[test]
+fn reject_pins_beyond_expander() {
+    let bus = BusMock::new(Mcp230xx::Mcp23008, EIGHT_BIT_PINS);
+    let result = Mcp230xxConnection::new(&bus, bus.expander, bus.pins, DelayMock);
+
+    assert_eq!(result.err(), Some(PinError::OutOfRange(8)));
+}
+
+#[test]
+fn reject_duplicate_pins() {
+    let pins = McpPins {
+        backlight: Some(12),
+        ..McpPins::ADAFRUIT_RGB_SHIELD
+    };
+    let bus = BusMock::new(Mcp230xx::Mcp23017, pins);
+    let result = Mcp230xxConnection::new(&bus, bus.expander, bus.pins, DelayMock);
+
+    assert_eq!(result.err(), Some(PinError::Duplicate(12)));
+}
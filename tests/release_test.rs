@@ -0,0 +1,241 @@
+extern crate clerk;
+
+use std::cell::Cell;
+use std::convert::Infallible;
+
+#[cfg(feature = "std")]
+use clerk::TraceRecorder;
+use clerk::{BacklightConnection, BusLine, BusSimulator, BusyFlagPolling, DataPins4Lines,
+            DefaultLines, Delay, Direction, Display, DisplayControlBuilder, DisplayHardwareLayer,
+            FunctionSetBuilder, Level, Pins, SharedBus, SimulatedPin, St7032Contrast};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+/// A simulated pin, which counts how often it has been cleaned up.
+struct TrackedPin<'a> {
+    pin: SimulatedPin<'a>,
+    cleanups: &'a Cell<u32>,
+    cleaned_up: Cell<bool>,
+}
+
+impl<'a> DisplayHardwareLayer for TrackedPin<'a> {
+    type Error = Infallible;
+
+    fn cleanup(&self) -> Result<(), Self::Error> {
+        self.cleanups.set(self.cleanups.get() + 1);
+        self.cleaned_up.set(true);
+        Ok(())
+    }
+
+    fn set_direction(&self, direction: Direction) -> Result<(), Self::Error> {
+        self.pin.set_direction(direction)
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), Self::Error> {
+        self.pin.set_level(level)
+    }
+
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        self.pin.get_value()
+    }
+}
+
+type TrackedPins<'a> = Pins<
+    TrackedPin<'a>,
+    TrackedPin<'a>,
+    TrackedPin<'a>,
+    DataPins4Lines<TrackedPin<'a>, TrackedPin<'a>, TrackedPin<'a>, TrackedPin<'a>>,
+>;
+
+fn tracked_pin<'a>(
+    bus: &'a BusSimulator,
+    line: BusLine,
+    cleanups: &'a Cell<u32>,
+) -> TrackedPin<'a> {
+    TrackedPin {
+        pin: bus.pin(line),
+        cleanups,
+        cleaned_up: Cell::new(false),
+    }
+}
+
+fn tracked_pins<'a>(bus: &'a BusSimulator, cleanups: &'a Cell<u32>) -> TrackedPins<'a> {
+    let pin = |line| tracked_pin(bus, line, cleanups);
+
+    Pins {
+        register_select: pin(BusLine::RegisterSelect),
+        read: pin(BusLine::ReadWrite),
+        enable: pin(BusLine::Enable),
+        data: DataPins4Lines {
+            data4: pin(BusLine::Data(4)),
+            data5: pin(BusLine::Data(5)),
+            data6: pin(BusLine::Data(6)),
+            data7: pin(BusLine::Data(7)),
+        },
+    }
+}
+
+fn display_is_on(bus: &BusSimulator) -> bool {
+    bus.display_control() & 0b0000_0100 != 0
+}
+
+#[test]
+fn release_cleans_up_all_pins() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(tracked_pins(&bus, &cleanups).into_connection(DelayMock));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+    lcd.write_message("Hi").unwrap();
+
+    let pins = lcd.release().unwrap();
+
+    assert_eq!(cleanups.get(), 7);
+    assert!(pins.register_select.cleaned_up.get());
+    assert!(pins.read.cleaned_up.get());
+    assert!(pins.enable.cleaned_up.get());
+    assert!(pins.data.data4.cleaned_up.get());
+    assert!(pins.data.data7.cleaned_up.get());
+    assert!(display_is_on(&bus));
+    assert_eq!(bus.ddram(0x00), b'H');
+}
+
+#[test]
+fn release_turns_display_off() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(tracked_pins(&bus, &cleanups).into_connection(DelayMock));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+    lcd.set_turn_off_on_release(true);
+
+    assert!(display_is_on(&bus));
+    lcd.release().unwrap();
+
+    assert_eq!(cleanups.get(), 7);
+    assert!(!display_is_on(&bus));
+}
+
+#[test]
+fn release_wrapped_connection() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let connection = tracked_pins(&bus, &cleanups).into_connection(DelayMock);
+    let lcd: Display<_, DefaultLines> = Display::new(BusyFlagPolling::new(connection));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    let pins = lcd.release().unwrap();
+
+    assert!(pins.enable.cleaned_up.get());
+    assert_eq!(cleanups.get(), 7);
+}
+
+#[test]
+fn release_backlight_connection() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let connection = tracked_pins(&bus, &cleanups).into_connection(DelayMock);
+    let backlight = tracked_pin(&bus, BusLine::Data(0), &cleanups);
+    let lcd: Display<_, DefaultLines> =
+        Display::new(BacklightConnection::new(connection, backlight));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    let (pins, backlight) = lcd.release().unwrap();
+
+    assert!(pins.enable.cleaned_up.get());
+    assert!(backlight.cleaned_up.get());
+    assert_eq!(cleanups.get(), 8);
+}
+
+#[test]
+fn release_st7032_connection() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let connection = tracked_pins(&bus, &cleanups).into_connection(DelayMock);
+    let function_set = FunctionSetBuilder::default();
+    let lcd: Display<_, DefaultLines> =
+        Display::new(St7032Contrast::new(connection, &function_set, false));
+
+    let pins = lcd.release().unwrap();
+
+    assert!(pins.read.cleaned_up.get());
+    assert_eq!(cleanups.get(), 7);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn release_tracing_connection() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let recorder = TraceRecorder::new();
+    let connection = tracked_pins(&bus, &cleanups).into_connection(DelayMock);
+    let lcd: Display<_, DefaultLines> = Display::new(recorder.connection(connection));
+
+    let pins = lcd.release().unwrap();
+
+    assert!(pins.data.data5.cleaned_up.get());
+    assert_eq!(cleanups.get(), 7);
+}
+
+#[test]
+fn release_shared_bus_connection_keeps_shared_pins() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let pins = tracked_pins(&bus, &cleanups);
+    let shared_bus = SharedBus::new(pins.register_select, pins.read, pins.data);
+    let lcd: Display<_, DefaultLines> =
+        Display::new(shared_bus.connection(pins.enable, DelayMock));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    let enable = lcd.release().unwrap();
+
+    assert!(enable.cleaned_up.get());
+    assert_eq!(cleanups.get(), 1);
+
+    let (register_select, _, data) = shared_bus.into_inner();
+    assert!(!register_select.cleaned_up.get());
+    assert!(!data.data4.cleaned_up.get());
+}
+
+#[test]
+fn release_on_drop() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    {
+        let mut lcd: Display<_, DefaultLines> =
+            Display::new(tracked_pins(&bus, &cleanups).into_connection(DelayMock));
+        lcd.set_turn_off_on_release(true);
+
+        let mut lcd = lcd.release_on_drop();
+        lcd.init(&FunctionSetBuilder::default()).unwrap();
+        lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+        lcd.write_message("Hi").unwrap();
+
+        assert_eq!(cleanups.get(), 0);
+        assert!(display_is_on(&bus));
+    }
+
+    assert_eq!(cleanups.get(), 7);
+    assert!(!display_is_on(&bus));
+    assert_eq!(bus.ddram(0x01), b'i');
+}
+
+#[test]
+fn into_inner_disarms_guard() {
+    let bus = BusSimulator::new();
+    let cleanups = Cell::new(0);
+    let lcd: Display<_, DefaultLines> =
+        Display::new(tracked_pins(&bus, &cleanups).into_connection(DelayMock));
+
+    {
+        let _lcd = lcd.release_on_drop().into_inner();
+    }
+
+    assert_eq!(cleanups.get(), 0);
+}
@@ -0,0 +1,192 @@
+extern crate clerk;
+
+use std::cell::RefCell;
+
+use clerk::{CursorBlinking, CursorState, DefaultLines, Delay, Display, DisplayControlBuilder,
+            DisplayState, FunctionSetBuilder, SeekFrom, SerialLcdConnection, SerialProtocol,
+            SerialWrite, SetFrom};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+#[derive(Default)]
+struct PortMock {
+    bytes: RefCell<Vec<u8>>,
+}
+
+impl SerialWrite for PortMock {
+    type Error = ();
+
+    fn write(&self, bytes: &[u8]) -> Result<(), ()> {
+        self.bytes.borrow_mut().extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+type SerialDisplay = Display<SerialLcdConnection<PortMock, DelayMock>, DefaultLines>;
+
+fn setup_display(protocol: SerialProtocol) -> SerialDisplay {
+    let connection = SerialLcdConnection::new(PortMock::default(), protocol, 16, DelayMock);
+    let lcd = Display::new(connection);
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    lcd
+}
+
+fn written_bytes(lcd: SerialDisplay) -> Vec<u8> {
+    lcd.get_connection().into_port().bytes.into_inner()
+}
+
+#[test]
+fn matrix_orbital_write_and_seek() {
+    let mut lcd = setup_display(SerialProtocol::MatrixOrbital);
+
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 3,
+    })
+    .unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x58, b'H', b'i', 0xfe, 0x47, 4, 2]
+    );
+}
+
+#[test]
+fn matrix_orbital_four_lines() {
+    let connection =
+        SerialLcdConnection::new(PortMock::default(), SerialProtocol::MatrixOrbital, 20, DelayMock);
+    let mut lcd: SerialDisplay = Display::new(connection);
+
+    lcd.seek(SeekFrom::Home(22)).unwrap();
+    lcd.seek(SeekFrom::Home(0x40 + 20)).unwrap();
+
+    assert_eq!(written_bytes(lcd), vec![0xfe, 0x47, 3, 3, 0xfe, 0x47, 1, 4]);
+}
+
+#[test]
+fn matrix_orbital_custom_character() {
+    let lcd = setup_display(SerialProtocol::MatrixOrbital);
+    let rows = [0x00, 0x0a, 0x0a, 0x00, 0x11, 0x0e, 0x00, 0x00];
+
+    let mut lcd = lcd.set_cgram_address(8).ok().unwrap();
+    for &row in &rows {
+        lcd.write(row).unwrap();
+    }
+    let mut lcd = lcd.set_ddram_address(SetFrom::Home(0)).ok().unwrap();
+    lcd.write(0x01).unwrap();
+
+    let mut expected = vec![0xfe, 0x58, 0xfe, 0x4e, 1];
+    expected.extend_from_slice(&rows);
+    expected.extend_from_slice(&[0xfe, 0x47, 1, 1, 0x01]);
+
+    assert_eq!(written_bytes(lcd), expected);
+}
+
+#[test]
+fn matrix_orbital_incomplete_character_is_flushed() {
+    let lcd = setup_display(SerialProtocol::MatrixOrbital);
+
+    let mut lcd = lcd.set_cgram_address(0).ok().unwrap();
+    lcd.write(0x1f).unwrap();
+    let lcd = lcd.set_ddram_address(SetFrom::Home(0)).ok().unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x58, 0xfe, 0x4e, 0, 0x1f, 0, 0, 0, 0, 0, 0, 0, 0xfe, 0x47, 1, 1]
+    );
+}
+
+#[test]
+fn matrix_orbital_display_control() {
+    let lcd = setup_display(SerialProtocol::MatrixOrbital);
+
+    let mut builder = DisplayControlBuilder::default();
+    builder.set_cursor(CursorState::On);
+    lcd.set_display_control(&builder).unwrap();
+    builder.set_cursor(CursorState::Off);
+    builder.set_cursor_blinking(CursorBlinking::On);
+    lcd.set_display_control(&builder).unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x58, 0xfe, 0x4a, 0xfe, 0x54, 0xfe, 0x4b, 0xfe, 0x53]
+    );
+}
+
+#[test]
+fn matrix_orbital_display_off_clears() {
+    let lcd = setup_display(SerialProtocol::MatrixOrbital);
+
+    let mut builder = DisplayControlBuilder::default();
+    builder.set_display(DisplayState::Off);
+    lcd.set_display_control(&builder).unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x58, 0xfe, 0x58, 0xfe, 0x4b, 0xfe, 0x54]
+    );
+}
+
+#[test]
+fn matrix_orbital_backlight_and_contrast() {
+    let lcd = setup_display(SerialProtocol::MatrixOrbital);
+
+    lcd.set_backlight(false).unwrap();
+    lcd.set_backlight(true).unwrap();
+    lcd.set_brightness(128).unwrap();
+    lcd.set_contrast(200).unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x58, 0xfe, 0x46, 0xfe, 0x42, 0, 0xfe, 0x99, 128, 0xfe, 0x50, 200]
+    );
+}
+
+#[test]
+fn serlcd_write_and_seek() {
+    let mut lcd = setup_display(SerialProtocol::SerLcd);
+
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 3,
+    })
+    .unwrap();
+
+    assert_eq!(written_bytes(lcd), vec![0xfe, 0x01, b'H', b'i', 0xfe, 0xc3]);
+}
+
+#[test]
+fn serlcd_backlight_and_contrast() {
+    let lcd = setup_display(SerialProtocol::SerLcd);
+
+    lcd.set_backlight(false).unwrap();
+    lcd.set_brightness(128).unwrap();
+    lcd.set_backlight(true).unwrap();
+    lcd.set_brightness(255).unwrap();
+    lcd.set_contrast(10).unwrap();
+
+    assert_eq!(
+        written_bytes(lcd),
+        vec![0xfe, 0x01, 0x7c, 128, 0x7c, 142, 0x7c, 157, 0x7c, 0x18, 10]
+    );
+}
+
+#[test]
+fn prefixes_are_substituted() {
+    let mut lcd = setup_display(SerialProtocol::MatrixOrbital);
+    lcd.write(0xfe).unwrap();
+    lcd.write(0x7c).unwrap();
+    assert_eq!(written_bytes(lcd), vec![0xfe, 0x58, b' ', 0x7c]);
+
+    let mut lcd = setup_display(SerialProtocol::SerLcd);
+    lcd.write(0xfe).unwrap();
+    lcd.write(0x7c).unwrap();
+    assert_eq!(written_bytes(lcd), vec![0xfe, 0x01, b' ', b' ']);
+}
@@ -0,0 +1,154 @@
+extern crate clerk;
+
+use std::cell::Cell;
+use std::convert::Infallible;
+
+use clerk::{BusLine, BusSimulator, DataPins4Lines, DefaultLines, Delay, Direction, Display,
+            DisplayHardwareLayer, FunctionSetBuilder, Init, Level, LineNumber, SeekFrom,
+            SharedBus};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+/// A line wired to the same bus line of all simulated displays.
+struct Wire<'a> {
+    displays: &'a [BusSimulator],
+    line: BusLine,
+}
+
+impl<'a> Wire<'a> {
+    fn new(displays: &'a [BusSimulator], line: BusLine) -> Self {
+        Wire { displays, line }
+    }
+}
+
+impl<'a> DisplayHardwareLayer for Wire<'a> {
+    type Error = Infallible;
+
+    fn set_direction(&self, _: Direction) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), Self::Error> {
+        for display in self.displays {
+            display.pin(self.line).set_level(level)?;
+        }
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        match self.displays.iter().find(|display| display.drives_data()) {
+            Some(display) => display.pin(self.line).get_value(),
+            None => Ok(0),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PinMock {
+    inits: Cell<u32>,
+}
+
+impl DisplayHardwareLayer for PinMock {
+    type Error = ();
+
+    fn init(&self) -> Result<(), Self::Error> {
+        self.inits.set(self.inits.get() + 1);
+        Ok(())
+    }
+
+    fn set_direction(&self, _: Direction) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_level(&self, _: Level) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, Self::Error> {
+        Ok(0)
+    }
+}
+
+fn function_set() -> FunctionSetBuilder {
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    builder
+}
+
+#[test]
+fn displays_on_shared_bus() {
+    let displays = [BusSimulator::new(), BusSimulator::new(), BusSimulator::new()];
+    let bus = SharedBus::new(
+        Wire::new(&displays, BusLine::RegisterSelect),
+        Wire::new(&displays, BusLine::ReadWrite),
+        DataPins4Lines {
+            data4: Wire::new(&displays, BusLine::Data(4)),
+            data5: Wire::new(&displays, BusLine::Data(5)),
+            data6: Wire::new(&displays, BusLine::Data(6)),
+            data7: Wire::new(&displays, BusLine::Data(7)),
+        },
+    );
+
+    let mut lcds: Vec<Display<_, DefaultLines>> = displays
+        .iter()
+        .map(|display| {
+            Display::new(bus.connection(display.pin(BusLine::Enable), &DelayMock))
+        })
+        .collect();
+
+    for lcd in &lcds {
+        lcd.init(&function_set()).unwrap();
+    }
+
+    lcds[0].write_message("one").unwrap();
+    lcds[1].write_message("two").unwrap();
+    lcds[2].seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 0,
+    }).unwrap();
+    lcds[2].write_message("three").unwrap();
+
+    assert_eq!(displays[0].ddram(0x00), b'o');
+    assert_eq!(displays[1].ddram(0x00), b't');
+    assert_eq!(displays[2].ddram(0x00), b' ');
+    assert_eq!(displays[2].ddram(0x40), b't');
+
+    assert_eq!(lcds[0].read_busy_flag(), Ok((false, 0x03)));
+    assert_eq!(lcds[2].read_busy_flag(), Ok((false, 0x45)));
+
+    lcds[1].seek(SeekFrom::Home(1)).unwrap();
+    assert_eq!(lcds[1].read_byte(), Ok(b'w'));
+    assert_eq!(displays[0].address_counter(), 0x03);
+}
+
+#[test]
+fn shared_pins_initialized_once() {
+    let bus = SharedBus::new(
+        PinMock::default(),
+        PinMock::default(),
+        DataPins4Lines {
+            data4: PinMock::default(),
+            data5: PinMock::default(),
+            data6: PinMock::default(),
+            data7: PinMock::default(),
+        },
+    );
+    let enables = [PinMock::default(), PinMock::default()];
+
+    for enable in &enables {
+        let connection = bus.connection(enable, DelayMock);
+        connection.init().unwrap();
+        connection.init().unwrap();
+    }
+
+    let (register_select, read, data) = bus.into_inner();
+    assert_eq!(register_select.inits.get(), 1);
+    assert_eq!(read.inits.get(), 1);
+    assert_eq!(data.data7.inits.get(), 1);
+    assert_eq!(enables[0].inits.get(), 2);
+    assert_eq!(enables[1].inits.get(), 2);
+}
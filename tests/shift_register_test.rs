@@ -0,0 +1,276 @@
+extern crate clerk;
+
+use std::cell::{Cell, RefCell};
+
+use clerk::{DefaultLines, Delay, Direction, Display, DisplayHardwareLayer, FunctionSetBuilder,
+            Init, Level, PinError, SeekFrom, Send, ShiftRegisterConnection, ShiftRegisterDataBits,
+            ShiftRegisterLayout, ShiftRegisterPins, WriteMode};
+
+/// A fake chain of 74HC595 shift registers with an attached display, which latches data on the
+/// falling edge of the enable signal.
+struct ShiftRegisterMock {
+    layout: ShiftRegisterLayout,
+    data: Cell<bool>,
+    shift_register: Cell<u16>,
+    storage_register: Cell<u16>,
+    latched_words: RefCell<Vec<u16>>,
+    pending_nibble: Cell<Option<u8>>,
+    send_bytes: RefCell<Vec<WriteMode>>,
+}
+
+impl ShiftRegisterMock {
+    fn new(layout: ShiftRegisterLayout) -> Self {
+        ShiftRegisterMock {
+            layout,
+            data: Cell::new(false),
+            shift_register: Cell::new(0),
+            storage_register: Cell::new(0),
+            latched_words: RefCell::new(vec![]),
+            pending_nibble: Cell::new(None),
+            send_bytes: RefCell::new(vec![]),
+        }
+    }
+
+    fn is_set(word: u16, bit: u8) -> bool {
+        word & (1 << bit) != 0
+    }
+
+    fn clock(&self) {
+        // a single shift register only holds 8 bits
+        let width_mask = if self.layout.enable < 8 { 0x00ff } else { 0xffff };
+
+        let word = self.shift_register.get() << 1 | self.data.get() as u16;
+        self.shift_register.set(word & width_mask);
+    }
+
+    fn latch(&self) {
+        let word = self.shift_register.get();
+        let previous = self.storage_register.replace(word);
+
+        self.latched_words.borrow_mut().push(word);
+
+        let enable = self.layout.enable;
+        if Self::is_set(previous, enable) && !Self::is_set(word, enable) {
+            self.latch_display(word);
+        }
+    }
+
+    fn latch_display(&self, word: u16) {
+        let value = match self.layout.data {
+            ShiftRegisterDataBits::FourBit(bits) => bits.to_vec(),
+            ShiftRegisterDataBits::EightBit(bits) => bits.to_vec(),
+        }.iter()
+            .enumerate()
+            .filter(|&(_, bit)| Self::is_set(word, *bit))
+            .fold(0u8, |value, (i, _)| value | (1 << i));
+
+        let value = match self.layout.data {
+            ShiftRegisterDataBits::EightBit(_) => value,
+            ShiftRegisterDataBits::FourBit(_) => match self.pending_nibble.take() {
+                None => {
+                    self.pending_nibble.set(Some(value));
+                    return;
+                }
+                Some(upper) => upper << 4 | value,
+            },
+        };
+
+        let mode = if Self::is_set(word, self.layout.register_select) {
+            WriteMode::Data(value)
+        } else {
+            WriteMode::Command(value)
+        };
+
+        self.send_bytes.borrow_mut().push(mode);
+    }
+}
+
+enum Role {
+    Data,
+    Clock,
+    Latch,
+}
+
+struct PinMock<'a> {
+    role: Role,
+    level: Cell<bool>,
+    shift_register: &'a ShiftRegisterMock,
+}
+
+impl<'a> PinMock<'a> {
+    fn new(role: Role, shift_register: &'a ShiftRegisterMock) -> Self {
+        PinMock {
+            role,
+            level: Cell::new(false),
+            shift_register,
+        }
+    }
+}
+
+impl<'a> DisplayHardwareLayer for PinMock<'a> {
+    type Error = ();
+
+    fn set_direction(&self, direction: Direction) -> Result<(), ()> {
+        assert_eq!(direction, Direction::Out);
+        Ok(())
+    }
+
+    fn set_level(&self, level: Level) -> Result<(), ()> {
+        let level = level == Level::High;
+        let rising_edge = !self.level.replace(level) && level;
+
+        match self.role {
+            Role::Data => self.shift_register.data.set(level),
+            Role::Clock if rising_edge => self.shift_register.clock(),
+            Role::Latch if rising_edge => self.shift_register.latch(),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn get_value(&self) -> Result<u8, ()> {
+        unreachable!("the shift register connection never reads its pins")
+    }
+}
+
+pub struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+type Connection<'a> = ShiftRegisterConnection<PinMock<'a>, PinMock<'a>, PinMock<'a>, DelayMock>;
+
+fn connect(
+    shift_register: &ShiftRegisterMock,
+    layout: ShiftRegisterLayout,
+) -> Result<Connection<'_>, PinError> {
+    let pins = ShiftRegisterPins {
+        data: PinMock::new(Role::Data, shift_register),
+        clock: PinMock::new(Role::Clock, shift_register),
+        latch: PinMock::new(Role::Latch, shift_register),
+    };
+
+    pins.into_connection(layout, DelayMock)
+}
+
+fn setup_connection(shift_register: &ShiftRegisterMock) -> Connection<'_> {
+    connect(shift_register, shift_register.layout).unwrap()
+}
+
+#[test]
+fn init_switches_backlight_on() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&shift_register);
+
+    connection.init().unwrap();
+
+    let latched_words = shift_register.latched_words.borrow();
+    assert_eq!(*latched_words, vec![0b1000_0000]);
+}
+
+#[test]
+fn send_4bit() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&shift_register);
+
+    connection.init().unwrap();
+    connection.send(WriteMode::Command(0x28)).unwrap();
+    connection.send(WriteMode::Data(b'k')).unwrap();
+
+    let send_bytes = shift_register.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Command(0x28), WriteMode::Data(b'k')]);
+}
+
+#[test]
+fn send_8bit_with_two_chips() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::TWO_CHIPS_8BIT);
+    let connection = setup_connection(&shift_register);
+
+    connection.init().unwrap();
+    connection.send(WriteMode::Data(0xc3)).unwrap();
+    connection.send(WriteMode::Command(0x38)).unwrap();
+
+    let send_bytes = shift_register.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Data(0xc3), WriteMode::Command(0x38)]);
+
+    let latched_words = shift_register.latched_words.borrow();
+    assert_eq!(latched_words[1], 0b0000_0101_1100_0011);
+    assert_eq!(latched_words[2], 0b0000_0111_1100_0011);
+}
+
+#[test]
+fn send_with_custom_layout() {
+    let layout = ShiftRegisterLayout {
+        register_select: 7,
+        enable: 6,
+        backlight: None,
+        data: ShiftRegisterDataBits::FourBit([0, 1, 2, 3]),
+    };
+
+    let shift_register = ShiftRegisterMock::new(layout);
+    let connection = setup_connection(&shift_register);
+
+    connection.init().unwrap();
+    connection.send(WriteMode::Data(0x4f)).unwrap();
+
+    let send_bytes = shift_register.send_bytes.borrow();
+    assert_eq!(*send_bytes, vec![WriteMode::Data(0x4f)]);
+
+    let latched_words = shift_register.latched_words.borrow();
+    assert_eq!(latched_words[1], 0b1000_0100);
+}
+
+#[test]
+fn set_backlight() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::ADAFRUIT_BACKPACK);
+    let connection = setup_connection(&shift_register);
+
+    connection.init().unwrap();
+    connection.set_backlight(false).unwrap();
+
+    let latched_words = shift_register.latched_words.borrow();
+    assert_eq!(latched_words.last(), Some(&0));
+}
+
+#[test]
+fn display_over_shift_register() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::ADAFRUIT_BACKPACK);
+    let mut lcd: Display<_, DefaultLines> = Display::new(setup_connection(&shift_register));
+
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 0,
+    }).unwrap();
+    lcd.write_message("hi").unwrap();
+
+    let send_bytes = shift_register.send_bytes.borrow();
+    assert_eq!(
+        send_bytes[send_bytes.len() - 3..],
+        [WriteMode::Command(0xc0), WriteMode::Data(b'h'), WriteMode::Data(b'i')]
+    );
+}
+
+#[test]
+fn reject_bits_beyond_two_chips() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::TWO_CHIPS_8BIT);
+    let layout = ShiftRegisterLayout {
+        backlight: Some(16),
+        ..ShiftRegisterLayout::TWO_CHIPS_8BIT
+    };
+
+    assert_eq!(connect(&shift_register, layout).err(), Some(PinError::OutOfRange(16)));
+}
+
+#[test]
+fn reject_duplicate_bits() {
+    let shift_register = ShiftRegisterMock::new(ShiftRegisterLayout::ADAFRUIT_BACKPACK);
+    let layout = ShiftRegisterLayout {
+        enable: 1,
+        ..ShiftRegisterLayout::ADAFRUIT_BACKPACK
+    };
+
+    assert_eq!(connect(&shift_register, layout).err(), Some(PinError::Duplicate(1)));
+}
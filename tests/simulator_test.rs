@@ -0,0 +1,217 @@
+extern crate clerk;
+
+use clerk::{BusLine, BusSimulator, DataLength, DefaultLines, Delay, Display,
+            DisplayControlBuilder, DisplayHardwareLayer, EntryModeBuilder, FunctionSetBuilder,
+            Level, LineNumber, SeekCgRamFrom, SeekFrom, Send, SetFrom, ShiftTo};
+
+struct DelayMock;
+
+impl Delay for DelayMock {
+    fn delay_ns(&self, _: u32) {}
+}
+
+fn four_bit() -> FunctionSetBuilder {
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+    builder
+}
+
+fn eight_bit() -> FunctionSetBuilder {
+    let mut builder = four_bit();
+    builder.set_data_length(DataLength::EightBit);
+    builder
+}
+
+#[test]
+fn init_4lines() {
+    let bus = BusSimulator::new();
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+
+    lcd.init(&four_bit()).unwrap();
+
+    assert_eq!(bus.data_length(), DataLength::FourBit);
+    assert_eq!(bus.line_number(), LineNumber::Two);
+    assert_eq!(bus.address_counter(), 0);
+}
+
+#[test]
+fn init_8lines() {
+    let bus = BusSimulator::new();
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_8lines().into_connection(DelayMock));
+
+    lcd.init(&eight_bit()).unwrap();
+
+    assert_eq!(bus.data_length(), DataLength::EightBit);
+    assert_eq!(bus.line_number(), LineNumber::Two);
+}
+
+#[test]
+fn write_and_read_4lines() {
+    let bus = BusSimulator::new();
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+    lcd.init(&four_bit()).unwrap();
+
+    lcd.write_message("Hi").unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 3,
+    }).unwrap();
+    lcd.write(b'!').unwrap();
+
+    assert_eq!(bus.ddram(0x00), b'H');
+    assert_eq!(bus.ddram(0x01), b'i');
+    assert_eq!(bus.ddram(0x43), b'!');
+    assert_eq!(lcd.read_busy_flag(), Ok((false, 0x44)));
+
+    lcd.seek(SeekFrom::Home(1)).unwrap();
+    assert_eq!(lcd.read_byte(), Ok(b'i'));
+    assert_eq!(lcd.read_byte(), Ok(b' '));
+    assert_eq!(bus.address_counter(), 0x03);
+}
+
+#[test]
+fn write_and_read_8lines() {
+    let bus = BusSimulator::new();
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_8lines().into_connection(DelayMock));
+    lcd.init(&eight_bit()).unwrap();
+
+    lcd.write_message("Hello").unwrap();
+    lcd.seek(SeekFrom::Home(3)).unwrap();
+
+    assert_eq!(lcd.read_byte(), Ok(b'l'));
+    assert_eq!(lcd.read_byte(), Ok(b'o'));
+    assert_eq!(lcd.read_busy_flag(), Ok((false, 0x05)));
+}
+
+#[test]
+fn cgram_4lines() {
+    let bus = BusSimulator::new();
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+    lcd.init(&four_bit()).unwrap();
+
+    let mut lcd = lcd.set_cgram_address(8).ok().unwrap();
+    lcd.write(0b1_0101).unwrap();
+    lcd.write(0b0_1010).unwrap();
+    lcd.seek(SeekCgRamFrom::Home(8)).unwrap();
+    assert_eq!(lcd.read_byte(), Ok(0b1_0101));
+
+    let mut lcd = lcd.set_ddram_address(SetFrom::Home(0)).ok().unwrap();
+    lcd.write(0x01).unwrap();
+
+    assert_eq!(bus.cgram(8), 0b1_0101);
+    assert_eq!(bus.cgram(9), 0b0_1010);
+    assert_eq!(bus.ddram(0), 0x01);
+}
+
+#[test]
+fn display_settings_8lines() {
+    let bus = BusSimulator::new();
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_8lines().into_connection(DelayMock));
+    lcd.init(&eight_bit()).unwrap();
+
+    lcd.set_display_control(&DisplayControlBuilder::default()).unwrap();
+    lcd.set_entry_mode(&EntryModeBuilder::default()).unwrap();
+    lcd.shift(ShiftTo::Left(2)).unwrap();
+    lcd.shift_cursor(ShiftTo::Right(3)).unwrap();
+
+    assert_eq!(bus.display_control(), 0b0000_0100);
+    assert_eq!(bus.display_shift(), 2);
+    assert_eq!(bus.address_counter(), 3);
+
+    lcd.clear().unwrap();
+    assert_eq!(bus.display_shift(), 0);
+    assert_eq!(bus.address_counter(), 0);
+}
+
+#[test]
+fn init_recovers_from_pending_nibble() {
+    let bus = BusSimulator::new();
+
+    let lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+    lcd.init(&four_bit()).unwrap();
+
+    // an MCU reset in the middle of a transfer leaves the controller waiting for the lower nibble
+    let connection = lcd.get_connection();
+    connection.send_upper_nibble(0b1100_0000).unwrap();
+
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+    lcd.init(&four_bit()).unwrap();
+    lcd.write_message("ok").unwrap();
+
+    assert_eq!(bus.data_length(), DataLength::FourBit);
+    assert_eq!(bus.ddram(0x00), b'o');
+    assert_eq!(bus.ddram(0x01), b'k');
+}
+
+#[test]
+fn read_back_lines_4lines() {
+    let bus = BusSimulator::new();
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_4lines().into_connection(DelayMock));
+    lcd.init(&four_bit()).unwrap();
+
+    lcd.write_message("first").unwrap();
+    lcd.seek(SeekFrom::Line {
+        line: DefaultLines::Two,
+        offset: 0,
+    }).unwrap();
+    lcd.write_message("second").unwrap();
+
+    let mut line = [0; 16];
+    assert_eq!(lcd.read_line(1, &mut line), Ok(16));
+    assert_eq!(&line, b"second          ");
+
+    let mut short = [0; 3];
+    assert_eq!(lcd.read_line(0, &mut short), Ok(3));
+    assert_eq!(&short, b"fir");
+    assert_eq!(lcd.read_line(2, &mut line), Ok(0));
+
+    // the cursor is restored behind "second"
+    lcd.write(b'!').unwrap();
+    assert_eq!(bus.ddram(0x46), b'!');
+    assert_eq!(bus.address_counter(), 0x47);
+}
+
+#[test]
+fn read_back_screen_8lines() {
+    let bus = BusSimulator::new();
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(bus.pins_8lines().into_connection(DelayMock));
+    lcd.init(&eight_bit()).unwrap();
+    lcd.set_geometry(20, 4);
+
+    for (offset, text) in [(0, "one"), (0x40, "two"), (20, "three"), (0x40 + 20, "four")] {
+        lcd.seek(SeekFrom::Home(offset)).unwrap();
+        lcd.write_message(text).unwrap();
+    }
+    lcd.seek(SeekFrom::Home(2)).unwrap();
+
+    let mut screen = [0; 100];
+    assert_eq!(lcd.read_screen(&mut screen), Ok(80));
+    assert_eq!(&screen[..20], b"one                 ");
+    assert_eq!(&screen[20..40], b"two                 ");
+    assert_eq!(&screen[40..60], b"three               ");
+    assert_eq!(&screen[60..80], b"four                ");
+
+    let mut partial = [0; 30];
+    assert_eq!(lcd.read_screen(&mut partial), Ok(30));
+    assert_eq!(&partial[20..], b"two       ");
+
+    assert_eq!(bus.address_counter(), 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "there is no data line D8")]
+fn reject_data_line_beyond_d7() {
+    let bus = BusSimulator::new();
+    let _ = bus.pin(BusLine::Data(8)).set_level(Level::High);
+}
@@ -0,0 +1,193 @@
+#![cfg(feature = "std")]
+
+extern crate clerk;
+
+use clerk::{check_timing, BusLine, BusSimulator, DataLength, DefaultLines, Delay, Display,
+            FunctionSetBuilder, Level, LineNumber, SeekFrom, Timing, TimingConstraints,
+            TraceRecorder, Transition, Violation, ViolationKind, VirtualDelay};
+
+fn transition(time: u64, line: BusLine, level: Level) -> Transition {
+    Transition { time, line, level }
+}
+
+#[test]
+fn display_4lines_meets_constraints() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(recorder.delay()));
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_line_number(LineNumber::Two);
+
+    lcd.init(&builder).unwrap();
+    lcd.write_message("Hello").unwrap();
+    lcd.seek(SeekFrom::Home(1)).unwrap();
+    assert_eq!(lcd.read_byte(), Ok(b'e'));
+
+    assert!(!recorder.transitions().is_empty());
+    assert_eq!(recorder.check(&TimingConstraints::default()), vec![]);
+}
+
+#[test]
+fn display_8lines_meets_constraints() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_8lines(bus.pins_8lines());
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(recorder.delay()));
+    let mut builder = FunctionSetBuilder::default();
+    builder.set_data_length(DataLength::EightBit);
+
+    lcd.init(&builder).unwrap();
+    lcd.write_message("Hello").unwrap();
+    lcd.clear().unwrap();
+    lcd.write(b'!').unwrap();
+
+    assert_eq!(bus.ddram(0), b'!');
+    assert_eq!(recorder.check(&TimingConstraints::VCC_5V), vec![]);
+}
+
+#[test]
+fn display_with_5v_timing() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(pins.into_connection(recorder.delay()).with_timing(Timing::VCC_5V));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.write(b'a').unwrap();
+
+    assert_eq!(bus.ddram(0), b'a');
+    assert_eq!(recorder.check(&TimingConstraints::VCC_5V), vec![]);
+
+    let violations = recorder.check(&TimingConstraints::VCC_3V);
+    assert!(violations.iter().any(|v| v.kind == ViolationKind::EnablePulseWidth));
+}
+
+#[test]
+fn display_with_short_enable_pulse() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+
+    let timing = Timing {
+        enable_pulse_width: 100,
+        ..Timing::default()
+    };
+    let mut lcd: Display<_, DefaultLines> =
+        Display::new(pins.into_connection(recorder.delay()).with_timing(timing));
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+    lcd.write(b'a').unwrap();
+
+    let pulses = recorder
+        .transitions()
+        .iter()
+        .filter(|t| t.line == BusLine::Enable && t.level == Level::Low)
+        .count();
+
+    let violations = recorder.check(&TimingConstraints::VCC_5V);
+    assert_eq!(violations.len(), pulses);
+    assert!(violations.iter().all(|v| v.kind == ViolationKind::EnablePulseWidth));
+    assert_eq!(violations[0].actual, 100);
+    assert_eq!(violations[0].required, 230);
+}
+
+#[test]
+fn setup_and_hold_violations() {
+    let trace = vec![
+        transition(1_000, BusLine::RegisterSelect, Level::High),
+        transition(1_020, BusLine::Enable, Level::High),
+        transition(1_450, BusLine::Data(7), Level::High),
+        transition(1_500, BusLine::Enable, Level::Low),
+        transition(1_505, BusLine::Data(7), Level::Low),
+        transition(1_510, BusLine::RegisterSelect, Level::Low),
+    ];
+
+    let violations = check_timing(&trace, &TimingConstraints::VCC_5V);
+
+    assert_eq!(
+        violations,
+        vec![
+            Violation {
+                kind: ViolationKind::AddressSetup,
+                time: 1_020,
+                actual: 20,
+                required: 40,
+            },
+            Violation {
+                kind: ViolationKind::DataSetup,
+                time: 1_500,
+                actual: 50,
+                required: 80,
+            },
+            Violation {
+                kind: ViolationKind::DataHold,
+                time: 1_505,
+                actual: 5,
+                required: 10,
+            },
+        ]
+    );
+}
+
+fn pulse(trace: &mut Vec<Transition>, time: u64) {
+    trace.push(transition(time, BusLine::Enable, Level::High));
+    trace.push(transition(time + 500, BusLine::Enable, Level::Low));
+}
+
+#[test]
+fn execution_time_violations() {
+    let mut trace = vec![];
+
+    // clear display
+    trace.push(transition(0, BusLine::Data(0), Level::High));
+    pulse(&mut trace, 1_000);
+    // a busy flag read is allowed at any time
+    trace.push(transition(1_600, BusLine::ReadWrite, Level::High));
+    pulse(&mut trace, 2_000);
+    trace.push(transition(2_600, BusLine::ReadWrite, Level::Low));
+    // a data write, which is started too early
+    trace.push(transition(3_000, BusLine::RegisterSelect, Level::High));
+    pulse(&mut trace, 3_100);
+    pulse(&mut trace, 4_000);
+
+    let violations = check_timing(&trace, &TimingConstraints::default());
+
+    assert_eq!(
+        violations,
+        vec![Violation {
+            kind: ViolationKind::ExecutionTime,
+            time: 4_000,
+            actual: 400,
+            required: 37_000,
+        }]
+    );
+}
+
+#[test]
+fn transitions_back_in_time() {
+    let trace = vec![
+        transition(1_000, BusLine::RegisterSelect, Level::High),
+        transition(500, BusLine::Enable, Level::High),
+        transition(0, BusLine::Enable, Level::Low),
+    ];
+
+    let violations = check_timing(&trace, &TimingConstraints::default());
+
+    assert!(violations.iter().any(|v| v.kind == ViolationKind::AddressSetup && v.actual == 0));
+    assert!(violations.iter().any(|v| v.kind == ViolationKind::EnablePulseWidth && v.actual == 0));
+}
+
+#[test]
+fn virtual_delays_have_own_clocks() {
+    let first = VirtualDelay::new();
+    let second = VirtualDelay::new();
+
+    first.delay_us(40);
+    second.delay_ns(270);
+
+    assert_eq!(first.now(), 40_000);
+    assert_eq!(second.now(), 270);
+}
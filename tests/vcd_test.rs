@@ -0,0 +1,126 @@
+#![cfg(feature = "std")]
+
+extern crate clerk;
+
+use clerk::{BusSimulator, DefaultLines, Display, FunctionSetBuilder, Operation, ReadMode,
+            TraceRecorder, WriteMode};
+
+fn write_vcd(recorder: &TraceRecorder) -> String {
+    let mut output = vec![];
+    recorder.write_vcd(&mut output).unwrap();
+
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn operations_are_recorded() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+    let connection = recorder.connection(pins.into_connection(recorder.delay()));
+
+    let mut lcd: Display<_, DefaultLines> = Display::new(connection);
+    lcd.write(b'a').unwrap();
+    lcd.read_busy_flag().unwrap();
+
+    let operations: Vec<Operation> = recorder
+        .operations()
+        .iter()
+        .map(|o| o.operation)
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            Operation::Send(WriteMode::Data(b'a')),
+            Operation::Wait(WriteMode::Data(b'a')),
+            Operation::Receive(ReadMode::BusyFlag),
+        ]
+    );
+}
+
+#[test]
+fn vcd_header() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+
+    let lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(recorder.delay()));
+    lcd.clear().unwrap();
+
+    let vcd = write_vcd(&recorder);
+    let header: Vec<&str> = vcd.lines().take_while(|l| *l != "#0").collect();
+
+    assert_eq!(
+        header,
+        vec![
+            concat!("$version clerk ", env!("CARGO_PKG_VERSION"), " $end"),
+            "$timescale 1ns $end",
+            "$scope module lcd $end",
+            "$var wire 1 ! RS $end",
+            "$var wire 1 \" RW $end",
+            "$var wire 1 # E $end",
+            "$var wire 1 ( D4 $end",
+            "$var wire 1 ) D5 $end",
+            "$var wire 1 * D6 $end",
+            "$var wire 1 + D7 $end",
+            "$var string 1 , operation $end",
+            "$upscope $end",
+            "$enddefinitions $end",
+        ]
+    );
+}
+
+#[test]
+fn vcd_value_changes() {
+    let bus = BusSimulator::new();
+    let recorder = TraceRecorder::new();
+    let pins = recorder.pins_8lines(bus.pins_8lines());
+    let connection = recorder.connection(pins.into_connection(recorder.delay()));
+
+    let lcd: Display<_, DefaultLines> = Display::new(connection);
+    lcd.init(&FunctionSetBuilder::default()).unwrap();
+
+    let vcd = write_vcd(&recorder);
+    let changes: Vec<&str> = vcd
+        .lines()
+        .skip_while(|l| *l != "$dumpvars")
+        .take(21)
+        .collect();
+
+    assert_eq!(
+        changes,
+        vec![
+            "$dumpvars", "0!", "0\"", "0#", "0$", "0%", "0&", "0'", "0(", "0)", "0*", "0+",
+            "$end",
+            "sinit ,",
+            "swait_40000us ,",
+            "#40000000",
+            "supper_nibble_0x3 ,",
+            "#40000060",
+            "1#",
+            "1(",
+            "1)",
+        ]
+    );
+
+    let times: Vec<u64> = vcd
+        .lines()
+        .filter(|l| l.starts_with('#'))
+        .map(|l| l[1..].parse().unwrap())
+        .collect();
+    assert!(times.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn real_time_recording() {
+    let recorder = TraceRecorder::with_real_time();
+    let bus = BusSimulator::new();
+    let pins = recorder.pins_4lines(bus.pins_4lines());
+
+    let lcd: Display<_, DefaultLines> = Display::new(pins.into_connection(recorder.delay()));
+    lcd.clear().unwrap();
+
+    let transitions = recorder.transitions();
+    assert!(!transitions.is_empty());
+    assert!(transitions.windows(2).all(|w| w[0].time <= w[1].time));
+}